@@ -0,0 +1,64 @@
+//! Hashing and normalization helpers shared by
+//! [`Schema::content_hash`][crate::Schema::content_hash],
+//! [`ExecutableDocument::operation_hash`][crate::ExecutableDocument::operation_hash],
+//! and persisted query IDs
+
+use crate::ast;
+use crate::Node;
+use sha2::Digest;
+use sha2::Sha256;
+use std::fmt::Write;
+
+/// Returns the lowercase hexadecimal SHA-256 hash of `data`
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    let hash = Sha256::digest(data);
+    let mut hex = String::with_capacity(hash.len() * 2);
+    for byte in hash {
+        write!(hex, "{byte:02x}").unwrap()
+    }
+    hex
+}
+
+/// Sorts arguments by name and normalizes their values
+pub(crate) fn normalize_arguments(arguments: &mut [Node<ast::Argument>]) {
+    for argument in arguments.iter_mut() {
+        normalize_value(argument.make_mut().value.make_mut())
+    }
+    arguments.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+}
+
+/// Sorts input object fields by name and formats floats consistently,
+/// so that equivalent values serialize the same way
+pub(crate) fn normalize_value(value: &mut ast::Value) {
+    match value {
+        ast::Value::Float(float) => {
+            if let Ok(float) = float.try_to_f64() {
+                *value = ast::Value::Float(float.into())
+            }
+        }
+        ast::Value::List(list) => {
+            for item in list {
+                normalize_value(item.make_mut())
+            }
+        }
+        ast::Value::Object(object) => {
+            for (_, field_value) in object.iter_mut() {
+                normalize_value(field_value.make_mut())
+            }
+            object.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()))
+        }
+        ast::Value::Null
+        | ast::Value::Enum(_)
+        | ast::Value::Variable(_)
+        | ast::Value::String(_)
+        | ast::Value::Boolean(_)
+        | ast::Value::Int(_) => {}
+    }
+}
+
+/// Normalizes arguments of directive applications, keeping the order of directives
+pub(crate) fn normalize_ast_directives(directives: &mut ast::DirectiveList) {
+    for directive in directives.iter_mut() {
+        normalize_arguments(&mut directive.make_mut().arguments)
+    }
+}
@@ -0,0 +1,119 @@
+use super::signature::collect_fragments;
+use super::*;
+use crate::collections::IndexSet;
+use crate::content_hash::normalize_arguments;
+use crate::content_hash::normalize_ast_directives;
+use crate::content_hash::normalize_value;
+use crate::content_hash::sha256_hex;
+
+impl ExecutableDocument {
+    /// Returns a content hash of an operation, for example as part of query plan cache keys.
+    ///
+    /// The operation is selected by `operation_name` as with [`OperationMap::get`].
+    /// The hash is the lowercase hexadecimal SHA-256 of a canonical serialization
+    /// of the operation and the fragments it uses, with fragment definitions sorted by name.
+    /// Variable definitions, arguments, and input object fields are sorted by name
+    /// since their order is not significant.
+    /// Everything else that affects execution is kept as-is, including
+    /// the order of selections and directives, aliases, and argument values.
+    ///
+    /// As a result the hash does not depend on whitespace, comments,
+    /// unused fragments or operations, or definition order,
+    /// and it is stable across process runs.
+    /// Changes to the canonical serialization are considered breaking changes of this crate.
+    ///
+    /// The document is expected to be [valid][crate::validation::Valid]
+    /// but this is not checked.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use apollo_compiler::ExecutableDocument;
+    /// use apollo_compiler::Schema;
+    ///
+    /// let schema = Schema::parse_and_validate(r#"
+    ///     type Query { user(id: ID!, full: Boolean): User }
+    ///     type User { name: String }
+    /// "#, "schema.graphql").unwrap();
+    /// let document_1 = ExecutableDocument::parse_and_validate(&schema, r#"
+    ///     { user(id: 1, full: true) { ...F } }
+    ///     fragment F on User { name }
+    /// "#, "query.graphql").unwrap();
+    /// let document_2 = ExecutableDocument::parse_and_validate(&schema, r#"
+    ///     fragment F on User { name } # Comment
+    ///     { user(full: true, id: 1) { ...F } }
+    /// "#, "query.graphql").unwrap();
+    /// assert_eq!(
+    ///     document_1.operation_hash(None).unwrap(),
+    ///     document_2.operation_hash(None).unwrap(),
+    /// );
+    /// ```
+    pub fn operation_hash(
+        &self,
+        operation_name: Option<&str>,
+    ) -> Result<String, GetOperationError> {
+        let operation = self.operations.get(operation_name)?;
+        let mut fragment_names = IndexSet::default();
+        collect_fragments(self, &operation.selection_set, &mut fragment_names);
+        let mut fragments = fragment_names
+            .iter()
+            .filter_map(|name| self.fragments.get(*name))
+            .collect::<Vec<_>>();
+        fragments.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+
+        let mut document = ast::Document::new();
+        document
+            .definitions
+            .extend(fragments.iter().map(|fragment| fragment.to_ast(None)));
+        document.definitions.push(operation.to_ast(None));
+        for definition in &mut document.definitions {
+            match definition {
+                ast::Definition::OperationDefinition(operation) => {
+                    let operation = operation.make_mut();
+                    operation
+                        .variables
+                        .sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+                    for variable in &mut operation.variables {
+                        let variable = variable.make_mut();
+                        if let Some(default) = &mut variable.default_value {
+                            normalize_value(default.make_mut())
+                        }
+                        normalize_ast_directives(&mut variable.directives)
+                    }
+                    normalize_ast_directives(&mut operation.directives);
+                    normalize_selection_set(&mut operation.selection_set)
+                }
+                ast::Definition::FragmentDefinition(fragment) => {
+                    let fragment = fragment.make_mut();
+                    normalize_ast_directives(&mut fragment.directives);
+                    normalize_selection_set(&mut fragment.selection_set)
+                }
+                _ => {}
+            }
+        }
+        Ok(sha256_hex(
+            document.serialize().no_indent().to_string().as_bytes(),
+        ))
+    }
+}
+
+fn normalize_selection_set(selections: &mut [ast::Selection]) {
+    for selection in selections {
+        match selection {
+            ast::Selection::Field(field) => {
+                let field = field.make_mut();
+                normalize_arguments(&mut field.arguments);
+                normalize_ast_directives(&mut field.directives);
+                normalize_selection_set(&mut field.selection_set)
+            }
+            ast::Selection::FragmentSpread(spread) => {
+                normalize_ast_directives(&mut spread.make_mut().directives)
+            }
+            ast::Selection::InlineFragment(inline) => {
+                let inline = inline.make_mut();
+                normalize_ast_directives(&mut inline.directives);
+                normalize_selection_set(&mut inline.selection_set)
+            }
+        }
+    }
+}
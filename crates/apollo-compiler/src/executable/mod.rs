@@ -19,8 +19,10 @@ use std::fmt;
 use std::path::Path;
 use std::sync::Arc;
 
+mod content_hash;
 pub(crate) mod from_ast;
 mod serialize;
+mod signature;
 pub(crate) mod validation;
 
 pub use crate::ast::Argument;
@@ -325,6 +327,47 @@ impl ExecutableDocument {
         errors.into_valid_result(self)
     }
 
+    /// Like [`validate`][Self::validate], but additionally reject selections of fields
+    /// and type conditions on types that are hidden by `visibility`,
+    /// such as with the [schema visibility][crate::execution::Executor::schema_visibility]
+    /// used to execute the document.
+    ///
+    /// Errors are the same as for fields and types that are not defined in the schema,
+    /// so that they don’t reveal the existence of hidden elements.
+    #[allow(clippy::result_large_err)] // same as `validate`
+    pub fn validate_with_visibility(
+        self,
+        schema: &Valid<Schema>,
+        visibility: &dyn schema::SchemaVisibility,
+    ) -> Result<Valid<Self>, WithErrors<Self>> {
+        let mut sources = IndexMap::clone(&schema.sources);
+        sources.extend(self.sources.iter().map(|(k, v)| (*k, v.clone())));
+        let mut errors = DiagnosticList::new(Arc::new(sources));
+        validation::validate_executable_document(&mut errors, schema, &self);
+        crate::validation::visibility::validate_visibility(&mut errors, schema, &self, visibility);
+        errors.into_valid_result(self)
+    }
+
+    /// Like [`validate`][Self::validate], but additionally check that each operation
+    /// stays within the given limits on its depth, aliases, root fields,
+    /// directives per field, and total number of fields.
+    ///
+    /// Each exceeded limit is a diagnostic with its own kind,
+    /// located at the operation or at the relevant field.
+    #[allow(clippy::result_large_err)] // same as `validate`
+    pub fn validate_with_limits(
+        self,
+        schema: &Valid<Schema>,
+        limits: &crate::validation::OperationLimits,
+    ) -> Result<Valid<Self>, WithErrors<Self>> {
+        let mut sources = IndexMap::clone(&schema.sources);
+        sources.extend(self.sources.iter().map(|(k, v)| (*k, v.clone())));
+        let mut errors = DiagnosticList::new(Arc::new(sources));
+        validation::validate_executable_document(&mut errors, schema, &self);
+        crate::validation::operation_limits::validate_operation_limits(&mut errors, &self, limits);
+        errors.into_valid_result(self)
+    }
+
     serialize_method!();
 }
 
@@ -0,0 +1,198 @@
+use super::*;
+use crate::collections::IndexSet;
+
+impl ExecutableDocument {
+    /// Returns a normalized signature of an operation, for example to aggregate
+    /// usage metrics of operations that only differ in formatting or literal values.
+    ///
+    /// The operation is selected by `operation_name` as with
+    /// [`OperationMap::get`]. In the signature:
+    ///
+    /// * Only the operation and the fragments it uses are kept,
+    ///   with fragment definitions sorted by name before the operation
+    /// * Literal values are hidden: numbers become `0`, strings `""`, lists `[]`,
+    ///   and input objects `{}`. Variables, enum values, booleans, and `null` are kept.
+    /// * Aliases are removed
+    /// * Selections are sorted: fields by name, then fragment spreads by name,
+    ///   then inline fragments by type condition.
+    ///   Arguments, directives, and variable definitions are sorted by name.
+    /// * Whitespace is removed except where needed between two names
+    ///
+    /// The signature is not necessarily a valid document:
+    /// for example, removing aliases can cause conflicting fields.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use apollo_compiler::ExecutableDocument;
+    /// use apollo_compiler::Schema;
+    ///
+    /// let schema = Schema::parse_and_validate(r#"
+    ///     type Query { a: Int, user(id: ID!): User }
+    ///     type User { name: String }
+    /// "#, "schema.graphql").unwrap();
+    /// let source = r#"
+    ///     query Q { b: user(id: "42") { name } a }
+    /// "#;
+    /// let document = ExecutableDocument::parse(&schema, source, "query.graphql").unwrap();
+    /// assert_eq!(
+    ///     document.operation_signature(Some("Q")).unwrap(),
+    ///     r#"query Q{a user(id:""){name}}"#,
+    /// );
+    /// ```
+    pub fn operation_signature(
+        &self,
+        operation_name: Option<&str>,
+    ) -> Result<String, GetOperationError> {
+        let operation = self.operations.get(operation_name)?;
+        let mut fragment_names = IndexSet::default();
+        collect_fragments(self, &operation.selection_set, &mut fragment_names);
+        let mut fragments = fragment_names
+            .iter()
+            .filter_map(|name| self.fragments.get(*name))
+            .collect::<Vec<_>>();
+        fragments.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+
+        let mut document = ast::Document::new();
+        document
+            .definitions
+            .extend(fragments.iter().map(|fragment| fragment.to_ast(None)));
+        document.definitions.push(operation.to_ast(None));
+        for definition in &mut document.definitions {
+            match definition {
+                ast::Definition::OperationDefinition(operation) => {
+                    let operation = operation.make_mut();
+                    operation
+                        .variables
+                        .sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+                    for variable in &mut operation.variables {
+                        let variable = variable.make_mut();
+                        if let Some(default) = &mut variable.default_value {
+                            hide_literals(default.make_mut())
+                        }
+                        normalize_directives(&mut variable.directives)
+                    }
+                    normalize_directives(&mut operation.directives);
+                    normalize_selection_set(&mut operation.selection_set)
+                }
+                ast::Definition::FragmentDefinition(fragment) => {
+                    let fragment = fragment.make_mut();
+                    normalize_directives(&mut fragment.directives);
+                    normalize_selection_set(&mut fragment.selection_set)
+                }
+                _ => {}
+            }
+        }
+        Ok(reduce_whitespace(
+            &document.serialize().no_indent().to_string(),
+        ))
+    }
+}
+
+/// Collects names of fragments used by `selection_set`, directly or through other fragments
+pub(super) fn collect_fragments<'doc>(
+    document: &'doc ExecutableDocument,
+    selection_set: &'doc SelectionSet,
+    names: &mut IndexSet<&'doc Name>,
+) {
+    for selection in &selection_set.selections {
+        match selection {
+            Selection::Field(field) => collect_fragments(document, &field.selection_set, names),
+            Selection::InlineFragment(inline) => {
+                collect_fragments(document, &inline.selection_set, names)
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = document.fragments.get(&spread.fragment_name) {
+                    // Checking for a new name also prevents infinite recursion in invalid documents
+                    if names.insert(&spread.fragment_name) {
+                        collect_fragments(document, &fragment.selection_set, names)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn normalize_selection_set(selections: &mut [ast::Selection]) {
+    for selection in selections.iter_mut() {
+        match selection {
+            ast::Selection::Field(field) => {
+                let field = field.make_mut();
+                field.alias = None;
+                normalize_arguments(&mut field.arguments);
+                normalize_directives(&mut field.directives);
+                normalize_selection_set(&mut field.selection_set)
+            }
+            ast::Selection::FragmentSpread(spread) => {
+                normalize_directives(&mut spread.make_mut().directives)
+            }
+            ast::Selection::InlineFragment(inline) => {
+                let inline = inline.make_mut();
+                normalize_directives(&mut inline.directives);
+                normalize_selection_set(&mut inline.selection_set)
+            }
+        }
+    }
+    selections.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+}
+
+fn sort_key(selection: &ast::Selection) -> (u8, &str) {
+    match selection {
+        ast::Selection::Field(field) => (0, &field.name),
+        ast::Selection::FragmentSpread(spread) => (1, &spread.fragment_name),
+        ast::Selection::InlineFragment(inline) => (
+            2,
+            inline
+                .type_condition
+                .as_ref()
+                .map_or("", |type_condition| type_condition.as_str()),
+        ),
+    }
+}
+
+fn normalize_directives(directives: &mut ast::DirectiveList) {
+    for directive in directives.iter_mut() {
+        normalize_arguments(&mut directive.make_mut().arguments)
+    }
+    directives.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+}
+
+fn normalize_arguments(arguments: &mut [Node<ast::Argument>]) {
+    for argument in arguments.iter_mut() {
+        hide_literals(argument.make_mut().value.make_mut())
+    }
+    arguments.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+}
+
+fn hide_literals(value: &mut ast::Value) {
+    match value {
+        ast::Value::Int(_) | ast::Value::Float(_) => *value = ast::Value::Int(0.into()),
+        ast::Value::String(string) => string.clear(),
+        ast::Value::List(list) => list.clear(),
+        ast::Value::Object(object) => object.clear(),
+        ast::Value::Null
+        | ast::Value::Enum(_)
+        | ast::Value::Variable(_)
+        | ast::Value::Boolean(_) => {}
+    }
+}
+
+/// Removes whitespace except between two name characters.
+/// Assumes string literals have been hidden, as they could contain significant whitespace.
+fn reduce_whitespace(source: &str) -> String {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut output = String::with_capacity(source.len());
+    let mut pending_space = false;
+    for c in source.chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && is_name_char(c) && output.ends_with(is_name_char) {
+            output.push(' ')
+        }
+        pending_space = false;
+        output.push(c)
+    }
+    output
+}
@@ -35,6 +35,7 @@ fn validate_with_schema(
         crate::validation::operation::validate_subscription(document, operation, errors);
         fields_in_set_can_merge.validate_operation(operation, errors);
     }
+    crate::validation::defer_stream::validate_defer_stream(errors, schema, document);
 }
 
 pub(crate) fn validate_with_or_without_schema(
@@ -0,0 +1,358 @@
+//! Asynchronous counterpart of `engine` and `result_coercion`,
+//! used with [`AsyncResolver`][crate::execution::AsyncResolver].
+
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Field;
+use crate::executable::Selection;
+use crate::execution::batch::is_batchable;
+use crate::execution::batch::AsyncBatchedList;
+use crate::execution::engine::collect_fields;
+use crate::execution::engine::try_nullify;
+use crate::execution::engine::ExecutionMode;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::LinkedPathElement;
+use crate::execution::engine::PropagateNull;
+use crate::execution::input_coercion::coerce_argument_values;
+use crate::execution::middleware::FieldMiddlewareCall;
+use crate::execution::resolver::AsyncObjectValue;
+use crate::execution::resolver::AsyncResolvedValue;
+use crate::execution::resolver::ResolverError;
+use crate::execution::result_coercion::complete_leaf_value;
+use crate::execution::result_coercion::output_type_def;
+use crate::execution::result_coercion::resolved_object_type;
+use crate::execution::Executor;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::ResponseDataPathElement;
+use crate::schema::FieldDefinition;
+use crate::schema::ObjectType;
+use crate::schema::Type;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
+use crate::Schema;
+use futures::future::join_all;
+use futures::future::BoxFuture;
+
+/// <https://spec.graphql.org/October2021/#ExecuteSelectionSet()>
+///
+/// In normal mode, the futures of sibling fields are awaited concurrently.
+/// In sequential mode, each field is resolved and completed before starting the next one.
+#[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+pub(crate) fn execute_selection_set<'a: 'b, 'b>(
+    executor: &'a Executor,
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    variable_values: &'a Valid<JsonMap>,
+    errors: &'b mut Vec<GraphQLError>,
+    path: LinkedPath<'b>,
+    mode: ExecutionMode,
+    object_type: &'a ObjectType,
+    object_value: &'a AsyncObjectValue<'a>,
+    selections: impl IntoIterator<Item = &'a Selection>,
+) -> BoxFuture<'b, Result<JsonMap, PropagateNull>> {
+    let mut grouped_field_set = IndexMap::with_hasher(Default::default());
+    collect_fields(
+        schema,
+        document,
+        variable_values,
+        object_type,
+        selections,
+        &mut HashSet::default(),
+        &mut grouped_field_set,
+        None,
+    );
+    Box::pin(async move {
+        let field_paths: Vec<_> = grouped_field_set
+            .keys()
+            .map(|&response_key| LinkedPathElement {
+                element: ResponseDataPathElement::Field(response_key.clone()),
+                next: path,
+            })
+            .collect();
+        // Each field gets its own list so that futures can run concurrently.
+        // Errors are then reported in field order, regardless of completion order.
+        let mut field_errors: Vec<Vec<GraphQLError>> = vec![Vec::new(); field_paths.len()];
+        let field_futures = grouped_field_set
+            .iter()
+            .zip(&field_paths)
+            .zip(&mut field_errors)
+            .map(
+                |(((&response_key, fields), field_path), errors)| async move {
+                    // Indexing should not panic: `collect_fields` only creates a `Vec` to push to it
+                    let field_name = &fields[0].name;
+                    let Ok(field_def) = schema.type_field(&object_type.name, field_name) else {
+                        // TODO: Return a `validation_bug`` field error here?
+                        return None;
+                    };
+                    let value = if field_name == "__typename" {
+                        Ok(JsonValue::from(object_type.name.as_str()))
+                    } else {
+                        execute_field(
+                            executor,
+                            schema,
+                            document,
+                            variable_values,
+                            errors,
+                            Some(field_path),
+                            &object_type.name,
+                            object_value,
+                            field_def,
+                            fields,
+                        )
+                        .await
+                    };
+                    Some((response_key, value))
+                },
+            );
+        let results = match mode {
+            ExecutionMode::Normal => join_all(field_futures).await,
+            ExecutionMode::Sequential => {
+                let mut results = Vec::with_capacity(field_paths.len());
+                for field_future in field_futures {
+                    let result = field_future.await;
+                    let propagate_null = matches!(result, Some((_, Err(PropagateNull))));
+                    results.push(result);
+                    if propagate_null {
+                        // Like in the synchronous engine, don’t start remaining fields
+                        break;
+                    }
+                }
+                results
+            }
+        };
+        errors.extend(field_errors.into_iter().flatten());
+        let mut response_map = JsonMap::with_capacity(results.len());
+        for (response_key, value) in results.into_iter().flatten() {
+            response_map.insert(response_key.as_str(), value?);
+        }
+        Ok(response_map)
+    })
+}
+
+/// <https://spec.graphql.org/October2021/#ExecuteField()>
+#[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+async fn execute_field<'a: 'b, 'b>(
+    executor: &'a Executor,
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    variable_values: &'a Valid<JsonMap>,
+    errors: &'b mut Vec<GraphQLError>,
+    path: LinkedPath<'b>,
+    parent_type: &'a Name,
+    object_value: &'a AsyncObjectValue<'a>,
+    field_def: &'a FieldDefinition,
+    fields: &'a [&'a Node<Field>],
+) -> Result<JsonValue, PropagateNull> {
+    let field = fields[0];
+    let argument_values = match coerce_argument_values(
+        executor,
+        schema,
+        document,
+        variable_values,
+        errors,
+        path,
+        field_def,
+        field,
+    ) {
+        Ok(argument_values) => argument_values,
+        Err(PropagateNull) => return try_nullify(&field_def.ty, Err(PropagateNull)),
+    };
+    let middleware =
+        FieldMiddlewareCall::new(executor, parent_type, field, &argument_values, path, errors);
+    let short_circuit = middleware
+        .as_ref()
+        .and_then(|middleware| middleware.before(errors, &document.sources));
+    let completed_result = if let Some(result) = short_circuit {
+        result
+    } else {
+        let resolved_result = object_value
+            .resolve_field(&field.name, &argument_values)
+            .await;
+        match resolved_result {
+            Ok(resolved) => {
+                complete_value(
+                    executor,
+                    schema,
+                    document,
+                    variable_values,
+                    errors,
+                    path,
+                    field.ty(),
+                    resolved,
+                    fields,
+                )
+                .await
+            }
+            Err(ResolverError { message }) => {
+                errors.push(GraphQLError::field_error(
+                    format!("resolver error: {message}"),
+                    path,
+                    field.name.location(),
+                    &document.sources,
+                ));
+                Err(PropagateNull)
+            }
+        }
+    };
+    let completed_result = match middleware {
+        Some(middleware) => {
+            middleware.after(completed_result, field.ty(), errors, &document.sources)
+        }
+        None => completed_result,
+    };
+    try_nullify(&field_def.ty, completed_result)
+}
+
+/// <https://spec.graphql.org/October2021/#CompleteValue()>
+///
+/// Returns `Err` for a field error being propagated upwards to find a nullable place
+#[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+fn complete_value<'a: 'b, 'b>(
+    executor: &'a Executor,
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    variable_values: &'a Valid<JsonMap>,
+    errors: &'b mut Vec<GraphQLError>,
+    path: LinkedPath<'b>,
+    ty: &'a Type,
+    resolved: AsyncResolvedValue<'a>,
+    fields: &'a [&'a Node<Field>],
+) -> BoxFuture<'b, Result<JsonValue, PropagateNull>> {
+    Box::pin(async move {
+        let location = fields[0].name.location();
+        macro_rules! field_error {
+            ($($arg: tt)+) => {
+                {
+                    errors.push(GraphQLError::field_error(
+                        format!($($arg)+),
+                        path,
+                        location,
+                        &document.sources
+                    ));
+                    return Err(PropagateNull);
+                }
+            };
+        }
+        if let AsyncResolvedValue::Leaf(JsonValue::Null) = resolved {
+            if ty.is_non_null() {
+                field_error!("Non-null type {ty} resolved to null")
+            } else {
+                return Ok(JsonValue::Null);
+            }
+        }
+        if let AsyncResolvedValue::List(iter) = resolved {
+            match ty {
+                Type::Named(_) | Type::NonNullNamed(_) => {
+                    field_error!("Non-list type {ty} resolved to a list")
+                }
+                Type::List(inner_ty) | Type::NonNullList(inner_ty) => {
+                    let iter = executor.take_list_items(iter);
+                    let batched_list = AsyncBatchedList::new(iter);
+                    let batch_calls = if is_batchable(executor, schema, inner_ty) {
+                        batched_list.plan(
+                            executor,
+                            schema,
+                            document,
+                            variable_values,
+                            inner_ty,
+                            fields,
+                        )
+                    } else {
+                        Vec::new()
+                    };
+                    let item_paths: Vec<_> = (0..batched_list.len())
+                        .map(|index| LinkedPathElement {
+                            element: ResponseDataPathElement::ListIndex(index),
+                            next: path,
+                        })
+                        .collect();
+                    for (index, inner_path) in item_paths.iter().enumerate() {
+                        executor.before_list_item(index, Some(inner_path), location)?;
+                    }
+                    let items = batched_list.items(executor, path, &batch_calls).await?;
+                    let mut item_errors: Vec<Vec<GraphQLError>> = vec![Vec::new(); items.len()];
+                    let inner_results = join_all(
+                        items
+                            .into_iter()
+                            .zip(&item_paths)
+                            .zip(&mut item_errors)
+                            .map(|((inner_resolved, inner_path), errors)| {
+                                complete_value(
+                                    executor,
+                                    schema,
+                                    document,
+                                    variable_values,
+                                    errors,
+                                    Some(inner_path),
+                                    inner_ty,
+                                    inner_resolved,
+                                    fields,
+                                )
+                            }),
+                    )
+                    .await;
+                    errors.extend(item_errors.into_iter().flatten());
+                    let mut completed_list = Vec::with_capacity(inner_results.len());
+                    for inner_result in inner_results {
+                        // On field error, try to nullify that item
+                        match try_nullify(inner_ty, inner_result) {
+                            Ok(inner_value) => completed_list.push(inner_value),
+                            // If the item is non-null, try to nullify the list
+                            Err(PropagateNull) => return try_nullify(ty, Err(PropagateNull)),
+                        }
+                    }
+                    return Ok(completed_list.into());
+                }
+            }
+        }
+        let ty_name = match ty {
+            Type::List(_) | Type::NonNullList(_) => {
+                field_error!("List type {ty} resolved to an object")
+            }
+            Type::Named(name) | Type::NonNullNamed(name) => name,
+        };
+        let ty_def = match output_type_def(schema, ty_name, location) {
+            Ok(ty_def) => ty_def,
+            Err(bug) => {
+                errors.push(bug.into_field_error(&document.sources, path));
+                return Err(PropagateNull);
+            }
+        };
+        let resolved_obj = match resolved {
+            AsyncResolvedValue::List(_) => unreachable!(), // early return above
+            AsyncResolvedValue::Leaf(json_value) => {
+                match complete_leaf_value(executor, ty_name, ty_def, json_value) {
+                    Ok(json_value) => return Ok(json_value),
+                    Err(message) => field_error!("{message}"),
+                }
+            }
+            AsyncResolvedValue::Object(resolved_obj) => resolved_obj,
+        };
+        let object_type =
+            match resolved_object_type(schema, ty_name, ty_def, resolved_obj.type_name()) {
+                Ok(object_type) => object_type,
+                Err(message) => field_error!("{message}"),
+            };
+        execute_selection_set(
+            executor,
+            schema,
+            document,
+            variable_values,
+            errors,
+            path,
+            // Only the root selection set of a mutation is executed sequentially
+            ExecutionMode::Normal,
+            object_type,
+            &*resolved_obj,
+            fields
+                .iter()
+                .flat_map(|field| &field.selection_set.selections),
+        )
+        .await
+        .map(JsonValue::Object)
+    })
+}
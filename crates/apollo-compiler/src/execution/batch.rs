@@ -0,0 +1,505 @@
+//! Batched resolution of fields of list items,
+//! with [`Resolver::resolve_field_batch`] and [`AsyncResolver::resolve_field_batch`]
+
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Field;
+use crate::execution::engine::collect_fields;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::LinkedPathElement;
+use crate::execution::engine::PropagateNull;
+use crate::execution::input_coercion::coerce_argument_values;
+use crate::execution::resolver::AsyncObjectValue;
+use crate::execution::resolver::AsyncResolvedValue;
+use crate::execution::resolver::AsyncResolver;
+use crate::execution::resolver::ObjectValue;
+use crate::execution::resolver::ResolvedValue;
+use crate::execution::resolver::Resolver;
+use crate::execution::resolver::ResolverError;
+use crate::execution::result_coercion::output_type_def;
+use crate::execution::result_coercion::resolved_object_type;
+use crate::execution::Executor;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::ResponseDataPathElement;
+use crate::schema::ExtendedType;
+use crate::schema::Type;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Node;
+use crate::Schema;
+use futures::future::join_all;
+use futures::future::BoxFuture;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+/// One call to `resolve_field_batch`,
+/// for a field selected on list items that have the same object type
+pub(crate) struct BatchCall<'doc> {
+    field: &'doc Node<Field>,
+    arguments: JsonMap,
+    /// Indices of the objects of this batch in the list
+    items: Vec<usize>,
+    keys: Vec<JsonValue>,
+}
+
+/// A result of `resolve_field_batch` for one object, not yet used by execution
+struct Preloaded<'a, T> {
+    field_name: &'a str,
+    arguments: &'a JsonMap,
+    result: Option<Result<T, ResolverError>>,
+}
+
+/// Items of a list being completed, collected so that their fields can be resolved in batches
+pub(crate) struct BatchedList<'a> {
+    items: Vec<ListItem<'a>>,
+}
+
+enum ListItem<'a> {
+    Object(Box<ObjectValue<'a>>),
+    /// Taken when returned by [`BatchedList::items`]
+    Other(Cell<Option<ResolvedValue<'a>>>),
+}
+
+/// Wraps a list item to return results of `resolve_field_batch` from `resolve_field`
+struct PreloadedObject<'a> {
+    object: &'a ObjectValue<'a>,
+    preloaded: RefCell<Vec<Preloaded<'a, ResolvedValue<'a>>>>,
+}
+
+/// Like [`BatchedList`], for [`AsyncResolver`]
+pub(crate) struct AsyncBatchedList<'a> {
+    items: Vec<AsyncListItem<'a>>,
+}
+
+enum AsyncListItem<'a> {
+    Object(Box<AsyncObjectValue<'a>>),
+    /// Taken when returned by [`AsyncBatchedList::items`]
+    Other(Mutex<Option<AsyncResolvedValue<'a>>>),
+}
+
+/// Like [`PreloadedObject`], for [`AsyncResolver`]
+struct AsyncPreloadedObject<'a> {
+    object: &'a AsyncObjectValue<'a>,
+    preloaded: Mutex<Vec<Preloaded<'a, AsyncResolvedValue<'a>>>>,
+}
+
+/// Resolved values are invariant over their lifetime because of `List`
+pub(crate) fn shorten<'a: 'b, 'b>(value: ResolvedValue<'a>) -> ResolvedValue<'b> {
+    match value {
+        ResolvedValue::Leaf(json) => ResolvedValue::Leaf(json),
+        ResolvedValue::Object(object) => ResolvedValue::Object(object),
+        ResolvedValue::List(iter) => ResolvedValue::List(Box::new(iter.map(shorten))),
+    }
+}
+
+/// Like [`shorten`], for [`AsyncResolvedValue`]
+fn shorten_async<'a: 'b, 'b>(value: AsyncResolvedValue<'a>) -> AsyncResolvedValue<'b> {
+    match value {
+        AsyncResolvedValue::Leaf(json) => AsyncResolvedValue::Leaf(json),
+        AsyncResolvedValue::Object(object) => AsyncResolvedValue::Object(object),
+        AsyncResolvedValue::List(iter) => {
+            AsyncResolvedValue::List(Box::new(iter.map(shorten_async)))
+        }
+    }
+}
+
+/// Whether items of a list of type `[ty]` can be objects with fields to batch.
+///
+/// Batching is disabled with field middleware or tracing,
+/// which must run around the resolver of each field:
+/// to skip it for a short-circuited field, or to record its timing in that field’s span.
+pub(crate) fn is_batchable(executor: &Executor, schema: &Schema, ty: &Type) -> bool {
+    if !executor.field_middleware.is_empty() || executor.trace_recorder.is_some() {
+        return false;
+    }
+    match ty {
+        Type::Named(name) | Type::NonNullNamed(name) => matches!(
+            schema.types.get(name),
+            Some(ExtendedType::Object(_) | ExtendedType::Interface(_) | ExtendedType::Union(_))
+        ),
+        Type::List(_) | Type::NonNullList(_) => false,
+    }
+}
+
+/// Checks [`ExecutionLimits`][crate::execution::ExecutionLimits] before a batch call,
+/// at the path of the field for the first object of the batch
+fn before_batch_call(
+    executor: &Executor,
+    path: LinkedPath<'_>,
+    call: &BatchCall<'_>,
+) -> Result<(), PropagateNull> {
+    let item_path = LinkedPathElement {
+        element: ResponseDataPathElement::ListIndex(call.items[0]),
+        next: path,
+    };
+    let field_path = LinkedPathElement {
+        element: ResponseDataPathElement::Field(call.field.response_key().clone()),
+        next: Some(&item_path),
+    };
+    executor.before_field_batch(
+        call.keys.len(),
+        Some(&field_path),
+        call.field.name.location(),
+    )
+}
+
+/// Groups objects with a batch key by type name,
+/// and returns one call for each field selected on each group
+#[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+fn plan_batches<'a, 'doc>(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &'doc Valid<ExecutableDocument>,
+    variable_values: &Valid<JsonMap>,
+    ty: &Type,
+    fields: &[&'doc Node<Field>],
+    objects: impl IntoIterator<Item = (usize, &'a str, JsonValue)>,
+) -> Vec<BatchCall<'doc>> {
+    // Items past the limit are not completed
+    let max_len = executor.max_list_length().unwrap_or(usize::MAX);
+    let mut groups = IndexMap::<&str, (Vec<usize>, Vec<JsonValue>)>::default();
+    for (index, type_name, key) in objects {
+        if index >= max_len {
+            break;
+        }
+        let (items, keys) = groups.entry(type_name).or_default();
+        items.push(index);
+        keys.push(key);
+    }
+    let ty_name = ty.inner_named_type();
+    let Ok(ty_def) = output_type_def(schema, ty_name, None) else {
+        return Vec::new();
+    };
+    let mut calls = Vec::new();
+    for (type_name, (items, keys)) in groups {
+        // Errors are reported when completing each object
+        let Ok(object_type) = resolved_object_type(schema, ty_name, ty_def, type_name) else {
+            continue;
+        };
+        let mut grouped_field_set = IndexMap::with_hasher(Default::default());
+        collect_fields(
+            schema,
+            document,
+            variable_values,
+            object_type,
+            fields
+                .iter()
+                .flat_map(|field| &field.selection_set.selections),
+            &mut HashSet::default(),
+            &mut grouped_field_set,
+            None,
+        );
+        for selections in grouped_field_set.values() {
+            let field = selections[0];
+            // `__typename` is not resolved by resolvers
+            if field.name.starts_with("__") {
+                continue;
+            }
+            let Ok(field_def) = schema.type_field(&object_type.name, &field.name) else {
+                continue;
+            };
+            // Not batched: `resolve_field` is not called either
+            // since execution of the field for each object reports the same error
+            let Ok(arguments) = coerce_argument_values(
+                executor,
+                schema,
+                document,
+                variable_values,
+                &mut Vec::new(),
+                None,
+                field_def,
+                field,
+            ) else {
+                continue;
+            };
+            calls.push(BatchCall {
+                field,
+                arguments,
+                items: items.clone(),
+                keys: keys.clone(),
+            })
+        }
+    }
+    calls
+}
+
+/// Assigns results of batch calls to list items by index
+fn distribute<'a, T>(
+    calls: &'a [BatchCall<'_>],
+    results: Vec<Option<Vec<Result<T, ResolverError>>>>,
+    len: usize,
+) -> Vec<Vec<Preloaded<'a, T>>> {
+    let mut preloaded: Vec<Vec<_>> = (0..len).map(|_| Vec::new()).collect();
+    for (call, results) in calls.iter().zip(results) {
+        let Some(results) = results else {
+            // Not batched, `resolve_field` will be called for each object
+            continue;
+        };
+        let preload = |result| Preloaded {
+            field_name: call.field.name.as_str(),
+            arguments: &call.arguments,
+            result: Some(result),
+        };
+        if results.len() == call.keys.len() {
+            for (&index, result) in call.items.iter().zip(results) {
+                preloaded[index].push(preload(result))
+            }
+        } else {
+            let error = ResolverError::new(format!(
+                "resolve_field_batch returned {} results for {} keys",
+                results.len(),
+                call.keys.len()
+            ));
+            for &index in &call.items {
+                preloaded[index].push(preload(Err(error.clone())))
+            }
+        }
+    }
+    preloaded
+}
+
+/// Takes the preloaded result for a field, if any
+fn take_preloaded<T>(
+    preloaded: &mut [Preloaded<'_, T>],
+    field_name: &str,
+    arguments: &JsonMap,
+) -> Option<Result<T, ResolverError>> {
+    preloaded
+        .iter_mut()
+        .find(|preloaded| {
+            preloaded.result.is_some()
+                && preloaded.field_name == field_name
+                && preloaded.arguments == arguments
+        })
+        .and_then(|preloaded| preloaded.result.take())
+}
+
+impl<'a> BatchedList<'a> {
+    pub(crate) fn new(iter: impl Iterator<Item = ResolvedValue<'a>>) -> Self {
+        let items = iter
+            .map(|item| match item {
+                ResolvedValue::Object(object) => ListItem::Object(object),
+                other => ListItem::Other(Cell::new(Some(other))),
+            })
+            .collect();
+        Self { items }
+    }
+
+    pub(crate) fn plan<'doc>(
+        &self,
+        executor: &Executor,
+        schema: &Valid<Schema>,
+        document: &'doc Valid<ExecutableDocument>,
+        variable_values: &Valid<JsonMap>,
+        ty: &Type,
+        fields: &[&'doc Node<Field>],
+    ) -> Vec<BatchCall<'doc>> {
+        let objects = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| match item {
+                ListItem::Object(object) => Some((index, object.type_name(), object.batch_key()?)),
+                ListItem::Other(_) => None,
+            });
+        plan_batches(
+            executor,
+            schema,
+            document,
+            variable_values,
+            ty,
+            fields,
+            objects,
+        )
+    }
+
+    /// Calls `resolve_field_batch` and returns list items with preloaded results.
+    /// Returns `Err` if execution should stop because of limits checked before each call.
+    /// Should only be called once.
+    pub(crate) fn items<'b>(
+        &'b self,
+        executor: &Executor,
+        path: LinkedPath<'_>,
+        calls: &'b [BatchCall<'_>],
+    ) -> Result<Box<dyn Iterator<Item = ResolvedValue<'b>> + 'b>, PropagateNull> {
+        let results = calls
+            .iter()
+            .map(|call| {
+                let ListItem::Object(object) = &self.items[call.items[0]] else {
+                    unreachable!() // only objects are batched
+                };
+                before_batch_call(executor, path, call)?;
+                Ok(object.resolve_field_batch(&call.field.name, &call.arguments, &call.keys))
+            })
+            .collect::<Result<_, _>>()?;
+        let preloaded = distribute(calls, results, self.items.len());
+        Ok(Box::new(self.items.iter().zip(preloaded).map(
+            |(item, preloaded)| match item {
+                ListItem::Object(object) => ResolvedValue::Object(Box::new(PreloadedObject {
+                    object: &**object,
+                    preloaded: RefCell::new(preloaded),
+                })),
+                ListItem::Other(item) => item.take().map_or_else(ResolvedValue::null, shorten),
+            },
+        )))
+    }
+}
+
+impl Resolver for PreloadedObject<'_> {
+    fn type_name(&self) -> &str {
+        self.object.type_name()
+    }
+
+    fn resolve_field<'b>(
+        &'b self,
+        field_name: &'b str,
+        arguments: &'b JsonMap,
+    ) -> Result<ResolvedValue<'b>, ResolverError> {
+        let preloaded = take_preloaded(&mut self.preloaded.borrow_mut(), field_name, arguments);
+        match preloaded {
+            Some(result) => result.map(shorten),
+            None => self.object.resolve_field(field_name, arguments),
+        }
+    }
+
+    fn batch_key(&self) -> Option<JsonValue> {
+        self.object.batch_key()
+    }
+
+    fn resolve_field_batch<'b>(
+        &'b self,
+        field_name: &'b str,
+        arguments: &'b JsonMap,
+        keys: &'b [JsonValue],
+    ) -> Option<Vec<Result<ResolvedValue<'b>, ResolverError>>> {
+        self.object.resolve_field_batch(field_name, arguments, keys)
+    }
+}
+
+impl<'a> AsyncBatchedList<'a> {
+    pub(crate) fn new(iter: impl Iterator<Item = AsyncResolvedValue<'a>>) -> Self {
+        let items = iter
+            .map(|item| match item {
+                AsyncResolvedValue::Object(object) => AsyncListItem::Object(object),
+                other => AsyncListItem::Other(Mutex::new(Some(other))),
+            })
+            .collect();
+        Self { items }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub(crate) fn plan<'doc>(
+        &self,
+        executor: &Executor,
+        schema: &Valid<Schema>,
+        document: &'doc Valid<ExecutableDocument>,
+        variable_values: &Valid<JsonMap>,
+        ty: &Type,
+        fields: &[&'doc Node<Field>],
+    ) -> Vec<BatchCall<'doc>> {
+        let objects = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| match item {
+                AsyncListItem::Object(object) => {
+                    Some((index, object.type_name(), object.batch_key()?))
+                }
+                AsyncListItem::Other(_) => None,
+            });
+        plan_batches(
+            executor,
+            schema,
+            document,
+            variable_values,
+            ty,
+            fields,
+            objects,
+        )
+    }
+
+    /// Calls `resolve_field_batch` concurrently for all batches
+    /// and returns list items with preloaded results.
+    /// Returns `Err` if execution should stop because of limits checked before each call.
+    /// Should only be called once.
+    pub(crate) async fn items<'b>(
+        &'b self,
+        executor: &Executor,
+        path: LinkedPath<'_>,
+        calls: &'b [BatchCall<'_>],
+    ) -> Result<Vec<AsyncResolvedValue<'b>>, PropagateNull> {
+        let futures = calls
+            .iter()
+            .map(|call| {
+                let AsyncListItem::Object(object) = &self.items[call.items[0]] else {
+                    unreachable!() // only objects are batched
+                };
+                before_batch_call(executor, path, call)?;
+                let future =
+                    object.resolve_field_batch(&call.field.name, &call.arguments, &call.keys);
+                Ok(async move {
+                    match future {
+                        Some(future) => Some(future.await),
+                        None => None,
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = join_all(futures).await;
+        let preloaded = distribute(calls, results, self.items.len());
+        Ok(self
+            .items
+            .iter()
+            .zip(preloaded)
+            .map(|(item, preloaded)| match item {
+                AsyncListItem::Object(object) => {
+                    AsyncResolvedValue::Object(Box::new(AsyncPreloadedObject {
+                        object: &**object,
+                        preloaded: Mutex::new(preloaded),
+                    }))
+                }
+                AsyncListItem::Other(item) => item
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .map_or_else(AsyncResolvedValue::null, shorten_async),
+            })
+            .collect())
+    }
+}
+
+impl AsyncResolver for AsyncPreloadedObject<'_> {
+    fn type_name(&self) -> &str {
+        self.object.type_name()
+    }
+
+    fn resolve_field<'b>(
+        &'b self,
+        field_name: &'b str,
+        arguments: &'b JsonMap,
+    ) -> BoxFuture<'b, Result<AsyncResolvedValue<'b>, ResolverError>> {
+        let preloaded = take_preloaded(&mut self.preloaded.lock().unwrap(), field_name, arguments);
+        match preloaded {
+            Some(result) => Box::pin(async move { result.map(shorten_async) }),
+            None => self.object.resolve_field(field_name, arguments),
+        }
+    }
+
+    fn batch_key(&self) -> Option<JsonValue> {
+        self.object.batch_key()
+    }
+
+    fn resolve_field_batch<'b>(
+        &'b self,
+        field_name: &'b str,
+        arguments: &'b JsonMap,
+        keys: &'b [JsonValue],
+    ) -> Option<BoxFuture<'b, Vec<Result<AsyncResolvedValue<'b>, ResolverError>>>> {
+        self.object.resolve_field_batch(field_name, arguments, keys)
+    }
+}
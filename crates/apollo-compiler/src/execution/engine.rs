@@ -1,12 +1,16 @@
 use crate::ast::Value;
 use crate::collections::HashSet;
 use crate::collections::IndexMap;
+use crate::executable::Directive;
+use crate::executable::DirectiveList;
 use crate::executable::Field;
 use crate::executable::Selection;
 use crate::execution::input_coercion::coerce_argument_values;
+use crate::execution::middleware::FieldMiddlewareCall;
 use crate::execution::resolver::ObjectValue;
 use crate::execution::resolver::ResolverError;
 use crate::execution::result_coercion::complete_value;
+use crate::execution::Executor;
 use crate::execution::GraphQLError;
 use crate::execution::JsonMap;
 use crate::execution::JsonValue;
@@ -21,6 +25,7 @@ use crate::validation::SuspectedValidationBug;
 use crate::validation::Valid;
 use crate::ExecutableDocument;
 use crate::Name;
+use crate::Node;
 use crate::Schema;
 
 /// <https://spec.graphql.org/October2021/#sec-Normal-and-Serial-Execution>
@@ -29,7 +34,6 @@ pub(crate) enum ExecutionMode {
     /// Allowed to resolve fields in any order, including in parellel
     Normal,
     /// Top-level fields of a mutation operation must be executed in order
-    #[allow(unused)]
     Sequential,
 }
 
@@ -49,6 +53,7 @@ pub(crate) struct LinkedPathElement<'a> {
 /// <https://spec.graphql.org/October2021/#ExecuteSelectionSet()>
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 pub(crate) fn execute_selection_set<'a>(
+    executor: &Executor,
     schema: &Valid<Schema>,
     document: &'a Valid<ExecutableDocument>,
     variable_values: &Valid<JsonMap>,
@@ -68,13 +73,17 @@ pub(crate) fn execute_selection_set<'a>(
         selections,
         &mut HashSet::default(),
         &mut grouped_field_set,
+        None,
     );
 
     match mode {
-        ExecutionMode::Normal => {}
+        ExecutionMode::Normal => {
+            // If we want parallelism, use Rayon’s `par_iter` here.
+            // (See `async_engine` for concurrent execution.)
+        }
         ExecutionMode::Sequential => {
-            // If we want parallelism, use `futures::future::join_all` (async)
-            // or Rayon’s `par_iter` (sync) here.
+            // Fields are resolved and completed one at a time below,
+            // and a null propagated to the root stops execution of remaining fields.
         }
     }
 
@@ -96,12 +105,13 @@ pub(crate) fn execute_selection_set<'a>(
                 next: path,
             };
             execute_field(
+                executor,
                 schema,
                 document,
                 variable_values,
                 errors,
                 Some(&field_path),
-                mode,
+                &object_type.name,
                 object_value,
                 field_def,
                 fields,
@@ -112,20 +122,32 @@ pub(crate) fn execute_selection_set<'a>(
     Ok(response_map)
 }
 
+/// A fragment with an active `@defer` directive, found by [`collect_fields`]
+pub(crate) struct DeferredFragment<'a> {
+    pub(crate) label: Option<String>,
+    pub(crate) selections: &'a [Selection],
+}
+
 /// <https://spec.graphql.org/October2021/#CollectFields()>
+///
+/// If `deferred_fragments` is `Some`, fragments with an active `@defer` directive
+/// are pushed there instead of having their fields collected.
+/// Otherwise `@defer` is ignored and their fields are collected like any other fragment.
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
-fn collect_fields<'a>(
+pub(crate) fn collect_fields<'a>(
     schema: &Schema,
     document: &'a ExecutableDocument,
     variable_values: &Valid<JsonMap>,
     object_type: &ObjectType,
     selections: impl IntoIterator<Item = &'a Selection>,
     visited_fragments: &mut HashSet<&'a Name>,
-    grouped_fields: &mut IndexMap<&'a Name, Vec<&'a Field>>,
+    grouped_fields: &mut IndexMap<&'a Name, Vec<&'a Node<Field>>>,
+    mut deferred_fragments: Option<&mut Vec<DeferredFragment<'a>>>,
 ) {
     for selection in selections {
-        if eval_if_arg(selection, "skip", variable_values).unwrap_or(false)
-            || !eval_if_arg(selection, "include", variable_values).unwrap_or(true)
+        let directives = selection.directives();
+        if eval_if_arg(directives, "skip", variable_values).unwrap_or(false)
+            || !eval_if_arg(directives, "include", variable_values).unwrap_or(true)
         {
             continue;
         }
@@ -133,7 +155,7 @@ fn collect_fields<'a>(
             Selection::Field(field) => grouped_fields
                 .entry(field.response_key())
                 .or_default()
-                .push(field.as_ref()),
+                .push(field),
             Selection::FragmentSpread(spread) => {
                 let new = visited_fragments.insert(&spread.fragment_name);
                 if !new {
@@ -145,14 +167,22 @@ fn collect_fields<'a>(
                 if !does_fragment_type_apply(schema, object_type, fragment.type_condition()) {
                     continue;
                 }
+                let selections = &fragment.selection_set.selections;
+                if let Some(deferred_fragments) = &mut deferred_fragments {
+                    if let Some(label) = eval_defer(selection, variable_values) {
+                        deferred_fragments.push(DeferredFragment { label, selections });
+                        continue;
+                    }
+                }
                 collect_fields(
                     schema,
                     document,
                     variable_values,
                     object_type,
-                    &fragment.selection_set.selections,
+                    selections,
                     visited_fragments,
                     grouped_fields,
+                    deferred_fragments.as_deref_mut(),
                 )
             }
             Selection::InlineFragment(inline) => {
@@ -161,14 +191,22 @@ fn collect_fields<'a>(
                         continue;
                     }
                 }
+                let selections = &inline.selection_set.selections;
+                if let Some(deferred_fragments) = &mut deferred_fragments {
+                    if let Some(label) = eval_defer(selection, variable_values) {
+                        deferred_fragments.push(DeferredFragment { label, selections });
+                        continue;
+                    }
+                }
                 collect_fields(
                     schema,
                     document,
                     variable_values,
                     object_type,
-                    &inline.selection_set.selections,
+                    selections,
                     visited_fragments,
                     grouped_fields,
+                    deferred_fragments.as_deref_mut(),
                 )
             }
         }
@@ -192,13 +230,12 @@ fn does_fragment_type_apply(
     }
 }
 
-fn eval_if_arg(
-    selection: &Selection,
+pub(crate) fn eval_if_arg(
+    directives: &DirectiveList,
     directive_name: &str,
     variable_values: &Valid<JsonMap>,
 ) -> Option<bool> {
-    match selection
-        .directives()
+    match directives
         .get(directive_name)?
         .specified_argument_by_name("if")?
         .as_ref()
@@ -209,21 +246,48 @@ fn eval_if_arg(
     }
 }
 
+/// If `selection` has an active `@defer` directive, returns its `label` argument
+fn eval_defer(selection: &Selection, variable_values: &Valid<JsonMap>) -> Option<Option<String>> {
+    let directives = selection.directives();
+    let directive = directives.get("defer")?;
+    if !eval_if_arg(directives, "defer", variable_values).unwrap_or(true) {
+        return None;
+    }
+    Some(eval_string_arg(directive, "label", variable_values))
+}
+
+pub(crate) fn eval_string_arg(
+    directive: &Directive,
+    argument_name: &str,
+    variable_values: &Valid<JsonMap>,
+) -> Option<String> {
+    match directive
+        .specified_argument_by_name(argument_name)?
+        .as_ref()
+    {
+        Value::String(value) => Some(value.clone()),
+        Value::Variable(var) => Some(variable_values.get(var.as_str())?.as_str()?.to_owned()),
+        _ => None,
+    }
+}
+
 /// <https://spec.graphql.org/October2021/#ExecuteField()>
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 fn execute_field(
+    executor: &Executor,
     schema: &Valid<Schema>,
     document: &Valid<ExecutableDocument>,
     variable_values: &Valid<JsonMap>,
     errors: &mut Vec<GraphQLError>,
     path: LinkedPath<'_>,
-    mode: ExecutionMode,
+    parent_type: &Name,
     object_value: &ObjectValue<'_>,
     field_def: &FieldDefinition,
-    fields: &[&Field],
+    fields: &[&Node<Field>],
 ) -> Result<JsonValue, PropagateNull> {
     let field = fields[0];
     let argument_values = match coerce_argument_values(
+        executor,
         schema,
         document,
         variable_values,
@@ -235,28 +299,42 @@ fn execute_field(
         Ok(argument_values) => argument_values,
         Err(PropagateNull) => return try_nullify(&field_def.ty, Err(PropagateNull)),
     };
-    let resolved_result = object_value.resolve_field(&field.name, &argument_values);
-    let completed_result = match resolved_result {
-        Ok(resolved) => complete_value(
-            schema,
-            document,
-            variable_values,
-            errors,
-            path,
-            mode,
-            field.ty(),
-            resolved,
-            fields,
-        ),
-        Err(ResolverError { message }) => {
-            errors.push(GraphQLError::field_error(
-                format!("resolver error: {message}"),
+    let middleware =
+        FieldMiddlewareCall::new(executor, parent_type, field, &argument_values, path, errors);
+    let short_circuit = middleware
+        .as_ref()
+        .and_then(|middleware| middleware.before(errors, &document.sources));
+    let completed_result = if let Some(result) = short_circuit {
+        result
+    } else {
+        match object_value.resolve_field(&field.name, &argument_values) {
+            Ok(resolved) => complete_value(
+                executor,
+                schema,
+                document,
+                variable_values,
+                errors,
                 path,
-                field.name.location(),
-                &document.sources,
-            ));
-            Err(PropagateNull)
+                field.ty(),
+                resolved,
+                fields,
+            ),
+            Err(ResolverError { message }) => {
+                errors.push(GraphQLError::field_error(
+                    format!("resolver error: {message}"),
+                    path,
+                    field.name.location(),
+                    &document.sources,
+                ));
+                Err(PropagateNull)
+            }
+        }
+    };
+    let completed_result = match middleware {
+        Some(middleware) => {
+            middleware.after(completed_result, field.ty(), errors, &document.sources)
         }
+        None => completed_result,
     };
     try_nullify(&field_def.ty, completed_result)
 }
@@ -0,0 +1,507 @@
+use crate::executable::Operation;
+use crate::executable::OperationType;
+use crate::execution::async_engine;
+use crate::execution::engine::execute_selection_set;
+use crate::execution::engine::ExecutionMode;
+use crate::execution::limits::LimitState;
+use crate::execution::resolver::AsyncObjectValue;
+use crate::execution::resolver::ObjectValue;
+use crate::execution::tracing::TraceRecorder;
+use crate::execution::CancellationToken;
+use crate::execution::ExecutionLimits;
+use crate::execution::FieldMiddleware;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::Response;
+use crate::execution::ScalarCoercion;
+use crate::execution::SchemaIntrospectionQuery;
+use crate::execution::SchemaIntrospectionSplit;
+use crate::parser::SourceMap;
+use crate::schema::SchemaVisibility;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Node;
+use crate::Schema;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+/// Execute a GraphQL request with the given resolver for the root operation type.
+///
+/// This is [ExecuteRequest()](https://spec.graphql.org/October2021/#ExecuteRequest())
+/// in the GraphQL specification:
+///
+/// * The operation is selected from `document` with
+///   [`OperationMap::get`][crate::executable::OperationMap::get]
+/// * `variables` are coerced with [`coerce_variable_values`][crate::execution::coerce_variable_values]
+/// * [Schema introspection] fields are executed against `schema`,
+///   separately from other fields as with [`SchemaIntrospectionQuery::split_and_execute`]
+/// * Other fields are resolved starting from `root_value`,
+///   which represents an object of the root operation type.
+///   Top-level fields of a mutation operation are [executed serially].
+///
+/// Failure in either of the first two steps is returned as a [request error].
+/// Subscription operations are not supported by this function
+/// and also return a request error. Use [`subscribe`][crate::execution::subscribe] instead.
+///
+/// [Schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+/// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+/// [executed serially]: https://spec.graphql.org/October2021/#sec-Normal-and-Serial-Execution
+pub fn execute_request(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    root_value: &ObjectValue<'_>,
+) -> Response {
+    Executor::default_ref().execute_request(schema, document, operation_name, variables, root_value)
+}
+
+/// Execute a GraphQL request with the given asynchronous resolver for the root operation type.
+///
+/// This is the same as [`execute_request`], except that field resolvers return futures.
+/// The futures of sibling fields are awaited concurrently,
+/// except for top-level fields of a mutation operation:
+/// each of them is resolved and completed before the next one starts.
+///
+/// Execution only relies on [`Future`][std::future::Future]
+/// and does not depend on any particular async runtime.
+pub async fn execute_request_async(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    root_value: &AsyncObjectValue<'_>,
+) -> Response {
+    Executor::default_ref()
+        .execute_request_async(schema, document, operation_name, variables, root_value)
+        .await
+}
+
+/// Configuration for executing GraphQL requests
+///
+/// Functions of the [`execution`][crate::execution] module such as [`execute_request`]
+/// use the default configuration.
+/// Methods of `Executor` with the same name and parameters use the configuration of `self` instead.
+///
+/// ## Example
+///
+/// ```no_run
+/// use apollo_compiler::execution::Executor;
+/// use apollo_compiler::execution::ScalarCoercion;
+/// # let (schema, document, root_value): (
+/// #     apollo_compiler::validation::Valid<apollo_compiler::Schema>,
+/// #     apollo_compiler::validation::Valid<apollo_compiler::ExecutableDocument>,
+/// #     Box<apollo_compiler::execution::ObjectValue<'static>>,
+/// # ) = todo!();
+///
+/// let scalars = ScalarCoercion::new();
+/// let executor = Executor::new().scalar_coercion(scalars);
+/// let response = executor.execute_request(&schema, &document, None, &Default::default(), &*root_value);
+/// ```
+#[derive(Clone, Default)]
+pub struct Executor {
+    pub(crate) scalar_coercion: ScalarCoercion,
+    pub(crate) field_middleware: Vec<Arc<dyn FieldMiddleware>>,
+    pub(crate) tracing: bool,
+    pub(crate) limits: ExecutionLimits,
+    pub(crate) cancellation_token: Option<CancellationToken>,
+    pub(crate) schema_visibility: Option<Arc<dyn SchemaVisibility>>,
+    /// Only set in a copy of the executor used for one request
+    pub(crate) trace_recorder: Option<Arc<TraceRecorder>>,
+    /// Only set in a copy of the executor used for one request
+    pub(crate) limit_state: Option<Arc<LimitState>>,
+}
+
+impl Executor {
+    /// Returns the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The default configuration, for functions that don’t take an `Executor`
+    pub(crate) fn default_ref() -> &'static Self {
+        static DEFAULT: OnceLock<Executor> = OnceLock::new();
+        DEFAULT.get_or_init(Executor::new)
+    }
+
+    /// Use the given coercion rules for custom scalars:
+    /// in variable values, in literal field arguments, and in leaf values returned by resolvers.
+    pub fn scalar_coercion(mut self, scalar_coercion: ScalarCoercion) -> Self {
+        self.scalar_coercion = scalar_coercion;
+        self
+    }
+
+    /// Add a middleware with hooks called around the resolution of each field.
+    ///
+    /// See [`FieldMiddleware`] for how multiple middleware are ordered.
+    pub fn field_middleware(mut self, middleware: impl FieldMiddleware + 'static) -> Self {
+        self.field_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Enable or disable per-field tracing. Disabled by default.
+    ///
+    /// When enabled, [`execute_request`][Self::execute_request]
+    /// and [`execute_request_async`][Self::execute_request_async] record
+    /// the timing, types, and errors of each field that is resolved,
+    /// and add them to [`Response::extensions`] as an
+    /// [`ExecutionTrace`][crate::execution::ExecutionTrace].
+    /// Schema introspection fields are not traced.
+    pub fn tracing(mut self, enabled: bool) -> Self {
+        self.tracing = enabled;
+        self
+    }
+
+    /// Set resource limits for each request.
+    ///
+    /// Limits apply to [`execute_request`][Self::execute_request],
+    /// [`execute_request_async`][Self::execute_request_async],
+    /// [`execute_incremental_request`][Self::execute_incremental_request] across all payloads,
+    /// and to each event of [`subscribe`][Self::subscribe] separately.
+    pub fn limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Stop execution when the given token is cancelled.
+    ///
+    /// This applies to the same functions as [`limits`][Self::limits].
+    /// Cancellation is reported as an error with the `EXECUTION_CANCELLED` code.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Hide schema elements from [schema introspection] results.
+    ///
+    /// This does not prevent selecting hidden fields: use
+    /// [`ExecutableDocument::validate_with_visibility`] with the same `visibility` for that.
+    ///
+    /// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+    pub fn schema_visibility(mut self, visibility: impl SchemaVisibility + 'static) -> Self {
+        self.schema_visibility = Some(Arc::new(visibility));
+        self
+    }
+
+    /// Returns a copy of `self` with state for one request,
+    /// or `None` if no state is needed or if `self` is already such a copy
+    fn start_request(&self) -> Option<Executor> {
+        if self.trace_recorder.is_some() || self.limit_state.is_some() {
+            return None;
+        }
+        let trace_recorder = self.tracing.then(TraceRecorder::new);
+        let limit_state = LimitState::new(&self.limits, self.cancellation_token.as_ref());
+        if trace_recorder.is_none() && limit_state.is_none() {
+            return None;
+        }
+        let mut executor = self.clone();
+        executor.trace_recorder = trace_recorder.map(Arc::new);
+        executor.limit_state = limit_state.map(Arc::new);
+        Some(executor)
+    }
+
+    /// Applies limits and tracing of an executor returned by [`Self::start_request`]
+    fn finish_request(&self, mut response: Response, sources: &SourceMap) -> Response {
+        if let Some(state) = &self.limit_state {
+            response = state.finish(response, sources)
+        }
+        if let Some(recorder) = &self.trace_recorder {
+            response = recorder.finish(response)
+        }
+        response
+    }
+
+    /// Like [`execute_request`], with the configuration of `self`
+    pub fn execute_request(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation_name: Option<&str>,
+        variables: &JsonMap,
+        root_value: &ObjectValue<'_>,
+    ) -> Response {
+        if let Some(executor) = self.start_request() {
+            let response =
+                executor.execute_request(schema, document, operation_name, variables, root_value);
+            return executor.finish_request(response, &document.sources);
+        }
+        let (operation, variable_values, mode) = match prepare_request(
+            self,
+            schema,
+            document,
+            operation_name,
+            variables,
+            "execute_request",
+        ) {
+            Ok(prepared) => prepared,
+            Err(response) => return response,
+        };
+        SchemaIntrospectionQuery::split_and_execute_with(
+            self,
+            schema,
+            document,
+            operation,
+            &variable_values,
+            |non_introspection_document| {
+                // The filtered document preserves the operation name
+                let Ok(operation) = non_introspection_document.operations.get(operation_name)
+                else {
+                    return Response::from_request_error(GraphQLError::new(
+                        "Operation not found after splitting schema introspection",
+                        operation.location(),
+                        &document.sources,
+                    ));
+                };
+                execute_root_selection_set(
+                    self,
+                    schema,
+                    non_introspection_document,
+                    operation,
+                    &variable_values,
+                    mode,
+                    root_value,
+                )
+            },
+        )
+    }
+
+    /// Like [`execute_request_async`], with the configuration of `self`
+    pub async fn execute_request_async(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation_name: Option<&str>,
+        variables: &JsonMap,
+        root_value: &AsyncObjectValue<'_>,
+    ) -> Response {
+        if let Some(executor) = self.start_request() {
+            let response = Box::pin(executor.execute_request_async(
+                schema,
+                document,
+                operation_name,
+                variables,
+                root_value,
+            ))
+            .await;
+            return executor.finish_request(response, &document.sources);
+        }
+        let (operation, variable_values, mode) = match prepare_request(
+            self,
+            schema,
+            document,
+            operation_name,
+            variables,
+            "execute_request_async",
+        ) {
+            Ok(prepared) => prepared,
+            Err(response) => return response,
+        };
+        match SchemaIntrospectionSplit::split(schema, document, operation) {
+            Ok(SchemaIntrospectionSplit::Only(introspection_query)) => {
+                introspection_query.execute_with(self, schema, &variable_values)
+            }
+            Ok(SchemaIntrospectionSplit::None) => {
+                execute_root_selection_set_async(
+                    self,
+                    schema,
+                    document,
+                    operation,
+                    &variable_values,
+                    mode,
+                    root_value,
+                )
+                .await
+            }
+            Ok(SchemaIntrospectionSplit::Both {
+                introspection_query,
+                filtered_document,
+            }) => {
+                // The filtered document preserves the operation name
+                let non_introspection_response =
+                    match filtered_document.operations.get(operation_name) {
+                        Ok(filtered_operation) => {
+                            execute_root_selection_set_async(
+                                self,
+                                schema,
+                                &filtered_document,
+                                filtered_operation,
+                                &variable_values,
+                                mode,
+                                root_value,
+                            )
+                            .await
+                        }
+                        Err(_) => Response::from_request_error(GraphQLError::new(
+                            "Operation not found after splitting schema introspection",
+                            operation.location(),
+                            &document.sources,
+                        )),
+                    };
+                let introspection_response =
+                    introspection_query.execute_with(self, schema, &variable_values);
+                non_introspection_response.merge(introspection_response)
+            }
+            Err(err) => err.into_response(&document.sources),
+        }
+    }
+}
+
+impl std::fmt::Debug for Executor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Executor")
+            .field("scalar_coercion", &self.scalar_coercion)
+            .field("field_middleware", &self.field_middleware.len())
+            .field("tracing", &self.tracing)
+            .field("limits", &self.limits)
+            .field("cancellation_token", &self.cancellation_token)
+            .field("schema_visibility", &self.schema_visibility.is_some())
+            .finish()
+    }
+}
+
+/// Get the operation, coerce variables, and pick an execution mode,
+/// or return a response with a request error
+#[allow(clippy::result_large_err)] // only called once per request
+pub(crate) fn prepare_request<'doc>(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &'doc Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    function_name: &str,
+) -> Result<(&'doc Node<Operation>, Valid<JsonMap>, ExecutionMode), Response> {
+    let (operation, variable_values) =
+        prepare_operation(executor, schema, document, operation_name, variables)?;
+    let mode = match operation.operation_type {
+        // https://spec.graphql.org/October2021/#sec-Query
+        OperationType::Query => ExecutionMode::Normal,
+        // https://spec.graphql.org/October2021/#sec-Mutation
+        OperationType::Mutation => ExecutionMode::Sequential,
+        OperationType::Subscription => {
+            return Err(Response::from_request_error(GraphQLError::new(
+                format!(
+                    "{function_name} does not support subscription operations, \
+                     use subscribe instead"
+                ),
+                operation.location(),
+                &document.sources,
+            )))
+        }
+    };
+    Ok((operation, variable_values, mode))
+}
+
+/// Get the operation and coerce variables, or return a response with a request error
+///
+/// <https://spec.graphql.org/October2021/#GetOperation()>
+/// <https://spec.graphql.org/October2021/#CoerceVariableValues()>
+#[allow(clippy::result_large_err)] // only called once per request
+pub(crate) fn prepare_operation<'doc>(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &'doc Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+) -> Result<(&'doc Node<Operation>, Valid<JsonMap>), Response> {
+    let Ok(operation) = document.operations.get(operation_name) else {
+        let message = if let Some(name) = operation_name {
+            format!("No operation named '{name}'")
+        } else {
+            "An operation name is required when the document does not contain \
+             exactly one operation"
+                .to_owned()
+        };
+        return Err(Response::from_request_error(GraphQLError::new(
+            message,
+            None,
+            &document.sources,
+        )));
+    };
+    let variable_values = executor
+        .coerce_variable_values_with_sources(schema, &document.sources, operation, variables)
+        .map_err(|err| err.into_response(&document.sources))?;
+    Ok((operation, variable_values))
+}
+
+/// Execute the selection set of an operation
+/// against an initial value for the root operation type.
+///
+/// <https://spec.graphql.org/October2021/#ExecuteQuery()>
+/// <https://spec.graphql.org/October2021/#ExecuteMutation()>
+pub(crate) fn execute_root_selection_set(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Operation,
+    variable_values: &Valid<JsonMap>,
+    mode: ExecutionMode,
+    initial_value: &ObjectValue<'_>,
+) -> Response {
+    let object_type_name = operation.object_type();
+    let Some(object_type_def) = schema.get_object(object_type_name) else {
+        return Response::from_request_error(GraphQLError::new(
+            "Undefined root operation type",
+            object_type_name.location(),
+            &document.sources,
+        ));
+    };
+    let mut errors = Vec::new();
+    let path = None;
+    let data = execute_selection_set(
+        executor,
+        schema,
+        document,
+        variable_values,
+        &mut errors,
+        path,
+        mode,
+        object_type_def,
+        initial_value,
+        &operation.selection_set.selections,
+    );
+    Response {
+        data: data.into(),
+        errors,
+        extensions: Default::default(),
+    }
+}
+
+/// Asynchronous counterpart of [`execute_root_selection_set`]
+pub(crate) async fn execute_root_selection_set_async(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Operation,
+    variable_values: &Valid<JsonMap>,
+    mode: ExecutionMode,
+    initial_value: &AsyncObjectValue<'_>,
+) -> Response {
+    let object_type_name = operation.object_type();
+    let Some(object_type_def) = schema.get_object(object_type_name) else {
+        return Response::from_request_error(GraphQLError::new(
+            "Undefined root operation type",
+            object_type_name.location(),
+            &document.sources,
+        ));
+    };
+    let mut errors = Vec::new();
+    let path = None;
+    let data = async_engine::execute_selection_set(
+        executor,
+        schema,
+        document,
+        variable_values,
+        &mut errors,
+        path,
+        mode,
+        object_type_def,
+        initial_value,
+        &operation.selection_set.selections,
+    )
+    .await;
+    Response {
+        data: data.into(),
+        errors,
+        extensions: Default::default(),
+    }
+}
@@ -0,0 +1,815 @@
+//! Synchronous engine for [incremental delivery] with `@defer` and `@stream`,
+//! based on `engine` and `result_coercion`.
+//!
+//! Deferred fragments and streamed list items are executed eagerly,
+//! but their results are kept in separate records
+//! that are delivered as subsequent payloads after the initial response.
+//!
+//! [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+
+use crate::ast::Value;
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Field;
+use crate::executable::Operation;
+use crate::executable::Selection;
+use crate::execution::engine::collect_fields;
+use crate::execution::engine::eval_if_arg;
+use crate::execution::engine::eval_string_arg;
+use crate::execution::engine::path_to_vec;
+use crate::execution::engine::try_nullify;
+use crate::execution::engine::DeferredFragment;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::LinkedPathElement;
+use crate::execution::engine::PropagateNull;
+use crate::execution::execute::prepare_request;
+use crate::execution::input_coercion::coerce_argument_values;
+use crate::execution::middleware::FieldMiddlewareCall;
+use crate::execution::resolver::ObjectValue;
+use crate::execution::resolver::ResolvedValue;
+use crate::execution::resolver::ResolverError;
+use crate::execution::result_coercion::complete_leaf_value;
+use crate::execution::result_coercion::output_type_def;
+use crate::execution::result_coercion::resolved_object_type;
+use crate::execution::CompletedResult;
+use crate::execution::Executor;
+use crate::execution::GraphQLError;
+use crate::execution::IncrementalResult;
+use crate::execution::InitialIncrementalResponse;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::PendingResult;
+use crate::execution::Response;
+use crate::execution::ResponseData;
+use crate::execution::ResponseDataPathElement;
+use crate::execution::SchemaIntrospectionSplit;
+use crate::execution::SubsequentIncrementalResponse;
+use crate::schema::FieldDefinition;
+use crate::schema::ObjectType;
+use crate::schema::Type;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
+use crate::Schema;
+use std::collections::VecDeque;
+
+/// Execute a GraphQL request with support for [incremental delivery]
+/// of fragments marked `@defer` and list fields marked `@stream`.
+///
+/// This is similar to [`execute_request`][crate::execution::execute_request],
+/// but returns an initial response followed by subsequent payloads.
+/// Each [`PendingResult`] announced in a payload is later completed in a subsequent one.
+/// If the operation does not use `@defer` or `@stream`,
+/// the initial response has `hasNext: false` and there is no subsequent payload.
+///
+/// Resolvers are synchronous, so all payloads are computed before this function returns.
+/// [`ExecutionLimits`][crate::execution::ExecutionLimits] apply to all payloads together:
+/// if a limit is exceeded, the initial response has null data and there is no subsequent payload.
+/// Data for deferred fragments is delivered separately even if some of its fields
+/// are also selected outside of the fragment.
+/// `@defer` and `@stream` in [schema introspection] fields are ignored.
+///
+/// The schema must define the directives,
+/// for example with [`SchemaBuilder::enable_incremental_delivery`][crate::schema::SchemaBuilder::enable_incremental_delivery].
+/// [`execute_request`][crate::execution::execute_request] and other execution functions
+/// ignore them and return all data in a single response.
+///
+/// [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+/// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+pub fn execute_incremental_request(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    root_value: &ObjectValue<'_>,
+) -> (
+    InitialIncrementalResponse,
+    Vec<SubsequentIncrementalResponse>,
+) {
+    Executor::default_ref().execute_incremental_request(
+        schema,
+        document,
+        operation_name,
+        variables,
+        root_value,
+    )
+}
+
+impl Executor {
+    /// Like [`execute_incremental_request`], with the configuration of `self`
+    pub fn execute_incremental_request(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation_name: Option<&str>,
+        variables: &JsonMap,
+        root_value: &ObjectValue<'_>,
+    ) -> (
+        InitialIncrementalResponse,
+        Vec<SubsequentIncrementalResponse>,
+    ) {
+        if let Some(executor) = self.with_limit_state() {
+            let (initial, subsequent) = executor.execute_incremental_request(
+                schema,
+                document,
+                operation_name,
+                variables,
+                root_value,
+            );
+            let Some(state) = executor
+                .limit_state
+                .as_ref()
+                .filter(|state| state.is_exceeded())
+            else {
+                return (initial, subsequent);
+            };
+            // Like data of the initial payload, subsequent payloads are discarded
+            let response = Response {
+                errors: initial.errors,
+                data: initial.data,
+                extensions: initial.extensions,
+            };
+            return (state.finish(response, &document.sources).into(), Vec::new());
+        }
+        // Fields are executed one at a time, which is also correct for mutations
+        let (operation, variable_values, _mode) = match prepare_request(
+            self,
+            schema,
+            document,
+            operation_name,
+            variables,
+            "execute_incremental_request",
+        ) {
+            Ok(prepared) => prepared,
+            Err(response) => return (response.into(), Vec::new()),
+        };
+        let (response, records) = match SchemaIntrospectionSplit::split(schema, document, operation)
+        {
+            Ok(SchemaIntrospectionSplit::Only(introspection_query)) => {
+                let response = introspection_query.execute_with(self, schema, &variable_values);
+                return (response.into(), Vec::new());
+            }
+            Ok(SchemaIntrospectionSplit::None) => execute_root_selection_set(
+                self,
+                schema,
+                document,
+                operation,
+                &variable_values,
+                root_value,
+            ),
+            Ok(SchemaIntrospectionSplit::Both {
+                introspection_query,
+                filtered_document,
+            }) => {
+                // The filtered document preserves the operation name
+                let Ok(filtered_operation) = filtered_document.operations.get(operation_name)
+                else {
+                    let response = Response::from_request_error(GraphQLError::new(
+                        "Operation not found after splitting schema introspection",
+                        operation.location(),
+                        &document.sources,
+                    ));
+                    return (response.into(), Vec::new());
+                };
+                let (response, records) = execute_root_selection_set(
+                    self,
+                    schema,
+                    &filtered_document,
+                    filtered_operation,
+                    &variable_values,
+                    root_value,
+                );
+                let introspection_response =
+                    introspection_query.execute_with(self, schema, &variable_values);
+                (response.merge(introspection_response), records)
+            }
+            Err(err) => return (err.into_response(&document.sources).into(), Vec::new()),
+        };
+        into_payloads(response, records)
+    }
+}
+
+/// Result of a deferred fragment or streamed list, delivered in a subsequent payload
+struct Record {
+    /// Index in `IncrementalExecution::records` of the record whose execution found this one,
+    /// or `None` if found while executing the initial response
+    parent: Option<usize>,
+    path: Vec<ResponseDataPathElement>,
+    label: Option<String>,
+    errors: Vec<GraphQLError>,
+    data: RecordData,
+}
+
+enum RecordData {
+    Defer(Result<JsonMap, PropagateNull>),
+    Stream {
+        /// Index in the list of the first item of `items`
+        start_index: usize,
+        items: Result<Vec<JsonValue>, PropagateNull>,
+    },
+}
+
+struct IncrementalExecution<'a> {
+    executor: &'a Executor,
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    variable_values: &'a Valid<JsonMap>,
+    records: Vec<Record>,
+}
+
+/// `@stream` arguments of a list field
+struct Stream {
+    initial_count: usize,
+    label: Option<String>,
+}
+
+fn execute_root_selection_set(
+    executor: &Executor,
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Operation,
+    variable_values: &Valid<JsonMap>,
+    initial_value: &ObjectValue<'_>,
+) -> (Response, Vec<Record>) {
+    let object_type_name = operation.object_type();
+    let Some(object_type_def) = schema.get_object(object_type_name) else {
+        let response = Response::from_request_error(GraphQLError::new(
+            "Undefined root operation type",
+            object_type_name.location(),
+            &document.sources,
+        ));
+        return (response, Vec::new());
+    };
+    let mut execution = IncrementalExecution {
+        executor,
+        schema,
+        document,
+        variable_values,
+        records: Vec::new(),
+    };
+    let mut errors = Vec::new();
+    let path = None;
+    let parent_record = None;
+    let data = execution.execute_selection_set(
+        &mut errors,
+        path,
+        parent_record,
+        object_type_def,
+        initial_value,
+        &operation.selection_set.selections,
+    );
+    let response = Response {
+        data: data.into(),
+        errors,
+        extensions: Default::default(),
+    };
+    (response, execution.records)
+}
+
+impl<'a> IncrementalExecution<'a> {
+    /// <https://spec.graphql.org/October2021/#ExecuteSelectionSet()>
+    ///
+    /// Fields are executed one at a time, in either execution mode.
+    /// Deferred fragments are executed after other fields of the same object,
+    /// each into a new record.
+    fn execute_selection_set(
+        &mut self,
+        errors: &mut Vec<GraphQLError>,
+        path: LinkedPath<'_>,
+        parent_record: Option<usize>,
+        object_type: &ObjectType,
+        object_value: &ObjectValue<'_>,
+        selections: impl IntoIterator<Item = &'a Selection>,
+    ) -> Result<JsonMap, PropagateNull> {
+        let mut grouped_field_set = IndexMap::with_hasher(Default::default());
+        let mut deferred_fragments = Vec::new();
+        collect_fields(
+            self.schema,
+            self.document,
+            self.variable_values,
+            object_type,
+            selections,
+            &mut HashSet::default(),
+            &mut grouped_field_set,
+            Some(&mut deferred_fragments),
+        );
+
+        let mut response_map = JsonMap::with_capacity(grouped_field_set.len());
+        for (&response_key, fields) in &grouped_field_set {
+            // Indexing should not panic: `collect_fields` only creates a `Vec` to push to it
+            let field_name = &fields[0].name;
+            let Ok(field_def) = self.schema.type_field(&object_type.name, field_name) else {
+                continue;
+            };
+            let value = if field_name == "__typename" {
+                JsonValue::from(object_type.name.as_str())
+            } else {
+                let field_path = LinkedPathElement {
+                    element: ResponseDataPathElement::Field(response_key.clone()),
+                    next: path,
+                };
+                self.execute_field(
+                    errors,
+                    Some(&field_path),
+                    parent_record,
+                    &object_type.name,
+                    object_value,
+                    field_def,
+                    fields,
+                )?
+            };
+            response_map.insert(response_key.as_str(), value);
+        }
+
+        for DeferredFragment { label, selections } in deferred_fragments {
+            let record = self.new_record(parent_record, path, label);
+            let mut record_errors = Vec::new();
+            let data = self.execute_selection_set(
+                &mut record_errors,
+                path,
+                Some(record),
+                object_type,
+                object_value,
+                selections,
+            );
+            self.records[record].errors = record_errors;
+            self.records[record].data = RecordData::Defer(data);
+        }
+        Ok(response_map)
+    }
+
+    fn new_record(
+        &mut self,
+        parent: Option<usize>,
+        path: LinkedPath<'_>,
+        label: Option<String>,
+    ) -> usize {
+        let index = self.records.len();
+        self.records.push(Record {
+            parent,
+            path: path_to_vec(path),
+            label,
+            errors: Vec::new(),
+            // Placeholder replaced after executing the record
+            data: RecordData::Defer(Err(PropagateNull)),
+        });
+        index
+    }
+
+    /// <https://spec.graphql.org/October2021/#ExecuteField()>
+    #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+    fn execute_field(
+        &mut self,
+        errors: &mut Vec<GraphQLError>,
+        path: LinkedPath<'_>,
+        parent_record: Option<usize>,
+        parent_type: &Name,
+        object_value: &ObjectValue<'_>,
+        field_def: &FieldDefinition,
+        fields: &[&'a Node<Field>],
+    ) -> Result<JsonValue, PropagateNull> {
+        let field = fields[0];
+        let argument_values = match coerce_argument_values(
+            self.executor,
+            self.schema,
+            self.document,
+            self.variable_values,
+            errors,
+            path,
+            field_def,
+            field,
+        ) {
+            Ok(argument_values) => argument_values,
+            Err(PropagateNull) => return try_nullify(&field_def.ty, Err(PropagateNull)),
+        };
+        // Like arguments, checked before calling the resolver
+        let stream = match self.eval_stream(field) {
+            Ok(stream) => stream,
+            Err(message) => {
+                errors.push(GraphQLError::field_error(
+                    message,
+                    path,
+                    field.name.location(),
+                    &self.document.sources,
+                ));
+                return try_nullify(&field_def.ty, Err(PropagateNull));
+            }
+        };
+        let middleware = FieldMiddlewareCall::new(
+            self.executor,
+            parent_type,
+            field,
+            &argument_values,
+            path,
+            errors,
+        );
+        let short_circuit = middleware
+            .as_ref()
+            .and_then(|middleware| middleware.before(errors, &self.document.sources));
+        let completed_result = if let Some(result) = short_circuit {
+            result
+        } else {
+            match object_value.resolve_field(&field.name, &argument_values) {
+                Ok(resolved) => self.complete_value(
+                    errors,
+                    path,
+                    parent_record,
+                    field.ty(),
+                    resolved,
+                    fields,
+                    stream,
+                ),
+                Err(ResolverError { message }) => {
+                    errors.push(GraphQLError::field_error(
+                        format!("resolver error: {message}"),
+                        path,
+                        field.name.location(),
+                        &self.document.sources,
+                    ));
+                    Err(PropagateNull)
+                }
+            }
+        };
+        let completed_result = match middleware {
+            Some(middleware) => {
+                middleware.after(completed_result, field.ty(), errors, &self.document.sources)
+            }
+            None => completed_result,
+        };
+        try_nullify(&field_def.ty, completed_result)
+    }
+
+    /// If `field` has an active `@stream` directive, returns its arguments,
+    /// or an error message if `initialCount` is negative
+    fn eval_stream(&self, field: &Field) -> Result<Option<Stream>, &'static str> {
+        let Some(directive) = field.directives.get("stream") else {
+            return Ok(None);
+        };
+        if !eval_if_arg(&field.directives, "stream", self.variable_values).unwrap_or(true) {
+            return Ok(None);
+        }
+        let initial_count = match directive
+            .specified_argument_by_name("initialCount")
+            .map(|value| value.as_ref())
+        {
+            Some(Value::Int(int)) => int.try_to_i32().ok(),
+            Some(Value::Variable(var)) => self
+                .variable_values
+                .get(var.as_str())
+                .and_then(|value| value.as_i64())
+                .and_then(|int| i32::try_from(int).ok()),
+            _ => None,
+        };
+        let Ok(initial_count) = usize::try_from(initial_count.unwrap_or(0)) else {
+            return Err("initialCount must be a non-negative integer");
+        };
+        Ok(Some(Stream {
+            initial_count,
+            label: eval_string_arg(directive, "label", self.variable_values),
+        }))
+    }
+
+    /// <https://spec.graphql.org/October2021/#CompleteValue()>
+    ///
+    /// Returns `Err` for a field error being propagated upwards to find a nullable place.
+    ///
+    /// `stream` is only given for the value of a field, not for nested list items.
+    #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+    fn complete_value(
+        &mut self,
+        errors: &mut Vec<GraphQLError>,
+        path: LinkedPath<'_>,
+        parent_record: Option<usize>,
+        ty: &Type,
+        resolved: ResolvedValue<'_>,
+        fields: &[&'a Node<Field>],
+        stream: Option<Stream>,
+    ) -> Result<JsonValue, PropagateNull> {
+        let location = fields[0].name.location();
+        macro_rules! field_error {
+            ($($arg: tt)+) => {
+                {
+                    errors.push(GraphQLError::field_error(
+                        format!($($arg)+),
+                        path,
+                        location,
+                        &self.document.sources
+                    ));
+                    return Err(PropagateNull);
+                }
+            };
+        }
+        if let ResolvedValue::Leaf(JsonValue::Null) = resolved {
+            if ty.is_non_null() {
+                field_error!("Non-null type {ty} resolved to null")
+            } else {
+                return Ok(JsonValue::Null);
+            }
+        }
+        if let ResolvedValue::List(iter) = resolved {
+            match ty {
+                Type::Named(_) | Type::NonNullNamed(_) => {
+                    field_error!("Non-list type {ty} resolved to a list")
+                }
+                Type::List(inner_ty) | Type::NonNullList(inner_ty) => {
+                    let mut iter = iter.peekable();
+                    let initial_count = stream
+                        .as_ref()
+                        .map_or(usize::MAX, |stream| stream.initial_count);
+                    let completed_list = self.complete_list_items(
+                        errors,
+                        path,
+                        parent_record,
+                        inner_ty,
+                        iter.by_ref().take(initial_count),
+                        0,
+                        fields,
+                    );
+                    let Some(completed_list) = completed_list else {
+                        // If the item is non-null, try to nullify the list
+                        return try_nullify(ty, Err(PropagateNull));
+                    };
+                    // Only create a record if there are remaining items
+                    if let Some(Stream { label, .. }) = stream.filter(|_| iter.peek().is_some()) {
+                        let start_index = completed_list.len();
+                        let record = self.new_record(parent_record, path, label);
+                        let mut record_errors = Vec::new();
+                        let items = self
+                            .complete_list_items(
+                                &mut record_errors,
+                                path,
+                                Some(record),
+                                inner_ty,
+                                iter,
+                                start_index,
+                                fields,
+                            )
+                            .ok_or(PropagateNull);
+                        self.records[record].errors = record_errors;
+                        self.records[record].data = RecordData::Stream { start_index, items };
+                    }
+                    return Ok(completed_list.into());
+                }
+            }
+        }
+        let ty_name = match ty {
+            Type::List(_) | Type::NonNullList(_) => {
+                field_error!("List type {ty} resolved to an object")
+            }
+            Type::Named(name) | Type::NonNullNamed(name) => name,
+        };
+        let ty_def = match output_type_def(self.schema, ty_name, location) {
+            Ok(ty_def) => ty_def,
+            Err(bug) => {
+                errors.push(bug.into_field_error(&self.document.sources, path));
+                return Err(PropagateNull);
+            }
+        };
+        let resolved_obj = match resolved {
+            ResolvedValue::List(_) => unreachable!(), // early return above
+            ResolvedValue::Leaf(json_value) => {
+                match complete_leaf_value(self.executor, ty_name, ty_def, json_value) {
+                    Ok(json_value) => return Ok(json_value),
+                    Err(message) => field_error!("{message}"),
+                }
+            }
+            ResolvedValue::Object(resolved_obj) => resolved_obj,
+        };
+        let object_type =
+            match resolved_object_type(self.schema, ty_name, ty_def, resolved_obj.type_name()) {
+                Ok(object_type) => object_type,
+                Err(message) => field_error!("{message}"),
+            };
+        self.execute_selection_set(
+            errors,
+            path,
+            parent_record,
+            object_type,
+            &*resolved_obj,
+            fields
+                .iter()
+                .flat_map(|&field| &field.selection_set.selections),
+        )
+        .map(JsonValue::Object)
+    }
+
+    /// Completes list items starting at `start_index`.
+    ///
+    /// Returns `None` if a field error on a non-null item should propagate to the list.
+    #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
+    fn complete_list_items<'b>(
+        &mut self,
+        errors: &mut Vec<GraphQLError>,
+        path: LinkedPath<'_>,
+        parent_record: Option<usize>,
+        inner_ty: &Type,
+        items: impl Iterator<Item = ResolvedValue<'b>>,
+        start_index: usize,
+        fields: &[&'a Node<Field>],
+    ) -> Option<Vec<JsonValue>> {
+        let mut completed_list = Vec::with_capacity(items.size_hint().0);
+        for (index, inner_resolved) in (start_index..).zip(items) {
+            let inner_path = LinkedPathElement {
+                element: ResponseDataPathElement::ListIndex(index),
+                next: path,
+            };
+            let location = fields[0].name.location();
+            self.executor
+                .before_list_item(index, Some(&inner_path), location)
+                .ok()?;
+            let inner_result = self.complete_value(
+                errors,
+                Some(&inner_path),
+                parent_record,
+                inner_ty,
+                inner_resolved,
+                fields,
+                None,
+            );
+            // On field error, try to nullify that item
+            match try_nullify(inner_ty, inner_result) {
+                Ok(inner_value) => completed_list.push(inner_value),
+                Err(PropagateNull) => return None,
+            }
+        }
+        Some(completed_list)
+    }
+}
+
+/// Turn records into subsequent payloads, in breadth-first order.
+///
+/// A record is only delivered if the object or list at its path still exists in its parent’s data,
+/// since a field error might have propagated null above it after it was executed.
+fn into_payloads(
+    response: Response,
+    records: Vec<Record>,
+) -> (
+    InitialIncrementalResponse,
+    Vec<SubsequentIncrementalResponse>,
+) {
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); records.len()];
+    let mut root_children = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        match record.parent {
+            Some(parent) => children[parent].push(index),
+            None => root_children.push(index),
+        }
+    }
+    let mut next_id = 0;
+    let mut queue = VecDeque::new();
+    // Assign IDs to nested records that are still reachable,
+    // and queue them for delivery in later payloads
+    let mut announce =
+        |queue: &mut VecDeque<_>, parent_data: ParentData<'_>, children: &[usize]| {
+            let mut pending = Vec::new();
+            for &index in children {
+                let record = &records[index];
+                if !parent_data.contains(record) {
+                    continue;
+                }
+                let id = next_id.to_string();
+                next_id += 1;
+                pending.push(PendingResult {
+                    id: id.clone(),
+                    path: record.path.clone(),
+                    label: record.label.clone(),
+                });
+                queue.push_back((index, id));
+            }
+            pending
+        };
+
+    let pending = match &response.data {
+        ResponseData::Object(data) => announce(
+            &mut queue,
+            ParentData::Object { path: &[], data },
+            &root_children,
+        ),
+        ResponseData::Null | ResponseData::Absent => Vec::new(),
+    };
+    let Response {
+        errors,
+        data,
+        extensions,
+    } = response;
+    let initial = InitialIncrementalResponse {
+        errors,
+        data,
+        has_next: !queue.is_empty(),
+        pending,
+        extensions,
+    };
+
+    let mut subsequent = Vec::new();
+    while let Some((index, id)) = queue.pop_front() {
+        let record = &records[index];
+        let mut errors = record.errors.clone();
+        let (incremental, pending) = match &record.data {
+            RecordData::Defer(Ok(data)) => {
+                let parent_data = ParentData::Object {
+                    path: &record.path,
+                    data,
+                };
+                let pending = announce(&mut queue, parent_data, &children[index]);
+                let incremental = IncrementalResult::Defer {
+                    id: id.clone(),
+                    data: data.clone(),
+                    errors: std::mem::take(&mut errors),
+                };
+                (Some(incremental), pending)
+            }
+            RecordData::Stream {
+                start_index,
+                items: Ok(items),
+            } => {
+                let parent_data = ParentData::List {
+                    path: &record.path,
+                    start_index: *start_index,
+                    items,
+                };
+                let pending = announce(&mut queue, parent_data, &children[index]);
+                let incremental = IncrementalResult::Stream {
+                    id: id.clone(),
+                    items: items.clone(),
+                    errors: std::mem::take(&mut errors),
+                };
+                (Some(incremental), pending)
+            }
+            RecordData::Defer(Err(PropagateNull))
+            | RecordData::Stream {
+                items: Err(PropagateNull),
+                ..
+            } => (None, Vec::new()),
+        };
+        subsequent.push(SubsequentIncrementalResponse {
+            pending,
+            incremental: incremental.into_iter().collect(),
+            completed: vec![CompletedResult { id, errors }],
+            has_next: !queue.is_empty(),
+            extensions: Default::default(),
+        })
+    }
+    (initial, subsequent)
+}
+
+/// Data delivered for a record (or the initial response),
+/// used to check whether the paths of nested records still exist.
+enum ParentData<'a> {
+    Object {
+        path: &'a [ResponseDataPathElement],
+        data: &'a JsonMap,
+    },
+    List {
+        path: &'a [ResponseDataPathElement],
+        start_index: usize,
+        items: &'a [JsonValue],
+    },
+}
+
+impl ParentData<'_> {
+    fn contains(&self, record: &Record) -> bool {
+        let (ParentData::Object { path, .. } | ParentData::List { path, .. }) = self;
+        let Some(relative) = record.path.strip_prefix(*path) else {
+            return false;
+        };
+        let Some((first, rest)) = relative.split_first() else {
+            // A deferred fragment on the same object as its parent
+            return true;
+        };
+        let value = match (self, first) {
+            (ParentData::Object { data, .. }, ResponseDataPathElement::Field(key)) => {
+                data.get(key.as_str())
+            }
+            (
+                ParentData::List {
+                    start_index, items, ..
+                },
+                ResponseDataPathElement::ListIndex(index),
+            ) => index
+                .checked_sub(*start_index)
+                .and_then(|index| items.get(index)),
+            _ => None,
+        };
+        let Some(mut value) = value else {
+            return false;
+        };
+        for element in rest {
+            let next = match (element, value) {
+                (ResponseDataPathElement::Field(key), JsonValue::Object(map)) => {
+                    map.get(key.as_str())
+                }
+                (ResponseDataPathElement::ListIndex(index), JsonValue::Array(list)) => {
+                    list.get(*index)
+                }
+                _ => None,
+            };
+            let Some(next) = next else { return false };
+            value = next
+        }
+        match record.data {
+            RecordData::Defer(_) => value.is_object(),
+            RecordData::Stream { .. } => value.is_array(),
+        }
+    }
+}
@@ -5,10 +5,12 @@ use crate::executable::Field;
 use crate::executable::Operation;
 use crate::execution::engine::LinkedPath;
 use crate::execution::engine::PropagateNull;
+use crate::execution::Executor;
 use crate::execution::GraphQLError;
 use crate::execution::JsonMap;
 use crate::execution::JsonValue;
 use crate::execution::Response;
+use crate::execution::ResponseData;
 use crate::parser::SourceMap;
 use crate::parser::SourceSpan;
 use crate::schema::ExtendedType;
@@ -40,34 +42,141 @@ pub fn coerce_variable_values(
     operation: &Operation,
     values: &JsonMap,
 ) -> Result<Valid<JsonMap>, InputCoercionError> {
-    let mut coerced_values = JsonMap::new();
-    for variable_def in &operation.variables {
-        let name = variable_def.name.as_str();
-        if let Some((key, value)) = values.get_key_value(name) {
-            let value =
-                coerce_variable_value(schema, "variable", "", "", name, &variable_def.ty, value)?;
-            coerced_values.insert(key.clone(), value);
-        } else if let Some(default) = &variable_def.default_value {
-            let value = graphql_value_to_json("variable default value", "", "", name, default)?;
-            coerced_values.insert(name, value);
-        } else if variable_def.ty.is_non_null() {
-            return Err(InputCoercionError::ValueError {
-                message: format!("missing value for non-null variable '{name}'"),
-                location: variable_def.location(),
-            });
-        } else {
-            // Nullable variable with no provided value nor explicit default.
-            // Spec says nothing for this case, but for the similar case in input objects:
-            //
-            // > there is a semantic difference between the explicitly provided value null
-            // > versus having not provided a value
+    Executor::default_ref().coerce_variable_values(schema, operation, values)
+}
+
+/// Errors from [`Executor::coerce_variable_values_with_sources`]
+pub(crate) enum VariableCoercionError {
+    Input(InputCoercionError),
+    /// A default value in the operation could not be coerced.
+    /// Errors are already resolved against the sources given for coercion.
+    DefaultValue {
+        errors: Vec<GraphQLError>,
+        location: Option<SourceSpan>,
+    },
+}
+
+impl VariableCoercionError {
+    /// Convert into a response with these errors as [request errors]
+    ///
+    /// [request errors]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+    pub(crate) fn into_response(self, sources: &SourceMap) -> Response {
+        match self {
+            Self::Input(err) => err.into_response(sources),
+            Self::DefaultValue { errors, .. } => Response {
+                errors,
+                data: ResponseData::Absent,
+                extensions: JsonMap::new(),
+            },
         }
     }
-    Ok(Valid(coerced_values))
+}
+
+impl Executor {
+    /// Like [`coerce_variable_values`], with the configuration of `self`
+    pub fn coerce_variable_values(
+        &self,
+        schema: &Valid<Schema>,
+        operation: &Operation,
+        values: &JsonMap,
+    ) -> Result<Valid<JsonMap>, InputCoercionError> {
+        // Without the document, locations within default values can’t be resolved:
+        // report the first error at the variable default value instead
+        self.coerce_variable_values_with_sources(schema, &SourceMap::default(), operation, values)
+            .map_err(|err| match err {
+                VariableCoercionError::Input(err) => err,
+                VariableCoercionError::DefaultValue { errors, location } => {
+                    InputCoercionError::ValueError {
+                        message: errors
+                            .into_iter()
+                            .next()
+                            .map(|error| error.message)
+                            .unwrap_or_default(),
+                        location,
+                    }
+                }
+            })
+    }
+
+    /// Like [`Executor::coerce_variable_values`], with the sources of the operation’s document
+    /// so that every error in a default value has a location
+    pub(crate) fn coerce_variable_values_with_sources(
+        &self,
+        schema: &Valid<Schema>,
+        sources: &SourceMap,
+        operation: &Operation,
+        values: &JsonMap,
+    ) -> Result<Valid<JsonMap>, VariableCoercionError> {
+        let mut coerced_values = JsonMap::new();
+        for variable_def in &operation.variables {
+            let name = variable_def.name.as_str();
+            if let Some((key, value)) = values.get_key_value(name) {
+                let value = coerce_variable_value(
+                    self,
+                    schema,
+                    "variable",
+                    "",
+                    "",
+                    name,
+                    &variable_def.ty,
+                    value,
+                )
+                .map_err(|err| match err {
+                    InputCoercionError::ValueError {
+                        message,
+                        location: None,
+                    } => InputCoercionError::ValueError {
+                        message,
+                        location: variable_def.location(),
+                    },
+                    err => err,
+                })
+                .map_err(VariableCoercionError::Input)?;
+                coerced_values.insert(key.clone(), value);
+            } else if let Some(default) = &variable_def.default_value {
+                // Default values are constants, so no variable is needed
+                let mut errors = Vec::new();
+                let value = coerce_argument_value(
+                    self,
+                    schema,
+                    sources,
+                    &Valid(JsonMap::new()),
+                    &mut errors,
+                    None,
+                    "variable default value",
+                    "",
+                    "",
+                    name,
+                    &variable_def.ty,
+                    default,
+                )
+                .map_err(|PropagateNull| VariableCoercionError::DefaultValue {
+                    errors,
+                    location: default.location(),
+                })?;
+                coerced_values.insert(name, value);
+            } else if variable_def.ty.is_non_null() {
+                return Err(VariableCoercionError::Input(
+                    InputCoercionError::ValueError {
+                        message: format!("missing value for non-null variable '{name}'"),
+                        location: variable_def.location(),
+                    },
+                ));
+            } else {
+                // Nullable variable with no provided value nor explicit default.
+                // Spec says nothing for this case, but for the similar case in input objects:
+                //
+                // > there is a semantic difference between the explicitly provided value null
+                // > versus having not provided a value
+            }
+        }
+        Ok(Valid(coerced_values))
+    }
 }
 
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 fn coerce_variable_value(
+    executor: &Executor,
     schema: &Valid<Schema>,
     kind: &str,
     parent: &str,
@@ -95,7 +204,9 @@ fn coerce_variable_value(
                 // If not an array, treat the value as an array of size one:
                 .unwrap_or(std::slice::from_ref(value))
                 .iter()
-                .map(|item| coerce_variable_value(schema, kind, parent, sep, name, inner, item))
+                .map(|item| {
+                    coerce_variable_value(executor, schema, kind, parent, sep, name, inner, item)
+                })
                 .collect();
         }
         Type::Named(ty_name) | Type::NonNullNamed(ty_name) => ty_name,
@@ -149,8 +260,17 @@ fn coerce_variable_value(
             }
             _ => {
                 // Custom scalar
-                // TODO: have a hook for coercion of custom scalars?
-                return Ok(value.clone());
+                let Some(custom_scalar) = executor.scalar_coercion.for_type(ty_def) else {
+                    return Ok(value.clone());
+                };
+                return custom_scalar.coerce_variable_value(value).map_err(|message| {
+                    InputCoercionError::ValueError {
+                        message: format!(
+                            "Could not coerce {kind} {parent}{sep}{name}: {value} to type {ty_name}: {message}"
+                        ),
+                        location: None,
+                    }
+                });
             }
         },
         ExtendedType::Enum(ty_def) => {
@@ -176,10 +296,33 @@ fn coerce_variable_value(
                         location: None,
                     });
                 }
+                if ty_def.is_one_of() {
+                    // https://github.com/graphql/graphql-spec/pull/825
+                    let mut entries = object.iter();
+                    let (Some((key, field_value)), None) = (entries.next(), entries.next()) else {
+                        return Err(InputCoercionError::ValueError {
+                            message: format!(
+                                "OneOf input object {ty_name} must specify exactly one key, found {}",
+                                object.len()
+                            ),
+                            location: None,
+                        });
+                    };
+                    if field_value.is_null() {
+                        return Err(InputCoercionError::ValueError {
+                            message: format!(
+                                "null value for field {ty_name}.{} of OneOf input object",
+                                key.as_str()
+                            ),
+                            location: None,
+                        });
+                    }
+                }
                 let mut object = object.clone();
                 for (field_name, field_def) in &ty_def.fields {
                     if let Some(field_value) = object.get_mut(field_name.as_str()) {
                         *field_value = coerce_variable_value(
+                            executor,
                             schema,
                             "input field",
                             ty_name,
@@ -216,7 +359,7 @@ fn coerce_variable_value(
     })
 }
 
-fn graphql_value_to_json(
+pub(crate) fn graphql_value_to_json(
     kind: &str,
     parent: &str,
     sep: &str,
@@ -267,7 +410,9 @@ fn graphql_value_to_json(
 }
 
 /// <https://spec.graphql.org/October2021/#sec-Coercing-Field-Arguments>
+#[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 pub(crate) fn coerce_argument_values(
+    executor: &Executor,
     schema: &Schema,
     document: &Valid<ExecutableDocument>,
     variable_values: &Valid<JsonMap>,
@@ -305,8 +450,9 @@ pub(crate) fn coerce_argument_values(
                 return Err(PropagateNull);
             } else {
                 let coerced_value = coerce_argument_value(
+                    executor,
                     schema,
-                    document,
+                    &document.sources,
                     variable_values,
                     errors,
                     path,
@@ -322,11 +468,20 @@ pub(crate) fn coerce_argument_values(
             }
         }
         if let Some(default) = &arg_def.default_value {
-            let value =
-                graphql_value_to_json("argument", "", "", arg_name, default).map_err(|err| {
-                    errors.push(err.into_field_error(path, &document.sources));
-                    PropagateNull
-                })?;
+            let value = coerce_argument_value(
+                executor,
+                schema,
+                &document.sources,
+                variable_values,
+                errors,
+                path,
+                "argument",
+                "",
+                "",
+                arg_name,
+                &arg_def.ty,
+                default,
+            )?;
             coerced_values.insert(arg_def.name.as_str(), value);
             continue;
         }
@@ -345,8 +500,9 @@ pub(crate) fn coerce_argument_values(
 
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 fn coerce_argument_value(
+    executor: &Executor,
     schema: &Schema,
-    document: &Valid<ExecutableDocument>,
+    sources: &SourceMap,
     variable_values: &Valid<JsonMap>,
     errors: &mut Vec<GraphQLError>,
     path: LinkedPath<'_>,
@@ -363,7 +519,7 @@ fn coerce_argument_value(
                 format!("null value for non-null {kind} {parent}{sep}{name}"),
                 path,
                 value.location(),
-                &document.sources,
+                sources,
             ));
             return Err(PropagateNull);
         } else {
@@ -377,7 +533,7 @@ fn coerce_argument_value(
                     format!("null variable value for non-null {kind} {parent}{sep}{name}"),
                     path,
                     value.location(),
-                    &document.sources,
+                    sources,
                 ));
                 return Err(PropagateNull);
             } else {
@@ -388,7 +544,7 @@ fn coerce_argument_value(
                 format!("missing variable for non-null {kind} {parent}{sep}{name}"),
                 path,
                 value.location(),
-                &document.sources,
+                sources,
             ));
             return Err(PropagateNull);
         } else {
@@ -405,8 +561,9 @@ fn coerce_argument_value(
                 .iter()
                 .map(|item| {
                     coerce_argument_value(
+                        executor,
                         schema,
-                        document,
+                        sources,
                         variable_values,
                         errors,
                         path,
@@ -428,10 +585,21 @@ fn coerce_argument_value(
                 message: format!("Undefined type {ty_name} for {kind} {parent}{sep}{name}"),
                 location: value.location(),
             }
-            .into_field_error(&document.sources, path),
+            .into_field_error(sources, path),
         );
         return Err(PropagateNull);
     };
+    if let Some(custom_scalar) = executor.scalar_coercion.for_type(ty_def) {
+        return custom_scalar.coerce_literal(value).map_err(|message| {
+            errors.push(GraphQLError::field_error(
+                format!("Could not coerce {kind} {parent}{sep}{name}: {value} to type {ty_name}: {message}"),
+                path,
+                value.location(),
+                sources,
+            ));
+            PropagateNull
+        });
+    }
     match ty_def {
         ExtendedType::InputObject(ty_def) => {
             // https://spec.graphql.org/October2021/#sec-Input-Objects.Input-Coercion
@@ -444,7 +612,7 @@ fn coerce_argument_value(
                         format!("Input object has key {key} not in type {ty_name}",),
                         path,
                         value.location(),
-                        &document.sources,
+                        sources,
                     ));
                     return Err(PropagateNull);
                 }
@@ -454,8 +622,9 @@ fn coerce_argument_value(
                 for (field_name, field_def) in &ty_def.fields {
                     if let Some(field_value) = object.get(field_name) {
                         let coerced_value = coerce_argument_value(
+                            executor,
                             schema,
-                            document,
+                            sources,
                             variable_values,
                             errors,
                             path,
@@ -468,12 +637,20 @@ fn coerce_argument_value(
                         )?;
                         coerced_object.insert(field_name.as_str(), coerced_value);
                     } else if let Some(default) = &field_def.default_value {
-                        let default =
-                            graphql_value_to_json("input field", ty_name, ".", field_name, default)
-                                .map_err(|err| {
-                                    errors.push(err.into_field_error(path, &document.sources));
-                                    PropagateNull
-                                })?;
+                        let default = coerce_argument_value(
+                            executor,
+                            schema,
+                            sources,
+                            variable_values,
+                            errors,
+                            path,
+                            "input field",
+                            ty_name,
+                            ".",
+                            field_name,
+                            &field_def.ty,
+                            default,
+                        )?;
                         coerced_object.insert(field_name.as_str(), default);
                     } else if field_def.ty.is_non_null() {
                         errors.push(GraphQLError::field_error(
@@ -482,7 +659,7 @@ fn coerce_argument_value(
                             ),
                             path,
                             value.location(),
-                            &document.sources,
+                            sources,
                         ));
                         return Err(PropagateNull);
                     } else {
@@ -495,7 +672,7 @@ fn coerce_argument_value(
         _ => {
             // For scalar and enums, rely and validation and just convert between Rust types
             return graphql_value_to_json(kind, parent, sep, name, value).map_err(|err| {
-                errors.push(err.into_field_error(path, &document.sources));
+                errors.push(err.into_field_error(path, sources));
                 PropagateNull
             });
         }
@@ -504,7 +681,7 @@ fn coerce_argument_value(
         format!("Could not coerce {kind} {parent}{sep}{name}: {value} to type {ty_name}"),
         path,
         value.location(),
-        &document.sources,
+        sources,
     ));
     Err(PropagateNull)
 }
@@ -1,9 +1,10 @@
 use crate::collections::HashMap;
 use crate::executable::Operation;
 use crate::executable::OperationType;
-use crate::execution::engine::execute_selection_set;
 use crate::execution::engine::ExecutionMode;
+use crate::execution::execute::execute_root_selection_set;
 use crate::execution::resolver::ResolvedValue;
+use crate::execution::Executor;
 use crate::execution::GraphQLError;
 use crate::execution::JsonMap;
 use crate::execution::Response;
@@ -12,6 +13,8 @@ use crate::execution::SchemaIntrospectionSplit;
 use crate::schema;
 use crate::schema::Implementers;
 use crate::schema::Name;
+use crate::schema::SchemaVisibility;
+use crate::schema::VisibilityCheck;
 use crate::validation::Valid;
 use crate::ExecutableDocument;
 use crate::Node;
@@ -69,10 +72,28 @@ impl SchemaIntrospectionQuery {
         operation: &Node<Operation>,
         variable_values: &Valid<JsonMap>,
         execute_non_introspection_parts: impl FnOnce(&Valid<ExecutableDocument>) -> Response,
+    ) -> Response {
+        Self::split_and_execute_with(
+            Executor::default_ref(),
+            schema,
+            document,
+            operation,
+            variable_values,
+            execute_non_introspection_parts,
+        )
+    }
+
+    pub(crate) fn split_and_execute_with(
+        executor: &Executor,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Node<Operation>,
+        variable_values: &Valid<JsonMap>,
+        execute_non_introspection_parts: impl FnOnce(&Valid<ExecutableDocument>) -> Response,
     ) -> Response {
         match SchemaIntrospectionSplit::split(schema, document, operation) {
             Ok(SchemaIntrospectionSplit::Only(introspection_query)) => {
-                introspection_query.execute(schema, variable_values)
+                introspection_query.execute_with(executor, schema, variable_values)
             }
             Ok(SchemaIntrospectionSplit::None) => execute_non_introspection_parts(document),
             Ok(SchemaIntrospectionSplit::Both {
@@ -81,7 +102,8 @@ impl SchemaIntrospectionQuery {
             }) => {
                 let non_introspection_response =
                     execute_non_introspection_parts(&filtered_operation);
-                let introspection_response = introspection_query.execute(schema, variable_values);
+                let introspection_response =
+                    introspection_query.execute_with(executor, schema, variable_values);
                 non_introspection_response.merge(introspection_response)
             }
             Err(err) => err.into_response(&document.sources),
@@ -96,6 +118,36 @@ impl SchemaIntrospectionQuery {
         let operation = self.0.operations.get(None).unwrap();
         execute_introspection_only_query(schema, &self.0, operation, variable_values)
     }
+
+    /// Like [`execute`][Self::execute], but schema elements hidden by `visibility`
+    /// are absent from the response
+    pub fn execute_with_visibility(
+        &self,
+        schema: &Valid<Schema>,
+        variable_values: &Valid<JsonMap>,
+        visibility: &dyn SchemaVisibility,
+    ) -> Response {
+        let operation = self.0.operations.get(None).unwrap();
+        execute_introspection(
+            schema,
+            &self.0,
+            operation,
+            variable_values,
+            Some(visibility),
+        )
+    }
+
+    /// Executes with the [schema visibility][Executor::schema_visibility] of `executor`, if any
+    pub(crate) fn execute_with(
+        &self,
+        executor: &Executor,
+        schema: &Valid<Schema>,
+        variable_values: &Valid<JsonMap>,
+    ) -> Response {
+        let operation = self.0.operations.get(None).unwrap();
+        let visibility = executor.schema_visibility.as_deref();
+        execute_introspection(schema, &self.0, operation, variable_values, visibility)
+    }
 }
 
 /// Execute a query whose [root fields][Operation::root_fields] are all intropsection meta-fields:
@@ -107,6 +159,16 @@ pub fn execute_introspection_only_query(
     document: &Valid<ExecutableDocument>,
     operation: &Node<Operation>,
     variable_values: &Valid<JsonMap>,
+) -> Response {
+    execute_introspection(schema, document, operation, variable_values, None)
+}
+
+fn execute_introspection(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Node<Operation>,
+    variable_values: &Valid<JsonMap>,
+    visibility: Option<&dyn SchemaVisibility>,
 ) -> Response {
     if operation.operation_type != OperationType::Query {
         return Response::from_request_error(GraphQLError::new(
@@ -119,48 +181,80 @@ pub fn execute_introspection_only_query(
         ));
     }
 
-    // https://spec.graphql.org/October2021/#sec-Query
-    let object_type_name = operation.object_type();
-    let Some(object_type_def) = schema.get_object(object_type_name) else {
-        return Response::from_request_error(GraphQLError::new(
-            "Undefined root operation type",
-            object_type_name.location(),
-            &document.sources,
-        ));
-    };
     let implementers_map = &OnceLock::new();
     let initial_value = &IntrospectionRootResolver(SchemaWithCache {
         schema,
         implementers_map,
+        visibility,
     });
-
-    let mut errors = Vec::new();
-    let path = None;
-    let data = execute_selection_set(
+    execute_root_selection_set(
+        // Introspection types don’t use custom scalars
+        Executor::default_ref(),
         schema,
         document,
+        operation,
         variable_values,
-        &mut errors,
-        path,
         ExecutionMode::Normal,
-        object_type_def,
         initial_value,
-        &operation.selection_set.selections,
-    );
-    Response {
-        data: data.into(),
-        errors,
-        extensions: Default::default(),
-    }
+    )
 }
 
 #[derive(Clone, Copy)]
 struct SchemaWithCache<'a> {
     schema: &'a Schema,
     implementers_map: &'a OnceLock<HashMap<Name, Implementers>>,
+    visibility: Option<&'a dyn SchemaVisibility>,
 }
 
 impl<'a> SchemaWithCache<'a> {
+    fn check(&self) -> Option<VisibilityCheck<'a>> {
+        self.visibility.map(|visibility| VisibilityCheck {
+            schema: self.schema,
+            visibility,
+        })
+    }
+
+    fn is_type_visible(&self, def: &schema::ExtendedType) -> bool {
+        self.visibility
+            .is_none_or(|visibility| visibility.is_type_visible(def))
+    }
+
+    fn is_field_visible(
+        &self,
+        parent_type: &schema::ExtendedType,
+        def: &schema::FieldDefinition,
+    ) -> bool {
+        self.check()
+            .is_none_or(|check| check.field(parent_type, def))
+    }
+
+    fn is_argument_visible(&self, def: &schema::InputValueDefinition) -> bool {
+        self.check().is_none_or(|check| check.argument(def))
+    }
+
+    fn is_input_field_visible(
+        &self,
+        parent_type: &schema::ExtendedType,
+        def: &schema::InputValueDefinition,
+    ) -> bool {
+        self.check()
+            .is_none_or(|check| check.input_field(parent_type, def))
+    }
+
+    fn is_enum_value_visible(
+        &self,
+        parent_type: &schema::ExtendedType,
+        def: &schema::EnumValueDefinition,
+    ) -> bool {
+        self.visibility
+            .is_none_or(|visibility| visibility.is_enum_value_visible(parent_type, def))
+    }
+
+    fn is_directive_visible(&self, def: &schema::DirectiveDefinition) -> bool {
+        self.visibility
+            .is_none_or(|visibility| visibility.is_directive_visible(def))
+    }
+
     fn implementers_of(&self, interface_name: &str) -> impl Iterator<Item = &'a Name> {
         self.implementers_map
             .get_or_init(|| self.schema.implementers_map())
@@ -217,6 +311,7 @@ fn type_def(schema: SchemaWithCache<'_>, name: impl AsRef<str>) -> ResolvedValue
         schema
             .types
             .get_key_value(name.as_ref())
+            .filter(|(_, def)| schema.is_type_visible(def))
             .map(|(name, def)| TypeDefResolver { schema, name, def }),
     )
 }
@@ -279,15 +374,25 @@ impl_resolver! {
     }
 
     fn types(&self_) {
-        Ok(ResolvedValue::list(self_.types.iter().map(|(name, def)| {
-            ResolvedValue::object(TypeDefResolver { schema: *self_, name, def })
-        })))
+        Ok(ResolvedValue::list(self_
+            .types
+            .iter()
+            .filter(|(_, def)| self_.is_type_visible(def))
+            .map(|(name, def)| {
+                ResolvedValue::object(TypeDefResolver { schema: *self_, name, def })
+            })
+        ))
     }
 
     fn directives(&self_) {
-        Ok(ResolvedValue::list(self_.directive_definitions.values().map(|def| {
-            ResolvedValue::object(DirectiveResolver { schema: *self_, def })
-        })))
+        Ok(ResolvedValue::list(self_
+            .directive_definitions
+            .values()
+            .filter(|def| self_.is_directive_visible(def))
+            .map(|def| {
+                ResolvedValue::object(DirectiveResolver { schema: *self_, def })
+            })
+        ))
     }
 
     fn queryType(&self_) {
@@ -342,6 +447,7 @@ impl_resolver! {
             .filter(move |def| {
                 include_deprecated || def.directives.get("deprecated").is_none()
             })
+            .filter(|def| self_.schema.is_field_visible(self_.def, def))
             .map(|def| {
                 ResolvedValue::object(FieldResolver { schema: self_.schema, def })
             })
@@ -358,7 +464,9 @@ impl_resolver! {
             schema::ExtendedType::InputObject(_) => return Ok(ResolvedValue::null()),
         };
         Ok(ResolvedValue::list(implements_interfaces.iter().filter_map(|name| {
-            self_.schema.types.get(&name.name).map(|def| {
+            self_.schema.types.get(&name.name).filter(|def| {
+                self_.schema.is_type_visible(def)
+            }).map(|def| {
                 ResolvedValue::object(TypeDefResolver { schema: self_.schema, name, def })
             })
         })))
@@ -368,7 +476,9 @@ impl_resolver! {
         macro_rules! types {
             ($names: expr) => {
                 Ok(ResolvedValue::list($names.filter_map(move |name| {
-                    self_.schema.types.get(name).map(move |def| {
+                    self_.schema.types.get(name).filter(|def| {
+                        self_.schema.is_type_visible(def)
+                    }).map(move |def| {
                         ResolvedValue::object(TypeDefResolver { schema: self_.schema, name, def })
                     })
                 })))
@@ -395,6 +505,7 @@ impl_resolver! {
             .filter(move |def| {
                 include_deprecated || def.directives.get("deprecated").is_none()
             })
+            .filter(|def| self_.schema.is_enum_value_visible(self_.def, def))
             .map(|def| {
                 ResolvedValue::object(EnumValueResolver { schema: self_.schema, def })
             })
@@ -412,6 +523,7 @@ impl_resolver! {
             .filter(move |def| {
                 include_deprecated || def.directives.get("deprecated").is_none()
             })
+            .filter(|def| self_.schema.is_input_field_visible(self_.def, def))
             .map(|def| {
                 ResolvedValue::object(InputValueResolver { schema: self_.schema, def })
             })
@@ -432,6 +544,13 @@ impl_resolver! {
             .and_then(|arg| arg.as_str())
         ))
     }
+
+    fn isOneOf(&self_) {
+        let schema::ExtendedType::InputObject(def) = self_.def else {
+            return Ok(ResolvedValue::null())
+        };
+        Ok(ResolvedValue::leaf(def.is_one_of()))
+    }
 }
 
 // Only used for non-null and list types
@@ -469,6 +588,7 @@ impl_resolver! {
     fn enumValues() { Ok(ResolvedValue::null()) }
     fn inputFields() { Ok(ResolvedValue::null()) }
     fn specifiedByURL() { Ok(ResolvedValue::null()) }
+    fn isOneOf() { Ok(ResolvedValue::null()) }
 }
 
 impl_resolver! {
@@ -493,6 +613,7 @@ impl_resolver! {
             .filter(move |def| {
                 include_deprecated || def.directives.get("deprecated").is_none()
             })
+            .filter(|def| self_.schema.is_argument_visible(def))
             .map(|def| {
                 ResolvedValue::object(InputValueResolver { schema: self_.schema, def })
             })
@@ -532,6 +653,7 @@ impl_resolver! {
             .filter(move |def| {
                 include_deprecated || def.directives.get("deprecated").is_none()
             })
+            .filter(|def| self_.schema.is_argument_visible(def))
             .map(|def| {
                 ResolvedValue::object(InputValueResolver { schema: self_.schema, def })
             })
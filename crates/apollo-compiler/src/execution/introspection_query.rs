@@ -0,0 +1,245 @@
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Schema;
+
+/// Options for generating the standard [schema introspection] query,
+/// the equivalent of `getIntrospectionQuery` in graphql-js.
+///
+/// The query fetches everything that
+/// [`Schema::from_introspection`] needs to rebuild a schema.
+/// Defaults match graphql-js: descriptions are included,
+/// and fields added to introspection in recent versions of the specification are not,
+/// since older servers would reject them.
+///
+/// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::IntrospectionQueryOptions;
+///
+/// let query = IntrospectionQueryOptions::new()
+///     .specified_by_url(true)
+///     .directive_is_repeatable(true)
+///     .source_text();
+/// assert!(query.starts_with("query IntrospectionQuery {"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntrospectionQueryOptions {
+    descriptions: bool,
+    specified_by_url: bool,
+    directive_is_repeatable: bool,
+    schema_description: bool,
+    input_value_deprecation: bool,
+    one_of: bool,
+    type_depth: usize,
+}
+
+impl Default for IntrospectionQueryOptions {
+    fn default() -> Self {
+        Self {
+            descriptions: true,
+            specified_by_url: false,
+            directive_is_repeatable: false,
+            schema_description: false,
+            input_value_deprecation: false,
+            one_of: false,
+            type_depth: 7,
+        }
+    }
+}
+
+impl IntrospectionQueryOptions {
+    /// Returns the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to select `description` of types, fields, arguments, enum values,
+    /// and directives. Defaults to `true`.
+    pub fn descriptions(mut self, value: bool) -> Self {
+        self.descriptions = value;
+        self
+    }
+
+    /// Whether to select `__Type.specifiedByURL`. Defaults to `false`.
+    pub fn specified_by_url(mut self, value: bool) -> Self {
+        self.specified_by_url = value;
+        self
+    }
+
+    /// Whether to select `__Directive.isRepeatable`. Defaults to `false`.
+    pub fn directive_is_repeatable(mut self, value: bool) -> Self {
+        self.directive_is_repeatable = value;
+        self
+    }
+
+    /// Whether to select `__Schema.description`. Defaults to `false`.
+    pub fn schema_description(mut self, value: bool) -> Self {
+        self.schema_description = value;
+        self
+    }
+
+    /// Whether to include deprecated arguments and input fields,
+    /// and select their `isDeprecated` and `deprecationReason`. Defaults to `false`.
+    pub fn input_value_deprecation(mut self, value: bool) -> Self {
+        self.input_value_deprecation = value;
+        self
+    }
+
+    /// Whether to select `__Type.isOneOf`. Defaults to `false`.
+    pub fn one_of(mut self, value: bool) -> Self {
+        self.one_of = value;
+        self
+    }
+
+    /// Set how many levels of `ofType` are selected in type references.
+    /// Defaults to 7, enough for types like `[[[Int!]!]!]!`.
+    pub fn type_depth(mut self, value: usize) -> Self {
+        self.type_depth = value;
+        self
+    }
+
+    /// Returns the source text of the query, for example to send to a remote endpoint
+    pub fn source_text(&self) -> String {
+        let args = if self.input_value_deprecation {
+            "(includeDeprecated: true)"
+        } else {
+            ""
+        };
+        let mut query = Lines::default();
+        query.open("query IntrospectionQuery");
+        query.open("__schema");
+        query.push_if(self.schema_description, "description");
+        for root in ["queryType", "mutationType", "subscriptionType"] {
+            query.open(root);
+            query.push("name");
+            query.close();
+        }
+        query.open("types");
+        query.push("...FullType");
+        query.close();
+        query.open("directives");
+        query.push("name");
+        query.push_if(self.descriptions, "description");
+        query.push_if(self.directive_is_repeatable, "isRepeatable");
+        query.push("locations");
+        query.open(&format!("args{args}"));
+        query.push("...InputValue");
+        query.close();
+        query.close();
+        query.close();
+        query.close();
+
+        query.push("");
+        query.open("fragment FullType on __Type");
+        query.push("kind");
+        query.push("name");
+        query.push_if(self.descriptions, "description");
+        query.push_if(self.specified_by_url, "specifiedByURL");
+        query.push_if(self.one_of, "isOneOf");
+        query.open("fields(includeDeprecated: true)");
+        query.push("name");
+        query.push_if(self.descriptions, "description");
+        query.open(&format!("args{args}"));
+        query.push("...InputValue");
+        query.close();
+        query.open("type");
+        query.push("...TypeRef");
+        query.close();
+        query.push("isDeprecated");
+        query.push("deprecationReason");
+        query.close();
+        query.open(&format!("inputFields{args}"));
+        query.push("...InputValue");
+        query.close();
+        query.open("interfaces");
+        query.push("...TypeRef");
+        query.close();
+        query.open("enumValues(includeDeprecated: true)");
+        query.push("name");
+        query.push_if(self.descriptions, "description");
+        query.push("isDeprecated");
+        query.push("deprecationReason");
+        query.close();
+        query.open("possibleTypes");
+        query.push("...TypeRef");
+        query.close();
+        query.close();
+
+        query.push("");
+        query.open("fragment InputValue on __InputValue");
+        query.push("name");
+        query.push_if(self.descriptions, "description");
+        query.open("type");
+        query.push("...TypeRef");
+        query.close();
+        query.push("defaultValue");
+        query.push_if(self.input_value_deprecation, "isDeprecated");
+        query.push_if(self.input_value_deprecation, "deprecationReason");
+        query.close();
+
+        query.push("");
+        query.open("fragment TypeRef on __Type");
+        query.push("kind");
+        query.push("name");
+        for _ in 0..self.type_depth {
+            query.open("ofType");
+            query.push("kind");
+            query.push("name");
+        }
+        for _ in 0..self.type_depth {
+            query.close();
+        }
+        query.close();
+        query.text
+    }
+
+    /// Returns the query as a document validated against `schema`,
+    /// for example to run it with
+    /// [`execute_introspection_only_query`][crate::execution::execute_introspection_only_query].
+    ///
+    /// Introspection types are the same in every schema, so validation always succeeds.
+    pub fn document(&self, schema: &Valid<Schema>) -> Valid<ExecutableDocument> {
+        ExecutableDocument::parse_and_validate(
+            schema,
+            self.source_text(),
+            "introspection_query.graphql",
+        )
+        .expect("generated introspection query should be valid")
+    }
+}
+
+/// Builds indented source text one line at a time
+#[derive(Default)]
+struct Lines {
+    text: String,
+    indent: usize,
+}
+
+impl Lines {
+    fn push(&mut self, line: &str) {
+        for _ in 0..self.indent {
+            self.text.push_str("  ")
+        }
+        self.text.push_str(line);
+        self.text.push('\n')
+    }
+
+    fn push_if(&mut self, condition: bool, line: &str) {
+        if condition {
+            self.push(line)
+        }
+    }
+
+    /// Starts a selection set
+    fn open(&mut self, prefix: &str) {
+        self.push(&format!("{prefix} {{"));
+        self.indent += 1
+    }
+
+    fn close(&mut self) {
+        self.indent -= 1;
+        self.push("}")
+    }
+}
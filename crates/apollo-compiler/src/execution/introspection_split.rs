@@ -1,21 +1,22 @@
-use crate::ast;
+use crate::collections::HashMap;
 use crate::collections::HashSet;
 use crate::collections::IndexMap;
 use crate::executable::Field;
 use crate::executable::Fragment;
-use crate::executable::FragmentSpread;
-use crate::executable::InlineFragment;
 use crate::executable::Operation;
 use crate::executable::OperationType;
 use crate::executable::Selection;
 use crate::executable::SelectionSet;
 use crate::execution::introspection_max_depth::DeeplyNestedIntrospectionListError;
+use crate::execution::operation_split::make_single_operation_document;
+use crate::execution::operation_split::split_selection_set;
+use crate::execution::operation_split::DocumentBuilder;
+use crate::execution::operation_split::Split;
 use crate::execution::GraphQLError;
 use crate::execution::Response;
 use crate::execution::SchemaIntrospectionQuery;
 use crate::parser::SourceMap;
 use crate::parser::SourceSpan;
-use crate::schema;
 use crate::schema::Name;
 use crate::validation::SuspectedValidationBug;
 use crate::validation::Valid;
@@ -89,6 +90,8 @@ impl SchemaIntrospectionSplit {
     /// to “plug in” such resolvers. Instead, this function splits an operation
     /// into either or both introspection and other parts that can be executed separately.
     /// Full execution of introspection parts is provided by [`SchemaIntrospectionQuery::execute`].
+    /// [`OperationSplit`][crate::execution::OperationSplit] splits operations
+    /// by other criteria in the same way.
     ///
     /// In an unconventional schema
     /// where the type of the `query` operation is also the type of some field,
@@ -129,22 +132,23 @@ impl SchemaIntrospectionSplit {
                 SchemaIntrospectionQuery::assume_only_intropsection_fields(introspection_document)?,
             ))
         } else {
-            let mut fragments_done = HashSet::with_hasher(Default::default());
+            let mut fragments_done = HashMap::with_hasher(Default::default());
             let mut new_documents = Split {
-                introspection: DocumentBuilder::new(document, operation),
+                matching: DocumentBuilder::new(document, operation),
                 other: DocumentBuilder::new(document, operation),
             };
             let operation_selection_set = split_selection_set(
                 &mut fragments_done,
                 &mut new_documents,
                 &operation.selection_set,
+                &mut |_ty, field| field_is_schema_introspection(field),
             );
             Ok(Self::Both {
                 introspection_query: SchemaIntrospectionQuery::assume_only_intropsection_fields(
-                    new_documents.introspection.build(
+                    new_documents.matching.build(
                         schema,
                         document,
-                        operation_selection_set.introspection,
+                        operation_selection_set.matching,
                     ),
                 )?,
                 filtered_document: new_documents.other.build(
@@ -228,28 +232,6 @@ fn check_non_query<'doc>(
     Ok(())
 }
 
-/// As found in `ExecutableDocument::fragments`
-type FragmentMap = IndexMap<Name, Node<Fragment>>;
-
-/// The given operation and fragments are expected to form a valid document.
-/// This is checked iff debug assertions are enabled.
-fn make_single_operation_document(
-    schema: &Valid<Schema>,
-    document: &Valid<ExecutableDocument>,
-    new_operation: Node<Operation>,
-    fragments: FragmentMap,
-) -> Valid<ExecutableDocument> {
-    let mut new_document = ExecutableDocument {
-        sources: document.sources.clone(),
-        operations: Default::default(),
-        fragments,
-    };
-    new_document.operations.insert(new_operation);
-    new_document
-        .validate(schema)
-        .expect("filtering a valid document should result in a valid document")
-}
-
 pub(crate) fn get_fragment<'doc>(
     document: &'doc Valid<ExecutableDocument>,
     name: &Name,
@@ -367,226 +349,6 @@ fn collect_field_kinds<'doc>(
     Ok(top_level_field_kinds)
 }
 
-#[derive(Default)]
-struct Split<T> {
-    introspection: T,
-    other: T,
-}
-
-struct DocumentBuilder<'doc> {
-    original_document: &'doc Valid<ExecutableDocument>,
-    original_operation: &'doc Node<Operation>,
-    variables_used: HashSet<&'doc Name>,
-    new_fragments: FragmentMap,
-}
-
-fn split_selection_set<'doc>(
-    fragments_done: &mut HashSet<&'doc Name>,
-    new_documents: &mut Split<DocumentBuilder<'doc>>,
-    selection_set: &'doc SelectionSet,
-) -> Split<SelectionSet> {
-    let mut new_selection_sets = Split {
-        introspection: SelectionSet::new(selection_set.ty.clone()),
-        other: SelectionSet::new(selection_set.ty.clone()),
-    };
-    for selection in &selection_set.selections {
-        match selection {
-            Selection::Field(field) => {
-                // A field’s sub-selections are not top-level and therefore don’t need to be split.
-                // Clone as-is, and visit to collect fragment definitions and variables used.
-                if field_is_schema_introspection(field) {
-                    new_selection_sets.introspection.push(field.clone());
-                    new_documents.introspection.visit_field(field);
-                } else {
-                    new_selection_sets.other.push(field.clone());
-                    new_documents.other.visit_field(field);
-                }
-            }
-            Selection::InlineFragment(inline_fragment) => {
-                // Add an inline fragment if the split nested selection set is non-empty
-                let if_non_empty = |doc: &mut DocumentBuilder<'doc>,
-                                    parent: &mut SelectionSet,
-                                    nested: SelectionSet| {
-                    if !nested.selections.is_empty() {
-                        doc.visit_directives(&inline_fragment.directives);
-                        parent.push(inline_fragment.same_location(InlineFragment {
-                            type_condition: inline_fragment.type_condition.clone(),
-                            directives: inline_fragment.directives.clone(),
-                            selection_set: nested,
-                        }))
-                    }
-                };
-                let nested = split_selection_set(
-                    // document,
-                    fragments_done,
-                    new_documents,
-                    &inline_fragment.selection_set,
-                );
-                if_non_empty(
-                    &mut new_documents.introspection,
-                    &mut new_selection_sets.introspection,
-                    nested.introspection,
-                );
-                if_non_empty(
-                    &mut new_documents.other,
-                    &mut new_selection_sets.other,
-                    nested.other,
-                );
-            }
-            Selection::FragmentSpread(fragment_spread) => {
-                let name = &fragment_spread.fragment_name;
-                let new = fragments_done.insert(name);
-                if new {
-                    let document = &new_documents.introspection.original_document;
-                    // Checked in `collect_field_kinds`
-                    let fragment_def = &document.fragments[name];
-                    // Add a fragment definition if the split selection set is non-empty
-                    let if_non_empty = |doc: &mut DocumentBuilder<'doc>, nested: SelectionSet| {
-                        if !nested.selections.is_empty() {
-                            doc.visit_directives(&fragment_def.directives);
-                            doc.new_fragments.insert(
-                                fragment_def.name.clone(),
-                                fragment_def.same_location(Fragment {
-                                    name: fragment_def.name.clone(),
-                                    directives: fragment_def.directives.clone(),
-                                    selection_set: nested,
-                                }),
-                            );
-                        }
-                    };
-                    let nested = split_selection_set(
-                        fragments_done,
-                        new_documents,
-                        &fragment_def.selection_set,
-                    );
-                    if_non_empty(&mut new_documents.introspection, nested.introspection);
-                    if_non_empty(&mut new_documents.other, nested.other);
-                }
-                // Add a fragment spread if the above resulted in a fragment definition
-                let if_defined = |doc: &mut DocumentBuilder<'doc>, parent: &mut SelectionSet| {
-                    if doc.new_fragments.contains_key(name) {
-                        doc.visit_directives(&fragment_spread.directives);
-                        parent.push(fragment_spread.same_location(FragmentSpread {
-                            fragment_name: fragment_spread.fragment_name.clone(),
-                            directives: fragment_spread.directives.clone(),
-                        }))
-                    }
-                };
-                if_defined(
-                    &mut new_documents.introspection,
-                    &mut new_selection_sets.introspection,
-                );
-                if_defined(&mut new_documents.other, &mut new_selection_sets.other);
-            }
-        }
-    }
-    new_selection_sets
-}
-
-impl<'doc> DocumentBuilder<'doc> {
-    fn new(
-        original_document: &'doc Valid<ExecutableDocument>,
-        original_operation: &'doc Node<Operation>,
-    ) -> Self {
-        Self {
-            original_document,
-            original_operation,
-            variables_used: Default::default(),
-            new_fragments: Default::default(),
-        }
-    }
-
-    fn visit_selection_set(&mut self, selection_set: &'doc SelectionSet) {
-        for selection in &selection_set.selections {
-            match selection {
-                Selection::Field(field) => self.visit_field(field),
-                Selection::InlineFragment(inline_fragment) => {
-                    self.visit_directives(&inline_fragment.directives);
-                    self.visit_selection_set(&inline_fragment.selection_set);
-                }
-                Selection::FragmentSpread(fragment_spread) => {
-                    self.visit_directives(&fragment_spread.directives);
-                    let name = &fragment_spread.fragment_name;
-                    if let Entry::Vacant(entry) = self.new_fragments.entry(name.clone()) {
-                        // Checked in `collect_field_kinds`
-                        let fragment_def = &self.original_document.fragments[name];
-                        entry.insert(fragment_def.clone());
-                        self.visit_directives(&fragment_def.directives);
-                        self.visit_selection_set(&fragment_def.selection_set);
-                    };
-                }
-            }
-        }
-    }
-
-    fn visit_field(&mut self, field: &'doc Field) {
-        for arg in &field.arguments {
-            self.visit_value(&arg.value)
-        }
-        self.visit_directives(&field.directives);
-        self.visit_selection_set(&field.selection_set);
-    }
-
-    fn visit_directives(&mut self, directives: &'doc ast::DirectiveList) {
-        for directive in directives {
-            for arg in &directive.arguments {
-                self.visit_value(&arg.value)
-            }
-        }
-    }
-
-    fn visit_value(&mut self, value: &'doc ast::Value) {
-        match value {
-            schema::Value::Variable(name) => {
-                self.variables_used.insert(name);
-            }
-            schema::Value::List(list) => {
-                for value in list {
-                    self.visit_value(value)
-                }
-            }
-            schema::Value::Object(object) => {
-                for (_name, value) in object {
-                    self.visit_value(value)
-                }
-            }
-            schema::Value::Null
-            | schema::Value::Enum(_)
-            | schema::Value::String(_)
-            | schema::Value::Float(_)
-            | schema::Value::Int(_)
-            | schema::Value::Boolean(_) => {}
-        }
-    }
-
-    fn build(
-        mut self,
-        schema: &Valid<Schema>,
-        document: &Valid<ExecutableDocument>,
-        operation_selection_set: SelectionSet,
-    ) -> Valid<ExecutableDocument> {
-        for directive in &self.original_operation.directives {
-            for arg in &directive.arguments {
-                self.visit_value(&arg.value)
-            }
-        }
-        let new_operation = self.original_operation.same_location(Operation {
-            operation_type: self.original_operation.operation_type,
-            name: self.original_operation.name.clone(),
-            variables: self
-                .original_operation
-                .variables
-                .iter()
-                .filter(|var| self.variables_used.contains(&var.name))
-                .cloned()
-                .collect(),
-            directives: self.original_operation.directives.clone(),
-            selection_set: operation_selection_set,
-        });
-        make_single_operation_document(schema, document, new_operation, self.new_fragments)
-    }
-}
-
 impl From<SuspectedValidationBug> for SchemaIntrospectionError {
     fn from(value: SuspectedValidationBug) -> Self {
         Self::SuspectedValidationBug(value)
@@ -0,0 +1,148 @@
+use crate::execution::resolver::ResolvedValue;
+use crate::execution::resolver::Resolver;
+use crate::execution::resolver::ResolverError;
+use crate::execution::Executor;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::schema::ExtendedType;
+use crate::schema::Type;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Schema;
+
+/// Execute a GraphQL request against a static JSON value for the root operation type,
+/// instead of resolver functions.
+///
+/// This is the same as [`execute_request`][crate::execution::execute_request]
+/// with resolvers that read the data tree:
+///
+/// * Each field resolves to the value of the JSON object key with the same name as the field.
+///   Aliases are not used as keys, but are applied in the response.
+///   Field arguments are ignored.
+///   A missing key is a [field error].
+/// * A JSON object where an interface or union type is expected must have a `__typename` key
+///   with the name of its concrete object type as a string. Otherwise this is a field error.
+/// * JSON arrays are resolved as lists where the schema expects a list type.
+///   Other values are completed as leaf values, so for example a custom scalar
+///   may be represented by any JSON value.
+///
+/// Values are then completed as usual: fragments are merged, leaf values are coerced,
+/// and a null in a non-null position propagates to the nearest nullable parent.
+/// This is useful for contract tests and local development with static data.
+///
+/// [field error]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+pub fn execute_request_with_json_root(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    root_value: &JsonMap,
+) -> Response {
+    Executor::default_ref().execute_request_with_json_root(
+        schema,
+        document,
+        operation_name,
+        variables,
+        root_value,
+    )
+}
+
+impl Executor {
+    /// Like [`execute_request_with_json_root`], with the configuration of `self`
+    pub fn execute_request_with_json_root(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation_name: Option<&str>,
+        variables: &JsonMap,
+        root_value: &JsonMap,
+    ) -> Response {
+        // If there is no such operation, `execute_request` returns a request error
+        // without calling the root resolver
+        let type_name = document
+            .operations
+            .get(operation_name)
+            .map_or("", |operation| operation.object_type().as_str());
+        let root_value = JsonResolver {
+            schema,
+            type_name,
+            object: root_value,
+        };
+        self.execute_request(schema, document, operation_name, variables, &root_value)
+    }
+}
+
+/// A resolver for an object of the given type, reading fields from a JSON object
+struct JsonResolver<'a> {
+    schema: &'a Schema,
+    type_name: &'a str,
+    object: &'a JsonMap,
+}
+
+impl Resolver for JsonResolver<'_> {
+    fn type_name(&self) -> &str {
+        self.type_name
+    }
+
+    fn resolve_field<'a>(
+        &'a self,
+        field_name: &'a str,
+        _arguments: &'a JsonMap,
+    ) -> Result<ResolvedValue<'a>, ResolverError> {
+        let Some(value) = self.object.get(field_name) else {
+            return Err(ResolverError::new(format!(
+                "JSON data for type {} has no key {field_name}",
+                self.type_name
+            )));
+        };
+        let Ok(field_def) = self.schema.type_field(self.type_name, field_name) else {
+            return Err(ResolverError::new(format!(
+                "Undefined field {}.{field_name}",
+                self.type_name
+            )));
+        };
+        resolve_json_value(self.schema, &field_def.ty, value)
+    }
+}
+
+/// Wraps a JSON value to be completed for the given type
+fn resolve_json_value<'a>(
+    schema: &'a Schema,
+    ty: &'a Type,
+    value: &'a JsonValue,
+) -> Result<ResolvedValue<'a>, ResolverError> {
+    match value {
+        JsonValue::Array(items) if ty.is_list() => {
+            let items = items
+                .iter()
+                .map(|item| resolve_json_value(schema, ty.item_type(), item))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ResolvedValue::list(items))
+        }
+        JsonValue::Object(object) => {
+            let type_name = match schema.types.get(ty.inner_named_type()) {
+                Some(ExtendedType::Object(def)) => def.name.as_str(),
+                Some(ExtendedType::Interface(_) | ExtendedType::Union(_)) => {
+                    match object.get("__typename").and_then(JsonValue::as_str) {
+                        Some(type_name) => type_name,
+                        None => {
+                            return Err(ResolverError::new(format!(
+                                "JSON data for abstract type {} has no __typename string",
+                                ty.inner_named_type()
+                            )))
+                        }
+                    }
+                }
+                // Completed as a leaf value, for example for a custom scalar
+                _ => return Ok(ResolvedValue::leaf(value.clone())),
+            };
+            Ok(ResolvedValue::object(JsonResolver {
+                schema,
+                type_name,
+                object,
+            }))
+        }
+        _ => Ok(ResolvedValue::leaf(value.clone())),
+    }
+}
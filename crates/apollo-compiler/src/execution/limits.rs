@@ -0,0 +1,378 @@
+use crate::execution::engine::path_to_vec;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::PropagateNull;
+use crate::execution::Executor;
+use crate::execution::GraphQLError;
+use crate::execution::Response;
+use crate::execution::ResponseData;
+use crate::execution::ResponseDataPathElement;
+use crate::parser::SourceMap;
+use crate::parser::SourceSpan;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Resource limits for executing one request, configured with [`Executor::limits`].
+/// No limit is set by default.
+///
+/// The size of response data is limited by its number of JSON values
+/// with [`Self::max_response_nodes`], not by its size in bytes.
+///
+/// Exceeding a limit stops execution: remaining fields are not resolved,
+/// and the response has null data and a single error
+/// whose `extensions.code` is given by [`LimitExceeded::code`].
+///
+/// [`Executor::limits`]: crate::execution::Executor::limits
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionLimits {
+    pub(crate) max_resolved_fields: Option<usize>,
+    pub(crate) max_response_nodes: Option<usize>,
+    pub(crate) max_list_length: Option<usize>,
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// A flag shared between clones, to cancel execution from another thread or task.
+/// Configured with [`Executor::cancellation_token`].
+///
+/// Cancellation is checked before resolving each field,
+/// so it does not interrupt a resolver that is already running.
+///
+/// [`Executor::cancellation_token`]: crate::execution::Executor::cancellation_token
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+/// Which limit caused execution to stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// See [`ExecutionLimits::max_resolved_fields`]
+    ResolvedFields,
+    /// See [`ExecutionLimits::max_response_nodes`]
+    ResponseNodes,
+    /// See [`ExecutionLimits::max_list_length`]
+    ListLength,
+    /// See [`ExecutionLimits::timeout`]
+    Timeout,
+    /// See [`CancellationToken`]
+    Cancelled,
+}
+
+impl ExecutionLimits {
+    /// Returns limits with no limit set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of fields that are resolved,
+    /// counting each field once per object it is resolved for.
+    pub fn max_resolved_fields(mut self, value: usize) -> Self {
+        self.max_resolved_fields = Some(value);
+        self
+    }
+
+    /// Set the maximum number of JSON values in response data,
+    /// counting one for each field value and each list item.
+    pub fn max_response_nodes(mut self, value: usize) -> Self {
+        self.max_response_nodes = Some(value);
+        self
+    }
+
+    /// Set the maximum number of items in each list value
+    pub fn max_list_length(mut self, value: usize) -> Self {
+        self.max_list_length = Some(value);
+        self
+    }
+
+    /// Set the maximum duration of execution, checked before resolving each field.
+    /// This does not interrupt a resolver that is already running.
+    pub fn timeout(mut self, value: Duration) -> Self {
+        self.timeout = Some(value);
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        let Self {
+            max_resolved_fields,
+            max_response_nodes,
+            max_list_length,
+            timeout,
+        } = self;
+        max_resolved_fields.is_none()
+            && max_response_nodes.is_none()
+            && max_list_length.is_none()
+            && timeout.is_none()
+    }
+}
+
+impl CancellationToken {
+    /// Returns a new token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of any execution using this token or one of its clones
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns whether [`Self::cancel`] was called on this token or one of its clones
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl LimitExceeded {
+    /// A stable code for the `extensions.code` entry of the response error
+    pub fn code(self) -> &'static str {
+        match self {
+            LimitExceeded::ResolvedFields => "MAX_RESOLVED_FIELDS_EXCEEDED",
+            LimitExceeded::ResponseNodes => "MAX_RESPONSE_NODES_EXCEEDED",
+            LimitExceeded::ListLength => "MAX_LIST_LENGTH_EXCEEDED",
+            LimitExceeded::Timeout => "EXECUTION_TIMEOUT",
+            LimitExceeded::Cancelled => "EXECUTION_CANCELLED",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            LimitExceeded::ResolvedFields => "Maximum number of resolved fields exceeded",
+            LimitExceeded::ResponseNodes => "Maximum response size exceeded",
+            LimitExceeded::ListLength => "Maximum list length exceeded",
+            LimitExceeded::Timeout => "Execution timed out",
+            LimitExceeded::Cancelled => "Execution was cancelled",
+        }
+    }
+}
+
+impl Executor {
+    /// Called before completing each item of a list value.
+    /// Returns `Err` if execution should stop.
+    pub(crate) fn before_list_item(
+        &self,
+        index: usize,
+        path: LinkedPath<'_>,
+        location: Option<SourceSpan>,
+    ) -> Result<(), PropagateNull> {
+        match &self.limit_state {
+            Some(state) => state
+                .before_list_item(index, path, location)
+                .map_err(|_| PropagateNull),
+            None => Ok(()),
+        }
+    }
+
+    /// Called before resolving a field for `count` objects at once with `resolve_field_batch`.
+    /// Returns `Err` if execution should stop.
+    pub(crate) fn before_field_batch(
+        &self,
+        count: usize,
+        path: LinkedPath<'_>,
+        location: Option<SourceSpan>,
+    ) -> Result<(), PropagateNull> {
+        match &self.limit_state {
+            Some(state) => state
+                .before_field_batch(count, path, location)
+                .map_err(|_| PropagateNull),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a copy of `self` with a new [`LimitState`] for one response,
+    /// or `None` if there is nothing to check or if `self` already has such state
+    pub(crate) fn with_limit_state(&self) -> Option<Executor> {
+        if self.limit_state.is_some() {
+            return None;
+        }
+        let state = LimitState::new(&self.limits, self.cancellation_token.as_ref())?;
+        let mut executor = self.clone();
+        executor.limit_state = Some(Arc::new(state));
+        Some(executor)
+    }
+
+    /// Stops reading a list value one item past [`ExecutionLimits::max_list_length`],
+    /// so that an endless list is reported by [`Self::before_list_item`]
+    /// instead of being read forever
+    pub(crate) fn take_list_items<I: Iterator>(&self, iter: I) -> std::iter::Take<I> {
+        let max = self.max_list_length();
+        iter.take(max.map_or(usize::MAX, |max| max.saturating_add(1)))
+    }
+
+    /// [`ExecutionLimits::max_list_length`] if limits apply to the current request
+    pub(crate) fn max_list_length(&self) -> Option<usize> {
+        self.limit_state
+            .as_ref()
+            .and_then(|state| state.limits.max_list_length)
+    }
+}
+
+/// Counters for [`ExecutionLimits`] during one request
+#[derive(Debug)]
+pub(crate) struct LimitState {
+    limits: ExecutionLimits,
+    cancellation_token: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    resolved_fields: AtomicUsize,
+    response_nodes: AtomicUsize,
+    /// The first limit exceeded, with the path and location of the field where it happened
+    exceeded: OnceLock<(
+        LimitExceeded,
+        Vec<ResponseDataPathElement>,
+        Option<SourceSpan>,
+    )>,
+}
+
+impl LimitState {
+    /// Returns `None` if there is nothing to check
+    pub(crate) fn new(
+        limits: &ExecutionLimits,
+        cancellation_token: Option<&CancellationToken>,
+    ) -> Option<Self> {
+        if limits.is_empty() && cancellation_token.is_none() {
+            return None;
+        }
+        Some(Self {
+            limits: limits.clone(),
+            cancellation_token: cancellation_token.cloned(),
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+            resolved_fields: AtomicUsize::new(0),
+            response_nodes: AtomicUsize::new(0),
+            exceeded: OnceLock::new(),
+        })
+    }
+
+    /// Called before resolving a field.
+    /// Returns `Err` if execution should stop, including if a limit was exceeded elsewhere.
+    pub(crate) fn before_field(
+        &self,
+        path: &[ResponseDataPathElement],
+        location: Option<SourceSpan>,
+    ) -> Result<(), LimitExceeded> {
+        if let Some((limit, ..)) = self.exceeded.get() {
+            return Err(*limit);
+        }
+        let check = || {
+            self.check_cancellation_and_timeout()?;
+            let resolved_fields = self.resolved_fields.fetch_add(1, Ordering::Relaxed) + 1;
+            if self
+                .limits
+                .max_resolved_fields
+                .is_some_and(|max| resolved_fields > max)
+            {
+                return Err(LimitExceeded::ResolvedFields);
+            }
+            self.count_response_node()
+        };
+        check().map_err(|limit| self.exceeded(limit, path, location))
+    }
+
+    /// Called before resolving a field for `count` objects at once.
+    /// The field is still counted for each object when it is completed,
+    /// but this fails early if those resolved fields would exceed the limit.
+    pub(crate) fn before_field_batch(
+        &self,
+        count: usize,
+        path: LinkedPath<'_>,
+        location: Option<SourceSpan>,
+    ) -> Result<(), LimitExceeded> {
+        if let Some((limit, ..)) = self.exceeded.get() {
+            return Err(*limit);
+        }
+        let check = || {
+            self.check_cancellation_and_timeout()?;
+            let resolved_fields = self
+                .resolved_fields
+                .load(Ordering::Relaxed)
+                .saturating_add(count);
+            if self
+                .limits
+                .max_resolved_fields
+                .is_some_and(|max| resolved_fields > max)
+            {
+                return Err(LimitExceeded::ResolvedFields);
+            }
+            Ok(())
+        };
+        check().map_err(|limit| self.exceeded(limit, &path_to_vec(path), location))
+    }
+
+    /// Called before completing each item of a list value
+    pub(crate) fn before_list_item(
+        &self,
+        index: usize,
+        path: LinkedPath<'_>,
+        location: Option<SourceSpan>,
+    ) -> Result<(), LimitExceeded> {
+        if let Some((limit, ..)) = self.exceeded.get() {
+            return Err(*limit);
+        }
+        let check = || {
+            if self.limits.max_list_length.is_some_and(|max| index >= max) {
+                return Err(LimitExceeded::ListLength);
+            }
+            self.count_response_node()
+        };
+        check().map_err(|limit| self.exceeded(limit, &path_to_vec(path), location))
+    }
+
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.exceeded.get().is_some()
+    }
+
+    fn check_cancellation_and_timeout(&self) -> Result<(), LimitExceeded> {
+        if self
+            .cancellation_token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(LimitExceeded::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(LimitExceeded::Timeout);
+        }
+        Ok(())
+    }
+
+    fn count_response_node(&self) -> Result<(), LimitExceeded> {
+        let response_nodes = self.response_nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        if self
+            .limits
+            .max_response_nodes
+            .is_some_and(|max| response_nodes > max)
+        {
+            return Err(LimitExceeded::ResponseNodes);
+        }
+        Ok(())
+    }
+
+    fn exceeded(
+        &self,
+        limit: LimitExceeded,
+        path: &[ResponseDataPathElement],
+        location: Option<SourceSpan>,
+    ) -> LimitExceeded {
+        let _ = self.exceeded.set((limit, path.to_vec(), location));
+        limit
+    }
+
+    /// If a limit was exceeded, replace the response with one with null data and a single error
+    pub(crate) fn finish(&self, response: Response, sources: &SourceMap) -> Response {
+        let Some((limit, path, location)) = self.exceeded.get() else {
+            return response;
+        };
+        let mut error = GraphQLError::new(limit.message(), *location, sources);
+        error.path = path.clone();
+        error.extensions.insert("code", limit.code().into());
+        Response {
+            errors: vec![error],
+            data: ResponseData::Null,
+            extensions: response.extensions,
+        }
+    }
+}
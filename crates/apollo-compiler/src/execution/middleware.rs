@@ -0,0 +1,260 @@
+use crate::executable::DirectiveList;
+use crate::executable::Field;
+use crate::execution::engine::path_to_vec;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::PropagateNull;
+use crate::execution::resolver::ResolverError;
+use crate::execution::Executor;
+use crate::execution::FieldTrace;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::ResponseDataPathElement;
+use crate::parser::SourceMap;
+use crate::schema::Type;
+use crate::Name;
+use crate::Node;
+
+/// Hooks called around the resolution of each field during execution,
+/// registered with [`Executor::field_middleware`].
+///
+/// Middleware can implement cross-cutting concerns such as authorization checks,
+/// metrics, or error shaping, as well as custom executable directives
+/// that are visible through [`FieldContext::directives`].
+/// All methods have default implementations that do nothing.
+///
+/// With multiple middleware, `before_field` hooks are called in registration order
+/// and the first one that returns `Some` short-circuits the others.
+/// `after_field` and `on_field_error` hooks are then called in reverse registration order,
+/// so that the first registered middleware wraps all others.
+///
+/// Hooks are not called for `__typename` or for [schema introspection] fields.
+///
+/// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+///
+/// ## Example
+///
+/// An `@uppercase` directive for fields of type `String`:
+///
+/// ```
+/// use apollo_compiler::execution::FieldContext;
+/// use apollo_compiler::execution::FieldMiddleware;
+/// use apollo_compiler::execution::JsonValue;
+/// use apollo_compiler::execution::ResolverError;
+///
+/// struct Uppercase;
+///
+/// impl FieldMiddleware for Uppercase {
+///     fn after_field(
+///         &self,
+///         context: &FieldContext<'_>,
+///         value: JsonValue,
+///     ) -> Result<JsonValue, ResolverError> {
+///         match value.as_str() {
+///             Some(str) if context.directives().has("uppercase") => {
+///                 Ok(str.to_uppercase().into())
+///             }
+///             _ => Ok(value),
+///         }
+///     }
+/// }
+/// ```
+pub trait FieldMiddleware: Send + Sync {
+    /// Called after arguments are coerced and before the field is resolved.
+    ///
+    /// * `None` continues execution normally.
+    /// * `Some(Ok(value))` skips the resolver. `value` is used as the completed value of the field:
+    ///   it is not coerced, and for object types it should already have the shape
+    ///   of the field’s selection set.
+    /// * `Some(Err(error))` skips the resolver and raises a [field error] with this message.
+    ///
+    /// [field error]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+    fn before_field(&self, context: &FieldContext<'_>) -> Option<Result<JsonValue, ResolverError>> {
+        let _ = context;
+        None
+    }
+
+    /// Called with the completed value of the field, which can be replaced.
+    /// Returning `Err` raises a [field error] with this message.
+    ///
+    /// This is not called if the field has already raised a field error,
+    /// but it is called with a value returned by [`before_field`][Self::before_field].
+    ///
+    /// [field error]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+    fn after_field(
+        &self,
+        context: &FieldContext<'_>,
+        value: JsonValue,
+    ) -> Result<JsonValue, ResolverError> {
+        let _ = context;
+        Ok(value)
+    }
+
+    /// Called for each [field error] raised for this field or for its list items,
+    /// but not for errors of nested fields which have their own context.
+    /// The error can be modified, for example to add [`extensions`][GraphQLError::extensions].
+    ///
+    /// [field error]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+    fn on_field_error(&self, context: &FieldContext<'_>, error: &mut GraphQLError) {
+        let _ = (context, error);
+    }
+}
+
+/// A field being executed, as seen by [`FieldMiddleware`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct FieldContext<'a> {
+    /// The object type that the field belongs to
+    pub parent_type: &'a Name,
+
+    /// The field selection in the executable document.
+    ///
+    /// When multiple selections with the same response key are merged,
+    /// this is the first of them.
+    pub field: &'a Node<Field>,
+
+    /// The coerced values of arguments, including default values
+    pub arguments: &'a JsonMap,
+
+    /// The path of the field in response data
+    pub path: Vec<ResponseDataPathElement>,
+}
+
+impl FieldContext<'_> {
+    /// Returns the directives applied to the field selection
+    pub fn directives(&self) -> &DirectiveList {
+        &self.field.directives
+    }
+}
+
+/// Calls [`FieldMiddleware`] hooks of an [`Executor`] for one field,
+/// and records its trace if tracing is enabled
+pub(crate) struct FieldMiddlewareCall<'a> {
+    executor: &'a Executor,
+    context: FieldContext<'a>,
+    /// Errors at this index and later are raised by this field or nested fields
+    errors_start: usize,
+    start_offset_nano: u64,
+}
+
+impl<'a> FieldMiddlewareCall<'a> {
+    /// Returns `None` if the executor has no middleware and tracing is disabled
+    pub(crate) fn new(
+        executor: &'a Executor,
+        parent_type: &'a Name,
+        field: &'a Node<Field>,
+        arguments: &'a JsonMap,
+        path: LinkedPath<'_>,
+        errors: &[GraphQLError],
+    ) -> Option<Self> {
+        if executor.field_middleware.is_empty()
+            && executor.trace_recorder.is_none()
+            && executor.limit_state.is_none()
+        {
+            return None;
+        }
+        Some(Self {
+            executor,
+            context: FieldContext {
+                parent_type,
+                field,
+                arguments,
+                path: path_to_vec(path),
+            },
+            errors_start: errors.len(),
+            start_offset_nano: executor
+                .trace_recorder
+                .as_ref()
+                .map_or(0, |recorder| recorder.offset()),
+        })
+    }
+
+    /// Returns `Some` if a middleware short-circuits the resolver,
+    /// or if execution should stop because of [`ExecutionLimits`][crate::execution::ExecutionLimits]
+    pub(crate) fn before(
+        &self,
+        errors: &mut Vec<GraphQLError>,
+        sources: &SourceMap,
+    ) -> Option<Result<JsonValue, PropagateNull>> {
+        if let Some(state) = &self.executor.limit_state {
+            let location = self.context.field.name.location();
+            if state.before_field(&self.context.path, location).is_err() {
+                // Reported as a single error when execution finishes
+                return Some(Err(PropagateNull));
+            }
+        }
+        let result = self
+            .executor
+            .field_middleware
+            .iter()
+            .find_map(|middleware| middleware.before_field(&self.context))?;
+        Some(result.map_err(|error| self.field_error(errors, sources, error.message)))
+    }
+
+    pub(crate) fn after(
+        &self,
+        mut result: Result<JsonValue, PropagateNull>,
+        ty: &Type,
+        errors: &mut Vec<GraphQLError>,
+        sources: &SourceMap,
+    ) -> Result<JsonValue, PropagateNull> {
+        for middleware in self.executor.field_middleware.iter().rev() {
+            let Ok(value) = result else { break };
+            result = middleware
+                .after_field(&self.context, value)
+                .map_err(|error| self.field_error(errors, sources, error.message));
+        }
+        if result.as_ref().is_ok_and(JsonValue::is_null) && ty.is_non_null() {
+            result = Err(self.field_error(
+                errors,
+                sources,
+                format!("Non-null type {ty} resolved to null"),
+            ));
+        }
+        let mut own_errors = Vec::new();
+        for error in &mut errors[self.errors_start..] {
+            if self.is_own_error(error) {
+                for middleware in self.executor.field_middleware.iter().rev() {
+                    middleware.on_field_error(&self.context, error)
+                }
+                if self.executor.trace_recorder.is_some() {
+                    own_errors.push(error.clone())
+                }
+            }
+        }
+        if let Some(recorder) = &self.executor.trace_recorder {
+            recorder.record(FieldTrace {
+                path: self.context.path.clone(),
+                parent_type: self.context.parent_type.clone(),
+                field_name: self.context.field.name.clone(),
+                return_type: self.context.field.ty().to_string(),
+                start_offset_nano: self.start_offset_nano,
+                end_offset_nano: recorder.offset(),
+                errors: own_errors,
+                children: Vec::new(),
+            })
+        }
+        result
+    }
+
+    /// Whether an error was raised for this field or its list items, as opposed to nested fields
+    fn is_own_error(&self, error: &GraphQLError) -> bool {
+        let own_path = &self.context.path;
+        error.path.starts_with(own_path)
+            && error.path[own_path.len()..]
+                .iter()
+                .all(|element| matches!(element, ResponseDataPathElement::ListIndex(_)))
+    }
+
+    fn field_error(
+        &self,
+        errors: &mut Vec<GraphQLError>,
+        sources: &SourceMap,
+        message: String,
+    ) -> PropagateNull {
+        let mut error = GraphQLError::new(message, self.context.field.name.location(), sources);
+        error.path = self.context.path.clone();
+        errors.push(error);
+        PropagateNull
+    }
+}
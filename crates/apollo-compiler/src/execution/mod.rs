@@ -6,25 +6,90 @@
 
 #[macro_use]
 mod resolver;
-mod engine;
+mod async_engine;
+mod batch;
+pub(crate) mod engine;
+mod execute;
+mod incremental;
 mod input_coercion;
 mod introspection_execute;
 mod introspection_max_depth;
+mod introspection_query;
 mod introspection_split;
+mod json_root;
+mod limits;
+mod middleware;
+pub(crate) mod operation_split;
+mod persisted_queries;
+mod request;
 mod response;
+mod response_merge;
+mod response_validation;
 mod result_coercion;
+mod scalar_coercion;
+mod subscription;
+mod tracing;
 
+pub use self::execute::execute_request;
+pub use self::execute::execute_request_async;
+pub use self::execute::Executor;
+pub use self::incremental::execute_incremental_request;
 pub use self::input_coercion::coerce_variable_values;
 pub use self::input_coercion::InputCoercionError;
 pub use self::introspection_execute::execute_introspection_only_query;
 pub use self::introspection_execute::SchemaIntrospectionQuery;
 pub use self::introspection_max_depth::check_introspection_max_depth;
+pub use self::introspection_query::IntrospectionQueryOptions;
 pub use self::introspection_split::SchemaIntrospectionError;
 pub use self::introspection_split::SchemaIntrospectionSplit;
+pub use self::json_root::execute_request_with_json_root;
+pub use self::limits::CancellationToken;
+pub use self::limits::ExecutionLimits;
+pub use self::limits::LimitExceeded;
+pub use self::middleware::FieldContext;
+pub use self::middleware::FieldMiddleware;
+pub use self::operation_split::OperationSplit;
+pub use self::operation_split::OperationSplitError;
+pub use self::persisted_queries::persisted_query_id;
+pub use self::persisted_queries::ManifestError;
+pub use self::persisted_queries::PersistedQuery;
+pub use self::persisted_queries::PersistedQueryError;
+pub use self::persisted_queries::PersistedQueryManifest;
+pub use self::request::BatchRequest;
+pub use self::request::Request;
+pub use self::request::RequestError;
+pub use self::resolver::AsyncObjectValue;
+pub use self::resolver::AsyncResolvedValue;
+pub use self::resolver::AsyncResolver;
+pub use self::resolver::ObjectValue;
+pub use self::resolver::ResolvedValue;
+pub use self::resolver::Resolver;
+pub use self::resolver::ResolverError;
+pub use self::resolver::SourceEventStream;
+pub use self::resolver::SubscriptionResolver;
+pub use self::response::CompletedResult;
 pub use self::response::GraphQLError;
+pub use self::response::IncrementalResult;
+pub use self::response::InitialIncrementalResponse;
+pub use self::response::PendingResult;
 pub use self::response::Response;
 pub use self::response::ResponseData;
 pub use self::response::ResponseDataPathElement;
+pub use self::response::SubsequentIncrementalResponse;
+pub use self::response_merge::ResponseMergeError;
+pub use self::response_merge::ResponseMergeErrorKind;
+pub use self::response_validation::validate_response;
+pub use self::response_validation::validate_response_data;
+pub use self::response_validation::ResponseShapeError;
+pub use self::response_validation::ResponseShapeErrorKind;
+pub use self::scalar_coercion::CustomScalar;
+pub use self::scalar_coercion::ScalarCoercion;
+pub use self::subscription::create_source_event_stream;
+pub use self::subscription::map_source_to_response_event;
+pub use self::subscription::subscribe;
+pub use self::tracing::ExecutionTrace;
+pub use self::tracing::FieldTrace;
+pub use self::tracing::TRACE_EXTENSION_KEY;
 /// Re-export of the version of the `serde_json_bytes` crate used for [`JsonValue`] and [`JsonMap`]
 pub use serde_json_bytes;
 
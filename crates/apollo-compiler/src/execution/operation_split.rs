@@ -0,0 +1,431 @@
+use crate::ast;
+use crate::collections::HashMap;
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::coordinate::TypeAttributeCoordinate;
+use crate::executable::Field;
+use crate::executable::Fragment;
+use crate::executable::FragmentSpread;
+use crate::executable::InlineFragment;
+use crate::executable::Operation;
+use crate::executable::Selection;
+use crate::executable::SelectionSet;
+use crate::execution::GraphQLError;
+use crate::execution::Response;
+use crate::parser::SourceMap;
+use crate::parser::SourceSpan;
+use crate::schema;
+use crate::schema::Name;
+use crate::schema::NamedType;
+use crate::validation::DiagnosticList;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Node;
+use crate::Schema;
+use indexmap::map::Entry;
+
+/// Result of [`split`][Self::split]ting an operation by a predicate over its top-level fields.
+///
+/// This generalizes [`SchemaIntrospectionSplit`][crate::execution::SchemaIntrospectionSplit]
+/// to other criteria, such as fields resolved locally and fields fetched from a remote service,
+/// or cacheable and non-cacheable fields.
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)] // same as `SchemaIntrospectionSplit`
+pub enum OperationSplit {
+    /// *No* top-level field of the selected operation matches the predicate.
+    /// It should be executed unchanged as the non-matching part.
+    None,
+
+    /// *Every* top-level field of the selected operation matches the predicate.
+    /// It should be executed unchanged as the matching part.
+    Only,
+
+    /// The selected operation has *both* matching and non-matching top-level fields.
+    /// Each part should be executed, and their responses merged with [`Response::merge`].
+    ///
+    /// Each document contains exactly one operation with the fields of the other part removed,
+    /// and the fragment definitions that are still needed.
+    /// The operation definition name is preserved,
+    /// so either `None` or the original `Option<&str>` name request can be passed
+    /// to [`OperationMap::get`][crate::executable::OperationMap::get]
+    /// to obtain the one operation.
+    Both {
+        /// The part of the operation with top-level fields that match the predicate
+        matching: Valid<ExecutableDocument>,
+
+        /// The rest of the operation
+        other: Valid<ExecutableDocument>,
+    },
+}
+
+/// An operation that [`OperationSplit::split`] cannot split into parts
+/// with disjoint response keys.
+#[derive(Debug, Clone)]
+pub struct OperationSplitError {
+    pub message: String,
+    pub location: Option<SourceSpan>,
+}
+
+impl OperationSplit {
+    /// Splits an operation into top-level fields that match `predicate` and other fields.
+    ///
+    /// `predicate` is called with the schema coordinate of each top-level field,
+    /// such as `Query.users` or `Query.__typename`.
+    /// Fields are top-level if they are in the root selection set of the operation,
+    /// directly or through (inline or named) fragments.
+    /// Nested fields are kept together with their top-level field,
+    /// so that the two parts have disjoint response keys.
+    ///
+    /// Executing a mutation in two parts changes the order
+    /// in which its top-level fields are executed.
+    ///
+    /// Returns an error if a named fragment with both matching and non-matching
+    /// top-level fields is also used nested in another field,
+    /// which is only possible in a schema where the root operation type is also
+    /// the type of some field.
+    pub fn split(
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Node<Operation>,
+        mut predicate: impl FnMut(&TypeAttributeCoordinate) -> bool,
+    ) -> Result<Self, OperationSplitError> {
+        let mut fragments_done = HashMap::with_hasher(Default::default());
+        let mut new_documents = Split {
+            matching: DocumentBuilder::new(document, operation),
+            other: DocumentBuilder::new(document, operation),
+        };
+        let operation_selection_set = split_selection_set(
+            &mut fragments_done,
+            &mut new_documents,
+            &operation.selection_set,
+            &mut |ty, field| {
+                predicate(&TypeAttributeCoordinate {
+                    ty: ty.clone(),
+                    attribute: field.name.clone(),
+                })
+            },
+        );
+        if operation_selection_set.matching.selections.is_empty() {
+            return Ok(Self::None);
+        }
+        if operation_selection_set.other.selections.is_empty() {
+            return Ok(Self::Only);
+        }
+        for new_document in [&new_documents.matching, &new_documents.other] {
+            for &name in &new_document.nested_fragments {
+                if fragments_done
+                    .get(name)
+                    .is_some_and(|done: &Split<bool>| done.matching && done.other)
+                {
+                    return Err(OperationSplitError {
+                        message: format!(
+                            "Fragment {name} has top-level fields in both parts of the split \
+                             and cannot also be used nested in other fields"
+                        ),
+                        location: document.fragments[name].location(),
+                    });
+                }
+            }
+        }
+        Ok(Self::Both {
+            matching: new_documents.matching.build(
+                schema,
+                document,
+                operation_selection_set.matching,
+            ),
+            other: new_documents
+                .other
+                .build(schema, document, operation_selection_set.other),
+        })
+    }
+}
+
+impl OperationSplitError {
+    /// Convert into a JSON-serializable error as represented in a GraphQL response
+    pub fn into_graphql_error(self, sources: &SourceMap) -> GraphQLError {
+        GraphQLError::new(self.message, self.location, sources)
+    }
+
+    /// Convert into a response with this error as a [request error]
+    /// that prevented execution from starting.
+    ///
+    /// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+    pub fn into_response(self, sources: &SourceMap) -> Response {
+        Response::from_request_error(self.into_graphql_error(sources))
+    }
+}
+
+/// As found in `ExecutableDocument::fragments`
+type FragmentMap = IndexMap<Name, Node<Fragment>>;
+
+/// The given operation and fragments are expected to form a valid document.
+/// This is checked iff debug assertions are enabled.
+pub(crate) fn make_single_operation_document(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    new_operation: Node<Operation>,
+    fragments: FragmentMap,
+) -> Valid<ExecutableDocument> {
+    try_make_single_operation_document(schema, document, new_operation, fragments)
+        .expect("filtering a valid document should result in a valid document")
+}
+
+/// Like [`make_single_operation_document`], but returns validation errors instead of panicking
+pub(crate) fn try_make_single_operation_document(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    new_operation: Node<Operation>,
+    fragments: FragmentMap,
+) -> Result<Valid<ExecutableDocument>, DiagnosticList> {
+    let mut new_document = ExecutableDocument {
+        sources: document.sources.clone(),
+        operations: Default::default(),
+        fragments,
+    };
+    new_document.operations.insert(new_operation);
+    new_document
+        .validate(schema)
+        .map_err(|with_errors| with_errors.errors)
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Split<T> {
+    pub(crate) matching: T,
+    pub(crate) other: T,
+}
+
+pub(crate) struct DocumentBuilder<'doc> {
+    original_document: &'doc Valid<ExecutableDocument>,
+    original_operation: &'doc Node<Operation>,
+    variables_used: HashSet<&'doc Name>,
+    new_fragments: FragmentMap,
+    /// Fragments spread in sub-selections of top-level fields, which need their full definition
+    nested_fragments: HashSet<&'doc Name>,
+}
+
+/// Splits the top-level fields of `selection_set`, recurring into fragments.
+///
+/// `fragments_done` records which parts each fragment definition was split into.
+pub(crate) fn split_selection_set<'doc>(
+    fragments_done: &mut HashMap<&'doc Name, Split<bool>>,
+    new_documents: &mut Split<DocumentBuilder<'doc>>,
+    selection_set: &'doc SelectionSet,
+    is_matching: &mut dyn FnMut(&NamedType, &Field) -> bool,
+) -> Split<SelectionSet> {
+    let mut new_selection_sets = Split {
+        matching: SelectionSet::new(selection_set.ty.clone()),
+        other: SelectionSet::new(selection_set.ty.clone()),
+    };
+    for selection in &selection_set.selections {
+        match selection {
+            Selection::Field(field) => {
+                // A field’s sub-selections are not top-level and therefore don’t need to be split.
+                // Clone as-is, and visit to collect fragment definitions and variables used.
+                if is_matching(&selection_set.ty, field) {
+                    new_selection_sets.matching.push(field.clone());
+                    new_documents.matching.visit_field(field);
+                } else {
+                    new_selection_sets.other.push(field.clone());
+                    new_documents.other.visit_field(field);
+                }
+            }
+            Selection::InlineFragment(inline_fragment) => {
+                // Add an inline fragment if the split nested selection set is non-empty
+                let if_non_empty = |doc: &mut DocumentBuilder<'doc>,
+                                    parent: &mut SelectionSet,
+                                    nested: SelectionSet| {
+                    if !nested.selections.is_empty() {
+                        doc.visit_directives(&inline_fragment.directives);
+                        parent.push(inline_fragment.same_location(InlineFragment {
+                            type_condition: inline_fragment.type_condition.clone(),
+                            directives: inline_fragment.directives.clone(),
+                            selection_set: nested,
+                        }))
+                    }
+                };
+                let nested = split_selection_set(
+                    fragments_done,
+                    new_documents,
+                    &inline_fragment.selection_set,
+                    is_matching,
+                );
+                if_non_empty(
+                    &mut new_documents.matching,
+                    &mut new_selection_sets.matching,
+                    nested.matching,
+                );
+                if_non_empty(
+                    &mut new_documents.other,
+                    &mut new_selection_sets.other,
+                    nested.other,
+                );
+            }
+            Selection::FragmentSpread(fragment_spread) => {
+                let name = &fragment_spread.fragment_name;
+                let non_empty = if let Some(done) = fragments_done.get(name) {
+                    *done
+                } else {
+                    let document = &new_documents.matching.original_document;
+                    // Fragments of a valid document are defined
+                    let fragment_def = &document.fragments[name];
+                    // Add a fragment definition if the split selection set is non-empty
+                    let if_non_empty = |doc: &mut DocumentBuilder<'doc>, nested: SelectionSet| {
+                        if nested.selections.is_empty() {
+                            return false;
+                        }
+                        doc.visit_directives(&fragment_def.directives);
+                        doc.new_fragments.insert(
+                            fragment_def.name.clone(),
+                            fragment_def.same_location(Fragment {
+                                name: fragment_def.name.clone(),
+                                directives: fragment_def.directives.clone(),
+                                selection_set: nested,
+                            }),
+                        );
+                        true
+                    };
+                    let nested = split_selection_set(
+                        fragments_done,
+                        new_documents,
+                        &fragment_def.selection_set,
+                        is_matching,
+                    );
+                    let done = Split {
+                        matching: if_non_empty(&mut new_documents.matching, nested.matching),
+                        other: if_non_empty(&mut new_documents.other, nested.other),
+                    };
+                    fragments_done.insert(name, done);
+                    done
+                };
+                // Add a fragment spread if the above resulted in a fragment definition
+                let if_defined =
+                    |doc: &mut DocumentBuilder<'doc>, parent: &mut SelectionSet, defined| {
+                        if defined {
+                            doc.visit_directives(&fragment_spread.directives);
+                            parent.push(fragment_spread.same_location(FragmentSpread {
+                                fragment_name: fragment_spread.fragment_name.clone(),
+                                directives: fragment_spread.directives.clone(),
+                            }))
+                        }
+                    };
+                if_defined(
+                    &mut new_documents.matching,
+                    &mut new_selection_sets.matching,
+                    non_empty.matching,
+                );
+                if_defined(
+                    &mut new_documents.other,
+                    &mut new_selection_sets.other,
+                    non_empty.other,
+                );
+            }
+        }
+    }
+    new_selection_sets
+}
+
+impl<'doc> DocumentBuilder<'doc> {
+    pub(crate) fn new(
+        original_document: &'doc Valid<ExecutableDocument>,
+        original_operation: &'doc Node<Operation>,
+    ) -> Self {
+        Self {
+            original_document,
+            original_operation,
+            variables_used: Default::default(),
+            new_fragments: Default::default(),
+            nested_fragments: Default::default(),
+        }
+    }
+
+    fn visit_selection_set(&mut self, selection_set: &'doc SelectionSet) {
+        for selection in &selection_set.selections {
+            match selection {
+                Selection::Field(field) => self.visit_field(field),
+                Selection::InlineFragment(inline_fragment) => {
+                    self.visit_directives(&inline_fragment.directives);
+                    self.visit_selection_set(&inline_fragment.selection_set);
+                }
+                Selection::FragmentSpread(fragment_spread) => {
+                    self.visit_directives(&fragment_spread.directives);
+                    let name = &fragment_spread.fragment_name;
+                    self.nested_fragments.insert(name);
+                    if let Entry::Vacant(entry) = self.new_fragments.entry(name.clone()) {
+                        // Fragments of a valid document are defined
+                        let fragment_def = &self.original_document.fragments[name];
+                        entry.insert(fragment_def.clone());
+                        self.visit_directives(&fragment_def.directives);
+                        self.visit_selection_set(&fragment_def.selection_set);
+                    };
+                }
+            }
+        }
+    }
+
+    fn visit_field(&mut self, field: &'doc Field) {
+        for arg in &field.arguments {
+            self.visit_value(&arg.value)
+        }
+        self.visit_directives(&field.directives);
+        self.visit_selection_set(&field.selection_set);
+    }
+
+    fn visit_directives(&mut self, directives: &'doc ast::DirectiveList) {
+        for directive in directives {
+            for arg in &directive.arguments {
+                self.visit_value(&arg.value)
+            }
+        }
+    }
+
+    fn visit_value(&mut self, value: &'doc ast::Value) {
+        match value {
+            schema::Value::Variable(name) => {
+                self.variables_used.insert(name);
+            }
+            schema::Value::List(list) => {
+                for value in list {
+                    self.visit_value(value)
+                }
+            }
+            schema::Value::Object(object) => {
+                for (_name, value) in object {
+                    self.visit_value(value)
+                }
+            }
+            schema::Value::Null
+            | schema::Value::Enum(_)
+            | schema::Value::String(_)
+            | schema::Value::Float(_)
+            | schema::Value::Int(_)
+            | schema::Value::Boolean(_) => {}
+        }
+    }
+
+    pub(crate) fn build(
+        mut self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation_selection_set: SelectionSet,
+    ) -> Valid<ExecutableDocument> {
+        for directive in &self.original_operation.directives {
+            for arg in &directive.arguments {
+                self.visit_value(&arg.value)
+            }
+        }
+        let new_operation = self.original_operation.same_location(Operation {
+            operation_type: self.original_operation.operation_type,
+            name: self.original_operation.name.clone(),
+            variables: self
+                .original_operation
+                .variables
+                .iter()
+                .filter(|var| self.variables_used.contains(&var.name))
+                .cloned()
+                .collect(),
+            directives: self.original_operation.directives.clone(),
+            selection_set: operation_selection_set,
+        });
+        make_single_operation_document(schema, document, new_operation, self.new_fragments)
+    }
+}
@@ -0,0 +1,354 @@
+use crate::collections::HashMap;
+use crate::collections::IndexMap;
+use crate::content_hash::sha256_hex;
+use crate::execution::GraphQLError;
+use crate::execution::JsonValue;
+use crate::execution::Request;
+use crate::execution::Response;
+use crate::validation::DiagnosticList;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Schema;
+use serde::Deserialize;
+
+/// A set of trusted operations, also known as persisted queries or persisted documents,
+/// each identified by an ID.
+///
+/// Every operation is validated against the schema when it is added to the manifest,
+/// so requests that [`resolve`][Self::resolve] to an operation do not need to parse or validate it.
+/// This can also be used as a safelist that rejects any other operation.
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::PersistedQueryManifest;
+/// use apollo_compiler::execution::Request;
+/// use apollo_compiler::Schema;
+///
+/// let schema = Schema::parse_and_validate("type Query { a: Int }", "schema.graphql").unwrap();
+/// let manifest = PersistedQueryManifest::from_relay_json(
+///     &schema,
+///     br#"{ "a1": "query A { a }" }"#,
+/// ).unwrap();
+///
+/// let request = Request::from_json(br#"{ "doc_id": "a1" }"#).unwrap();
+/// let document = &manifest.resolve(&request).unwrap().document;
+/// assert!(document.operations.get(Some("A")).is_ok());
+///
+/// let request = Request::from_json(br#"{ "query": "{ __typename }" }"#).unwrap();
+/// assert!(manifest.resolve(&request).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PersistedQueryManifest {
+    operations: IndexMap<String, PersistedQuery>,
+    ids_by_body: HashMap<String, String>,
+    require_id: bool,
+}
+
+/// An entry of a [`PersistedQueryManifest`]
+#[derive(Debug, Clone)]
+pub struct PersistedQuery {
+    /// The ID that clients send instead of the query,
+    /// such as in the `documentId` request parameter
+    pub id: String,
+
+    /// The name of the operation, if provided by the manifest
+    pub name: Option<String>,
+
+    /// The source text of the executable document
+    pub body: String,
+
+    /// The executable document parsed from `body` and validated against the schema
+    pub document: Valid<ExecutableDocument>,
+}
+
+/// An error when loading a [`PersistedQueryManifest`]
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum ManifestError {
+    #[error("invalid JSON in manifest: {0}")]
+    InvalidJson(String),
+
+    #[error("invalid manifest: expected {expected}")]
+    InvalidFormat { expected: &'static str },
+
+    #[error("duplicate persisted query ID `{id}`")]
+    DuplicateId { id: String },
+
+    /// The body of an entry is not a valid executable document.
+    /// Diagnostics use the ID as the file name.
+    #[error("invalid persisted query `{id}`:\n{errors}")]
+    InvalidOperation { id: String, errors: DiagnosticList },
+}
+
+/// A request that cannot be executed according to a [`PersistedQueryManifest`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum PersistedQueryError {
+    /// The requested ID is not in the manifest
+    #[error("persisted query `{id}` not found")]
+    NotFound { id: String },
+
+    /// The request has both an ID and a query that is not the one for this ID
+    #[error("query does not match persisted query `{id}`")]
+    QueryMismatch { id: String },
+
+    /// The request has a query but no ID, and the manifest requires IDs
+    #[error("a persisted query ID is required")]
+    IdRequired,
+
+    /// The request has a query but no ID, and the query is not in the manifest
+    #[error("operation is not in the safelist")]
+    NotInSafelist,
+}
+
+/// The JSON format generated by Apollo tooling, such as `rover persisted-queries`
+#[derive(Deserialize)]
+struct ApolloManifest {
+    format: String,
+    version: u64,
+    operations: Vec<ApolloManifestOperation>,
+}
+
+#[derive(Deserialize)]
+struct ApolloManifestOperation {
+    id: String,
+    name: Option<String>,
+    body: String,
+}
+
+/// Returns the ID of a persisted query as in [automatic persisted queries]:
+/// the lowercase hexadecimal SHA-256 hash of the UTF-8 body.
+///
+/// [automatic persisted queries]: https://www.apollographql.com/docs/apollo-server/performance/apq/
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::persisted_query_id;
+///
+/// assert_eq!(
+///     persisted_query_id("{ a }"),
+///     "1c7e1e347f726166b5b1c55afd61f278cc9b45e00c108ec33d540a566379811b",
+/// );
+/// ```
+pub fn persisted_query_id(body: &str) -> String {
+    sha256_hex(body.as_bytes())
+}
+
+impl PersistedQueryManifest {
+    /// Returns an empty manifest
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a manifest in the Apollo JSON format:
+    ///
+    /// ```json
+    /// {
+    ///   "format": "apollo-persisted-query-manifest",
+    ///   "version": 1,
+    ///   "operations": [
+    ///     { "id": "…", "name": "GetUser", "type": "query", "body": "query GetUser { … }" }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Returns every error found, including every entry that is invalid against `schema`.
+    pub fn from_apollo_json(
+        schema: &Valid<Schema>,
+        json: &[u8],
+    ) -> Result<Self, Vec<ManifestError>> {
+        let manifest: ApolloManifest = serde_json_bytes::serde_json::from_slice(json)
+            .map_err(|error| vec![ManifestError::InvalidJson(error.to_string())])?;
+        if manifest.format != "apollo-persisted-query-manifest" || manifest.version != 1 {
+            return Err(vec![ManifestError::InvalidFormat {
+                expected: "format `apollo-persisted-query-manifest` version 1",
+            }]);
+        }
+        Self::from_entries(
+            schema,
+            manifest
+                .operations
+                .into_iter()
+                .map(|operation| (operation.id, operation.name, operation.body)),
+        )
+    }
+
+    /// Loads a manifest in the Relay JSON format, an object mapping IDs to bodies:
+    ///
+    /// ```json
+    /// { "…": "query GetUser { … }" }
+    /// ```
+    ///
+    /// Returns every error found, including every entry that is invalid against `schema`.
+    pub fn from_relay_json(
+        schema: &Valid<Schema>,
+        json: &[u8],
+    ) -> Result<Self, Vec<ManifestError>> {
+        let invalid_format = || {
+            vec![ManifestError::InvalidFormat {
+                expected: "an object with string values",
+            }]
+        };
+        let value: JsonValue = serde_json_bytes::serde_json::from_slice(json)
+            .map_err(|error| vec![ManifestError::InvalidJson(error.to_string())])?;
+        let JsonValue::Object(map) = value else {
+            return Err(invalid_format());
+        };
+        let entries = map
+            .into_iter()
+            .map(|(id, body)| match body {
+                JsonValue::String(body) => {
+                    Ok((id.as_str().to_owned(), None, body.as_str().to_owned()))
+                }
+                _ => Err(invalid_format()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_entries(schema, entries)
+    }
+
+    fn from_entries(
+        schema: &Valid<Schema>,
+        entries: impl IntoIterator<Item = (String, Option<String>, String)>,
+    ) -> Result<Self, Vec<ManifestError>> {
+        let mut manifest = Self::new();
+        let mut errors = Vec::new();
+        for (id, name, body) in entries {
+            if let Err(error) = manifest.insert(schema, id, name, body) {
+                errors.push(error)
+            }
+        }
+        if errors.is_empty() {
+            Ok(manifest)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parses and validates `body` against `schema`, then adds it to the manifest
+    pub fn insert(
+        &mut self,
+        schema: &Valid<Schema>,
+        id: impl Into<String>,
+        name: Option<String>,
+        body: impl Into<String>,
+    ) -> Result<(), ManifestError> {
+        let id = id.into();
+        let body = body.into();
+        if self.operations.contains_key(&id) {
+            return Err(ManifestError::DuplicateId { id });
+        }
+        let document =
+            ExecutableDocument::parse_and_validate(schema, &body, &id).map_err(|with_errors| {
+                ManifestError::InvalidOperation {
+                    id: id.clone(),
+                    errors: with_errors.errors,
+                }
+            })?;
+        self.ids_by_body
+            .entry(body.clone())
+            .or_insert_with(|| id.clone());
+        self.operations.insert(
+            id.clone(),
+            PersistedQuery {
+                id,
+                name,
+                body,
+                document,
+            },
+        );
+        Ok(())
+    }
+
+    /// Set whether [`resolve`][Self::resolve] rejects requests without a persisted query ID,
+    /// even if their query is in the manifest. Defaults to `false`.
+    pub fn require_id(mut self, value: bool) -> Self {
+        self.require_id = value;
+        self
+    }
+
+    /// Returns the entry for the given ID, if any
+    pub fn get(&self, id: &str) -> Option<&PersistedQuery> {
+        self.operations.get(id)
+    }
+
+    /// Returns an iterator of entries, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &PersistedQuery> {
+        self.operations.values()
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns whether there is no entry
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Returns the entry to execute for `request`, or an error if it should be rejected.
+    ///
+    /// * If the request has a [persisted query ID][Request::persisted_query_id],
+    ///   returns the entry for that ID.
+    ///   If the request also has a query, it must be the body of that entry.
+    /// * Otherwise, the request is a freeform operation.
+    ///   Unless [`require_id`][Self::require_id] is set,
+    ///   returns an entry whose body is exactly the query of the request.
+    ///
+    /// The operation to execute is then selected with the request’s
+    /// [`operation_name`][Request::operation_name] as usual.
+    pub fn resolve(&self, request: &Request) -> Result<&PersistedQuery, PersistedQueryError> {
+        if let Some(id) = request.persisted_query_id() {
+            let entry = self
+                .operations
+                .get(id)
+                .ok_or_else(|| PersistedQueryError::NotFound { id: id.to_owned() })?;
+            if request
+                .query
+                .as_ref()
+                .is_some_and(|query| *query != entry.body)
+            {
+                return Err(PersistedQueryError::QueryMismatch { id: id.to_owned() });
+            }
+            return Ok(entry);
+        }
+        if self.require_id {
+            return Err(PersistedQueryError::IdRequired);
+        }
+        request
+            .query
+            .as_ref()
+            .and_then(|query| self.ids_by_body.get(query))
+            .map(|id| &self.operations[id])
+            .ok_or(PersistedQueryError::NotInSafelist)
+    }
+}
+
+impl PersistedQueryError {
+    /// The error code to use in the `extensions` of a GraphQL response
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "PERSISTED_QUERY_NOT_IN_LIST",
+            Self::QueryMismatch { .. } => "PERSISTED_QUERY_HASH_MISMATCH",
+            Self::IdRequired => "PERSISTED_QUERY_ID_REQUIRED",
+            Self::NotInSafelist => "QUERY_NOT_IN_SAFELIST",
+        }
+    }
+
+    /// Convert into a JSON-serializable error as represented in a GraphQL response
+    pub fn into_graphql_error(self) -> GraphQLError {
+        let mut error = GraphQLError::new(self.to_string(), None, &Default::default());
+        error.extensions.insert("code", self.code().into());
+        error
+    }
+
+    /// Convert into a response with this error as a [request error]
+    /// that prevented execution from starting.
+    ///
+    /// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+    pub fn into_response(self) -> Response {
+        Response::from_request_error(self.into_graphql_error())
+    }
+}
@@ -0,0 +1,379 @@
+use crate::executable::OperationType;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::ExecutableDocument;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A GraphQL request as sent over HTTP, following the [GraphQL-over-HTTP] specification.
+///
+/// Fields have the types expected by [`ExecutableDocument::parse_and_validate`],
+/// [`coerce_variable_values`][crate::execution::coerce_variable_values],
+/// and [`execute_request`][crate::execution::execute_request].
+///
+/// [GraphQL-over-HTTP]: https://graphql.github.io/graphql-over-http/draft/
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::execute_request;
+/// use apollo_compiler::execution::ObjectValue;
+/// use apollo_compiler::execution::Request;
+/// use apollo_compiler::execution::RequestError;
+/// use apollo_compiler::execution::Response;
+/// use apollo_compiler::execution::ResponseData;
+/// use apollo_compiler::validation::Valid;
+/// use apollo_compiler::ExecutableDocument;
+/// use apollo_compiler::Schema;
+///
+/// fn handle_post(schema: &Valid<Schema>, body: &[u8], root_value: &ObjectValue) -> Response {
+///     let request = match Request::from_json(body) {
+///         Ok(request) => request,
+///         Err(error) => return error.into_response(),
+///     };
+///     let Some(query) = &request.query else {
+///         // This server does not support persisted queries
+///         return RequestError::MissingQuery.into_response();
+///     };
+///     let document = match ExecutableDocument::parse_and_validate(
+///         schema,
+///         query,
+///         "request.graphql",
+///     ) {
+///         Ok(document) => document,
+///         Err(with_errors) => {
+///             // Report every validation error as a request error
+///             return Response {
+///                 errors: with_errors
+///                     .errors
+///                     .iter()
+///                     .map(|diagnostic| diagnostic.to_json())
+///                     .collect(),
+///                 data: ResponseData::Absent,
+///                 extensions: Default::default(),
+///             };
+///         }
+///     };
+///     execute_request(
+///         schema,
+///         &document,
+///         request.operation_name.as_deref(),
+///         &request.variables,
+///         root_value,
+///     )
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(try_from = "JsonValue")]
+pub struct Request {
+    /// The source text of an executable document.
+    ///
+    /// May only be absent if the request has a [`document_id`][Self::document_id]
+    /// for a [persisted query][crate::execution::PersistedQueryManifest].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// The name of the operation to execute,
+    /// required if the document contains more than one operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+
+    /// The ID of a [persisted query][crate::execution::PersistedQueryManifest],
+    /// from the `documentId` or `doc_id` parameter.
+    ///
+    /// See also [`persisted_query_id`][Self::persisted_query_id].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_id: Option<String>,
+
+    /// Values for variables of the operation, before coercion
+    #[serde(skip_serializing_if = "JsonMap::is_empty")]
+    pub variables: JsonMap,
+
+    /// Reserved for implementors to extend the protocol
+    #[serde(skip_serializing_if = "JsonMap::is_empty")]
+    pub extensions: JsonMap,
+}
+
+/// The JSON body of a POST request: either a single request or an array of requests.
+///
+/// A batch is executed as separate requests,
+/// and their responses should be sent back as a JSON array in the same order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)] // single requests are the common case
+pub enum BatchRequest {
+    Single(Request),
+    Batch(Vec<Request>),
+}
+
+/// A request that does not follow the [GraphQL-over-HTTP] specification
+///
+/// [GraphQL-over-HTTP]: https://graphql.github.io/graphql-over-http/draft/
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RequestError {
+    /// The request body could not be parsed as JSON, with the message of the parse error
+    #[error("invalid JSON in request body: {0}")]
+    InvalidJson(String),
+
+    /// The request body is JSON but neither an object nor a non-empty array of objects
+    #[error("request body must be a JSON object or a non-empty array of objects")]
+    InvalidBody,
+
+    /// Neither a `query` parameter nor a persisted query ID
+    #[error("missing `query` parameter")]
+    MissingQuery,
+
+    /// A request parameter has a value of the wrong type
+    #[error("invalid `{name}` parameter: expected {expected}")]
+    InvalidParameter {
+        /// The name of the parameter, such as `variables`
+        name: &'static str,
+        /// A description of the expected value, such as `an object`
+        expected: &'static str,
+    },
+
+    /// GET requests must not have side effects
+    #[error("{operation_type} operations are not allowed in GET requests, use POST instead")]
+    OperationNotAllowedOverGet { operation_type: OperationType },
+}
+
+impl Request {
+    /// Returns a request for the given executable document source,
+    /// without operation name, variables, or extensions
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: Some(query.into()),
+            operation_name: None,
+            document_id: None,
+            variables: JsonMap::new(),
+            extensions: JsonMap::new(),
+        }
+    }
+
+    /// Parses the JSON body of a POST request containing a single request.
+    ///
+    /// Use [`BatchRequest::from_json`] to also accept an array of requests.
+    pub fn from_json(body: &[u8]) -> Result<Self, RequestError> {
+        parse_json(body)?.try_into()
+    }
+
+    /// Parses the parameters of a GET request from an URL query string (without the leading `?`).
+    ///
+    /// `variables` and `extensions` are JSON-encoded.
+    /// Unknown parameters are ignored.
+    ///
+    /// Before executing a GET request,
+    /// use [`check_http_get`][Self::check_http_get] to reject mutations.
+    pub fn from_query_string(query_string: &str) -> Result<Self, RequestError> {
+        let mut map = JsonMap::new();
+        for (key, value) in parse_query_string(query_string) {
+            let value = match key.as_str() {
+                "variables" => parse_json_parameter(&value, "variables")?,
+                "extensions" => parse_json_parameter(&value, "extensions")?,
+                _ => value.into(),
+            };
+            // Like most URL query string parsers, the last occurrence of a parameter wins
+            map.insert(key, value);
+        }
+        Self::from_map(map)
+    }
+
+    /// Returns an error if the operation to execute is not a query.
+    ///
+    /// The [GraphQL-over-HTTP] specification only allows query operations in GET requests.
+    /// If the operation is not found, this returns `Ok`
+    /// and execution will return a request error.
+    ///
+    /// [GraphQL-over-HTTP]: https://graphql.github.io/graphql-over-http/draft/#sel-EALFPCCBCEtC37P
+    pub fn check_http_get(&self, document: &ExecutableDocument) -> Result<(), RequestError> {
+        match document.operations.get(self.operation_name.as_deref()) {
+            Ok(operation) if operation.operation_type != OperationType::Query => {
+                Err(RequestError::OperationNotAllowedOverGet {
+                    operation_type: operation.operation_type,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the ID of the persisted query to execute, if any:
+    /// either [`document_id`][Self::document_id]
+    /// or the `sha256Hash` of the `persistedQuery` extension
+    /// used by [automatic persisted queries].
+    ///
+    /// [automatic persisted queries]: https://www.apollographql.com/docs/apollo-server/performance/apq/
+    pub fn persisted_query_id(&self) -> Option<&str> {
+        self.document_id.as_deref().or_else(|| {
+            self.extensions
+                .get("persistedQuery")?
+                .get("sha256Hash")?
+                .as_str()
+        })
+    }
+
+    fn from_map(mut map: JsonMap) -> Result<Self, RequestError> {
+        let query = string_parameter(&mut map, "query")?;
+        let operation_name = string_parameter(&mut map, "operationName")?;
+        let document_id = match string_parameter(&mut map, "documentId")? {
+            Some(id) => Some(id),
+            None => string_parameter(&mut map, "doc_id")?,
+        };
+        let request = Self {
+            query,
+            operation_name,
+            document_id,
+            variables: object_parameter(&mut map, "variables")?,
+            extensions: object_parameter(&mut map, "extensions")?,
+        };
+        if request.query.is_none() && request.persisted_query_id().is_none() {
+            return Err(RequestError::MissingQuery);
+        }
+        Ok(request)
+    }
+}
+
+impl TryFrom<JsonValue> for Request {
+    type Error = RequestError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Object(map) => Self::from_map(map),
+            _ => Err(RequestError::InvalidBody),
+        }
+    }
+}
+
+impl BatchRequest {
+    /// Parses the JSON body of a POST request,
+    /// containing either a single request object or a non-empty array of them.
+    pub fn from_json(body: &[u8]) -> Result<Self, RequestError> {
+        match parse_json(body)? {
+            JsonValue::Array(items) if !items.is_empty() => Ok(Self::Batch(
+                items
+                    .into_iter()
+                    .map(Request::try_from)
+                    .collect::<Result<_, _>>()?,
+            )),
+            value @ JsonValue::Object(_) => Ok(Self::Single(value.try_into()?)),
+            _ => Err(RequestError::InvalidBody),
+        }
+    }
+
+    /// Returns whether responses should be sent back as a JSON array
+    pub fn is_batch(&self) -> bool {
+        matches!(self, Self::Batch(_))
+    }
+
+    /// Returns the requests to execute, in order
+    pub fn into_requests(self) -> Vec<Request> {
+        match self {
+            Self::Single(request) => vec![request],
+            Self::Batch(requests) => requests,
+        }
+    }
+}
+
+impl RequestError {
+    /// The HTTP status code to respond with:
+    /// 405 Method Not Allowed for [`OperationNotAllowedOverGet`][Self::OperationNotAllowedOverGet],
+    /// 400 Bad Request otherwise.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::OperationNotAllowedOverGet { .. } => 405,
+            _ => 400,
+        }
+    }
+
+    /// Convert into a JSON-serializable error as represented in a GraphQL response
+    pub fn into_graphql_error(self) -> GraphQLError {
+        GraphQLError::new(self.to_string(), None, &Default::default())
+    }
+
+    /// Convert into a response with this error as a [request error]
+    /// that prevented execution from starting.
+    ///
+    /// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+    pub fn into_response(self) -> Response {
+        Response::from_request_error(self.into_graphql_error())
+    }
+}
+
+fn parse_json(body: &[u8]) -> Result<JsonValue, RequestError> {
+    serde_json_bytes::serde_json::from_slice(body)
+        .map_err(|error| RequestError::InvalidJson(error.to_string()))
+}
+
+/// Parses a JSON-encoded query string parameter
+fn parse_json_parameter(value: &str, name: &'static str) -> Result<JsonValue, RequestError> {
+    parse_json(value.as_bytes()).map_err(|_| RequestError::InvalidParameter {
+        name,
+        expected: "a JSON-encoded object",
+    })
+}
+
+/// Removes an optional parameter that must be a string if present and non-null
+fn string_parameter(map: &mut JsonMap, name: &'static str) -> Result<Option<String>, RequestError> {
+    match map.remove(name) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::String(value)) => Ok(Some(value.as_str().to_owned())),
+        Some(_) => Err(RequestError::InvalidParameter {
+            name,
+            expected: "a string",
+        }),
+    }
+}
+
+/// Removes an optional parameter that must be an object if present and non-null
+fn object_parameter(map: &mut JsonMap, name: &'static str) -> Result<JsonMap, RequestError> {
+    match map.remove(name) {
+        None | Some(JsonValue::Null) => Ok(JsonMap::new()),
+        Some(JsonValue::Object(object)) => Ok(object),
+        Some(_) => Err(RequestError::InvalidParameter {
+            name,
+            expected: "an object",
+        }),
+    }
+}
+
+/// Splits `application/x-www-form-urlencoded` key-value pairs and decodes them
+fn parse_query_string(query_string: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+}
+
+/// Decodes `+` as space and `%XX` escapes.
+/// Invalid escapes are kept as-is, and invalid UTF-8 is replaced with U+FFFD.
+fn percent_decode(input: &str) -> String {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        rest = after;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(decoded) = hex {
+                    bytes.push(decoded);
+                    rest = &rest[2..];
+                } else {
+                    bytes.push(b'%')
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
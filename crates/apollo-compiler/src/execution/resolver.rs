@@ -1,19 +1,21 @@
 use crate::execution::JsonMap;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
 use serde_json_bytes::Value as JsonValue;
 
 /// A GraphQL object whose fields can be resolved during execution
-pub(crate) type ObjectValue<'a> = dyn Resolver + 'a;
+pub type ObjectValue<'a> = dyn Resolver + 'a;
 
 /// Abstraction for implementing field resolvers. Used through [`ObjectValue`].
 ///
 /// Use the [`impl_resolver!`][crate::impl_resolver] macro to implement this trait
 /// with reduced boilerplate
-pub(crate) trait Resolver {
+pub trait Resolver {
     /// Returns the name of the concrete object type this resolver represents
     ///
     /// That name expected to be that of an object type defined in the schema.
     /// This is called when the schema indicates an abstract (interface or union) type.
-    fn type_name(&self) -> &'static str;
+    fn type_name(&self) -> &str;
 
     /// Resolves a field of this object with the given arguments
     ///
@@ -24,13 +26,68 @@ pub(crate) trait Resolver {
         field_name: &'a str,
         arguments: &'a JsonMap,
     ) -> Result<ResolvedValue<'_>, ResolverError>;
+
+    /// Returns a key identifying this object for [batched resolution][Self::resolve_field_batch],
+    /// or `None` to always resolve its fields one object at a time.
+    ///
+    /// The default implementation returns `None`.
+    fn batch_key(&self) -> Option<JsonValue> {
+        None
+    }
+
+    /// Resolves a field for a batch of objects of this type at once,
+    /// such as to make a single call to a backend instead of one per list item.
+    ///
+    /// When completing a list, items that are objects with a [batch key][Self::batch_key]
+    /// are grouped by [type name][Self::type_name].
+    /// Other items are completed one at a time.
+    /// For each field selected on a group, this method is called on the first object of the group
+    /// with the batch keys of all objects of the group.
+    ///
+    /// If this returns `Some`, the `Vec` is expected to contain one result per key, in the same order.
+    /// Each result is then used as if it was returned by [`resolve_field`][Self::resolve_field]
+    /// for the corresponding object, at the path of that object in the response.
+    /// If this returns `None`, `resolve_field` is called for each object instead.
+    ///
+    /// Each list is batched separately: for nested selections such as `users { friends { name } }`,
+    /// `name` is resolved in one batch per `friends` list.
+    /// [`ExecutionLimits`][crate::execution::ExecutionLimits] and cancellation are checked
+    /// before each call, counting the field as resolved once per key.
+    /// Batching is not used by [`execute_incremental_request`][crate::execution::execute_incremental_request],
+    /// nor with [field middleware][crate::execution::Executor::field_middleware]
+    /// or [tracing][crate::execution::Executor::tracing].
+    ///
+    /// The default implementation returns `None`.
+    fn resolve_field_batch<'a>(
+        &'a self,
+        field_name: &'a str,
+        arguments: &'a JsonMap,
+        keys: &'a [JsonValue],
+    ) -> Option<Vec<Result<ResolvedValue<'a>, ResolverError>>> {
+        let _ = (field_name, arguments, keys);
+        None
+    }
 }
 
-pub(crate) struct ResolverError {
-    pub(crate) message: String,
+/// An error returned by [`Resolver::resolve_field`], turned into a [field error] by execution
+///
+/// [field error]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+#[derive(Debug, Clone)]
+pub struct ResolverError {
+    /// A description of the error, included in the message of the resulting GraphQL error
+    pub message: String,
+}
+
+impl ResolverError {
+    /// Returns an error with the given message
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
 }
 
-/// Implements the [`Resolver`] trait with reduced boilerplate
+/// Implements the [`Resolver`][crate::execution::Resolver] trait with reduced boilerplate
 ///
 /// Define:
 ///
@@ -38,9 +95,36 @@ pub(crate) struct ResolverError {
 /// * The __typename string
 /// * One pseudo-method per field. Types are omitted in the signature for brevity.
 ///   - Takes two optional arguments: `&self` (which must be spelled something else because macros)
-///     and `args: `[`&JsonMap`][crate::JsonMap] for the field arguments.
+///     and `args: `[`&JsonMap`][crate::execution::JsonMap] for the field arguments.
 ///     Field arguments are coerced according to their definition in the schema.
-///   - Returns `Result<ResolvedValue, String>`, `Err` it turned into a field error
+///   - Returns `Result<`[`ResolvedValue`][crate::execution::ResolvedValue]`, `[`ResolverError`][crate::execution::ResolverError]`>`,
+///     `Err` is turned into a field error
+///
+/// # Example
+///
+/// ```
+/// use apollo_compiler::execution::ResolvedValue;
+/// use apollo_compiler::impl_resolver;
+///
+/// struct Query {
+///     world: String,
+/// }
+///
+/// impl_resolver! {
+///     for Query:
+///
+///     __typename = "Query";
+///
+///     fn hello(&self_) {
+///         Ok(ResolvedValue::leaf(format!("Hello {}!", self_.world)))
+///     }
+///
+///     fn echo(&_self, args) {
+///         Ok(ResolvedValue::leaf(args["value"].clone()))
+///     }
+/// }
+/// ```
+#[macro_export]
 macro_rules! impl_resolver {
     (
         for $ty: ty:
@@ -52,8 +136,8 @@ macro_rules! impl_resolver {
         )*
 
     ) => {
-        impl $crate::execution::resolver::Resolver for $ty {
-            fn type_name(&self) -> &'static str {
+        impl $crate::execution::Resolver for $ty {
+            fn type_name(&self) -> &str {
                 $type_name
             }
 
@@ -62,8 +146,8 @@ macro_rules! impl_resolver {
                 field_name: &'a str,
                 arguments: &'a $crate::execution::JsonMap,
             ) -> Result<
-                $crate::execution::resolver::ResolvedValue<'_>,
-                crate::execution::resolver::ResolverError
+                $crate::execution::ResolvedValue<'_>,
+                $crate::execution::ResolverError
             > {
                 let _allow_unused = arguments;
                 match field_name {
@@ -78,7 +162,7 @@ macro_rules! impl_resolver {
                             return $block
                         },
                     )*
-                    _ => Err(crate::execution::resolver::ResolverError {
+                    _ => Err($crate::execution::ResolverError {
                         message: format!(
                             "unexpected field name: {field_name} in type {}",
                             self.type_name()
@@ -91,7 +175,7 @@ macro_rules! impl_resolver {
 }
 
 /// The value of a resolved field
-pub(crate) enum ResolvedValue<'a> {
+pub enum ResolvedValue<'a> {
     /// * JSON null represents GraphQL null
     /// * A GraphQL enum value is represented as a JSON string
     /// * GraphQL built-in scalars are coerced according to their respective *Result Coercion* spec
@@ -107,22 +191,22 @@ pub(crate) enum ResolvedValue<'a> {
 
 impl<'a> ResolvedValue<'a> {
     /// Construct a null leaf resolved value
-    pub(crate) fn null() -> Self {
+    pub fn null() -> Self {
         Self::Leaf(JsonValue::Null)
     }
 
     /// Construct a leaf resolved value from something that is convertible to JSON
-    pub(crate) fn leaf(json: impl Into<JsonValue>) -> Self {
+    pub fn leaf(json: impl Into<JsonValue>) -> Self {
         Self::Leaf(json.into())
     }
 
     /// Construct an object resolved value from the resolver for that object
-    pub(crate) fn object(resolver: impl Resolver + 'a) -> Self {
+    pub fn object(resolver: impl Resolver + 'a) -> Self {
         Self::Object(Box::new(resolver))
     }
 
     /// Construct an object resolved value or null, from an optional resolver
-    pub(crate) fn opt_object(opt_resolver: Option<impl Resolver + 'a>) -> Self {
+    pub fn opt_object(opt_resolver: Option<impl Resolver + 'a>) -> Self {
         match opt_resolver {
             Some(resolver) => Self::Object(Box::new(resolver)),
             None => Self::null(),
@@ -130,7 +214,7 @@ impl<'a> ResolvedValue<'a> {
     }
 
     /// Construct a list resolved value from an iterator
-    pub(crate) fn list<I>(iter: I) -> Self
+    pub fn list<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = Self>,
         I::IntoIter: 'a,
@@ -139,9 +223,129 @@ impl<'a> ResolvedValue<'a> {
     }
 }
 
+/// A GraphQL object whose fields can be resolved asynchronously during execution
+pub type AsyncObjectValue<'a> = dyn AsyncResolver + 'a;
+
+/// Abstraction for implementing asynchronous field resolvers. Used through [`AsyncObjectValue`].
+///
+/// This is similar to [`Resolver`], except that [`resolve_field`][Self::resolve_field]
+/// returns a future. Execution only relies on [`Future`][std::future::Future]
+/// and does not depend on any particular async runtime.
+pub trait AsyncResolver: Send + Sync {
+    /// Returns the name of the concrete object type this resolver represents
+    ///
+    /// That name expected to be that of an object type defined in the schema.
+    /// This is called when the schema indicates an abstract (interface or union) type.
+    fn type_name(&self) -> &str;
+
+    /// Resolves a field of this object with the given arguments
+    ///
+    /// The resolved is expected to match the type of the corresponding field definition
+    /// in the schema.
+    fn resolve_field<'a>(
+        &'a self,
+        field_name: &'a str,
+        arguments: &'a JsonMap,
+    ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>>;
+
+    /// Like [`Resolver::batch_key`]
+    fn batch_key(&self) -> Option<JsonValue> {
+        None
+    }
+
+    /// Like [`Resolver::resolve_field_batch`], but returns a future
+    fn resolve_field_batch<'a>(
+        &'a self,
+        field_name: &'a str,
+        arguments: &'a JsonMap,
+        keys: &'a [JsonValue],
+    ) -> Option<BoxFuture<'a, Vec<Result<AsyncResolvedValue<'a>, ResolverError>>>> {
+        let _ = (field_name, arguments, keys);
+        None
+    }
+}
+
+/// The value of a field resolved by an [`AsyncResolver`]
+pub enum AsyncResolvedValue<'a> {
+    /// * JSON null represents GraphQL null
+    /// * A GraphQL enum value is represented as a JSON string
+    /// * GraphQL built-in scalars are coerced according to their respective *Result Coercion* spec
+    /// * For custom scalars, any JSON value is passed through as-is (including array or object)
+    Leaf(JsonValue),
+
+    /// Expected where the GraphQL type is an object, interface, or union type
+    Object(Box<AsyncObjectValue<'a>>),
+
+    /// Expected for GraphQL list types
+    List(Box<dyn Iterator<Item = AsyncResolvedValue<'a>> + Send + 'a>),
+}
+
+impl<'a> AsyncResolvedValue<'a> {
+    /// Construct a null leaf resolved value
+    pub fn null() -> Self {
+        Self::Leaf(JsonValue::Null)
+    }
+
+    /// Construct a leaf resolved value from something that is convertible to JSON
+    pub fn leaf(json: impl Into<JsonValue>) -> Self {
+        Self::Leaf(json.into())
+    }
+
+    /// Construct an object resolved value from the resolver for that object
+    pub fn object(resolver: impl AsyncResolver + 'a) -> Self {
+        Self::Object(Box::new(resolver))
+    }
+
+    /// Construct an object resolved value or null, from an optional resolver
+    pub fn opt_object(opt_resolver: Option<impl AsyncResolver + 'a>) -> Self {
+        match opt_resolver {
+            Some(resolver) => Self::Object(Box::new(resolver)),
+            None => Self::null(),
+        }
+    }
+
+    /// Construct a list resolved value from an iterator
+    pub fn list<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+        I::IntoIter: Send + 'a,
+    {
+        Self::List(Box::new(iter.into_iter()))
+    }
+}
+
+/// A stream of [source events] for a subscription operation,
+/// as returned by [`SubscriptionResolver::resolve_field_event_stream`].
+///
+/// Each event is an object of the subscription root operation type,
+/// used as the initial value when executing the operation’s selection set.
+/// An `Err` item is turned into a response with a [request error].
+///
+/// [source events]: https://spec.graphql.org/October2021/#sec-Source-Stream
+/// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+pub type SourceEventStream<'a> = BoxStream<'a, Result<Box<AsyncObjectValue<'a>>, ResolverError>>;
+
+/// Abstraction for creating the [source stream] of a subscription operation
+///
+/// [source stream]: https://spec.graphql.org/October2021/#sec-Source-Stream
+pub trait SubscriptionResolver: Send + Sync {
+    /// Resolves the root field of a subscription operation to a stream of events
+    ///
+    /// This is [ResolveFieldEventStream()](https://spec.graphql.org/October2021/#ResolveFieldEventStream())
+    /// in the GraphQL specification.
+    ///
+    /// Unlike [`AsyncResolver::resolve_field`], `arguments` are passed by value
+    /// since the returned stream may need them for as long as the subscription lasts.
+    fn resolve_field_event_stream<'a>(
+        &'a self,
+        field_name: &'a str,
+        arguments: JsonMap,
+    ) -> BoxFuture<'a, Result<SourceEventStream<'a>, ResolverError>>;
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::execution::resolver::ResolvedValue;
+    use crate::execution::ResolvedValue;
 
     struct QueryResolver {
         world: String,
@@ -1,5 +1,6 @@
 use crate::execution::engine::PropagateNull;
 use crate::execution::JsonMap;
+use crate::execution::JsonValue;
 use crate::parser::LineColumn;
 use crate::parser::SourceMap;
 use crate::parser::SourceSpan;
@@ -25,6 +26,126 @@ pub struct Response {
     pub extensions: JsonMap,
 }
 
+/// The first payload of an [incremental delivery] response,
+/// as returned by [`execute_incremental_request`][crate::execution::execute_incremental_request]
+///
+/// This is a [`Response`] with additional `pending` and `hasNext` entries.
+///
+/// [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct InitialIncrementalResponse {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+
+    #[serde(skip_serializing_if = "ResponseData::is_absent")]
+    #[serde(default = "ResponseData::absent")]
+    pub data: ResponseData,
+
+    /// Deferred fragments and streamed lists whose results will be delivered
+    /// in [subsequent payloads][SubsequentIncrementalResponse]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub pending: Vec<PendingResult>,
+
+    /// Whether more payloads will follow
+    pub has_next: bool,
+
+    /// Reserved for any additional information
+    #[serde(skip_serializing_if = "JsonMap::is_empty")]
+    #[serde(default)]
+    pub extensions: JsonMap,
+}
+
+/// A payload following [`InitialIncrementalResponse`] in an [incremental delivery] response
+///
+/// [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsequentIncrementalResponse {
+    /// Deferred fragments and streamed lists announced in this payload,
+    /// nested in results delivered in this payload.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub pending: Vec<PendingResult>,
+
+    /// Results for previously announced [`PendingResult`]s
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub incremental: Vec<IncrementalResult>,
+
+    /// Previously announced [`PendingResult`]s that will not receive any more results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub completed: Vec<CompletedResult>,
+
+    /// Whether more payloads will follow
+    pub has_next: bool,
+
+    /// Reserved for any additional information
+    #[serde(skip_serializing_if = "JsonMap::is_empty")]
+    #[serde(default)]
+    pub extensions: JsonMap,
+}
+
+/// A deferred fragment or streamed list whose results will be delivered later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PendingResult {
+    /// Identifies this result in later [`IncrementalResult`]s and [`CompletedResult`]s
+    pub id: String,
+
+    /// For a deferred fragment, the path to the object the fragment applies to.
+    /// For a streamed list, the path to the list.
+    pub path: Vec<ResponseDataPathElement>,
+
+    /// The `label` argument of the `@defer` or `@stream` directive, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Data for a [`PendingResult`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IncrementalResult {
+    /// Fields of a deferred fragment,
+    /// to be merged into the object at the path of the pending result
+    Defer {
+        id: String,
+        data: JsonMap,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        #[serde(default)]
+        errors: Vec<GraphQLError>,
+    },
+
+    /// Items of a streamed list,
+    /// to be appended to the list at the path of the pending result
+    Stream {
+        id: String,
+        items: Vec<JsonValue>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        #[serde(default)]
+        errors: Vec<GraphQLError>,
+    },
+}
+
+/// Marks a [`PendingResult`] as done
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompletedResult {
+    pub id: String,
+
+    /// If non-empty, an error propagated null to the root of the deferred fragment
+    /// or streamed list item, and its data was not delivered.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
 /// The `data` entry of a [`Response`]
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(from = "Option<JsonMap>")]
@@ -123,6 +244,24 @@ impl Response {
     }
 }
 
+impl From<Response> for InitialIncrementalResponse {
+    /// Convert a response without any deferred fragment or streamed list
+    fn from(response: Response) -> Self {
+        let Response {
+            errors,
+            data,
+            extensions,
+        } = response;
+        Self {
+            errors,
+            data,
+            pending: Vec::new(),
+            has_next: false,
+            extensions,
+        }
+    }
+}
+
 impl GraphQLError {
     pub fn new(
         message: impl Into<String>,
@@ -0,0 +1,335 @@
+use crate::execution::response_validation::fmt_path;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::execution::ResponseData;
+use crate::execution::ResponseDataPathElement;
+use crate::Name;
+use std::fmt;
+
+/// A problem found by [`Response::merge_at_path`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseMergeError {
+    pub kind: ResponseMergeErrorKind,
+
+    /// The path of the relevant value in the data of the target response
+    pub path: Vec<ResponseDataPathElement>,
+}
+
+/// The kind of a [`ResponseMergeError`]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ResponseMergeErrorKind {
+    /// The merge path does not lead to an object in the target response
+    #[error("no object to merge into")]
+    InvalidTarget,
+
+    /// The path fans out over a list, but incoming data is not an object
+    /// with a single list of one entity per target object
+    #[error("expected incoming data with a single list of {expected} entities")]
+    InvalidEntities { expected: usize },
+
+    /// The target and incoming data have different values at the same path.
+    /// The existing value is kept.
+    #[error("conflicting values {existing} and {incoming}")]
+    ConflictingValues {
+        existing: JsonValue,
+        incoming: JsonValue,
+    },
+}
+
+impl fmt::Display for ResponseMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at path ", self.kind)?;
+        fmt_path(&self.path, f)
+    }
+}
+
+impl std::error::Error for ResponseMergeError {}
+
+/// Objects found by following a merge path in response data
+struct Targets {
+    /// Concrete paths of target objects, in response order
+    paths: Vec<Vec<ResponseDataPathElement>>,
+    /// If the path fans out over a list, the path of the first such list
+    fan_out: Option<Vec<ResponseDataPathElement>>,
+}
+
+impl Response {
+    /// Deep-merges a response from another source, such as a subgraph fetch in a gateway,
+    /// into the data of `self` at the given path.
+    ///
+    /// When a [`Field`][ResponseDataPathElement::Field] element of `path` is applied to a list,
+    /// or when `path` ends at a list,
+    /// the path fans out over every item of that list, skipping null items.
+    /// Otherwise, the path is expected to lead to an object.
+    ///
+    /// * Without fan out, the data of `other` is merged into the object at `path`.
+    ///   Errors of `other` are rebased: their path is appended to `path`.
+    /// * With fan out, the data of `other` is expected to be an object with a single entry
+    ///   whose value is a list with one item per target object, in response order,
+    ///   such as the `_entities` field of an entity fetch.
+    ///   Each item is merged into the corresponding target object,
+    ///   and errors with a path starting with that entry and an item index are rebased
+    ///   onto the path of the corresponding target object.
+    ///
+    /// Other errors of `other`, such as request errors, are rebased onto the path
+    /// up to the first list that the path fans out over.
+    /// Extensions of `other` are added to those of `self`.
+    ///
+    /// Objects are merged recursively, and lists of the same length item by item.
+    /// Other values present on both sides must be equal.
+    /// Returns every place where this is not the case or where the path could not be followed.
+    /// Returns an empty vector if the merge succeeded.
+    pub fn merge_at_path(
+        &mut self,
+        path: &[ResponseDataPathElement],
+        other: Response,
+    ) -> Vec<ResponseMergeError> {
+        let mut merge_errors = Vec::new();
+        let Response {
+            errors,
+            data,
+            extensions,
+        } = other;
+        self.extensions.extend(extensions);
+        let targets = match &self.data {
+            ResponseData::Object(map) => find_targets(map, path, &mut merge_errors),
+            ResponseData::Null | ResponseData::Absent => {
+                merge_errors.push(ResponseMergeError {
+                    kind: ResponseMergeErrorKind::InvalidTarget,
+                    path: Vec::new(),
+                });
+                Targets {
+                    paths: Vec::new(),
+                    fan_out: None,
+                }
+            }
+        };
+        let default_path = targets.fan_out.as_deref().unwrap_or(path);
+        let ResponseData::Object(self_data) = &mut self.data else {
+            self.errors.extend(errors.into_iter().map(|mut error| {
+                error.path.splice(0..0, default_path.iter().cloned());
+                error
+            }));
+            return merge_errors;
+        };
+        let incoming = match data {
+            ResponseData::Object(incoming) => Some(incoming),
+            // Nothing to merge, errors should explain why
+            ResponseData::Null | ResponseData::Absent => None,
+        };
+        let mut entities_key = None;
+        if targets.fan_out.is_none() {
+            if let (Some(incoming), [target_path]) = (incoming, &targets.paths[..]) {
+                let target = object_at_path_mut(self_data, target_path).unwrap();
+                deep_merge(
+                    target,
+                    incoming,
+                    &mut target_path.clone(),
+                    &mut merge_errors,
+                )
+            }
+        } else if let Some(incoming) = incoming {
+            let mut entries = incoming.into_iter();
+            match (entries.next(), entries.next()) {
+                (Some((key, JsonValue::Array(entities))), None)
+                    if entities.len() == targets.paths.len() =>
+                {
+                    for (target_path, entity) in targets.paths.iter().zip(entities) {
+                        let target = object_at_path_mut(self_data, target_path).unwrap();
+                        match entity {
+                            JsonValue::Object(entity) => deep_merge(
+                                target,
+                                entity,
+                                &mut target_path.clone(),
+                                &mut merge_errors,
+                            ),
+                            JsonValue::Null => {}
+                            entity => merge_errors.push(ResponseMergeError {
+                                kind: ResponseMergeErrorKind::ConflictingValues {
+                                    existing: JsonValue::Object(target.clone()),
+                                    incoming: entity,
+                                },
+                                path: target_path.clone(),
+                            }),
+                        }
+                    }
+                    entities_key = Some(key);
+                }
+                _ => merge_errors.push(ResponseMergeError {
+                    kind: ResponseMergeErrorKind::InvalidEntities {
+                        expected: targets.paths.len(),
+                    },
+                    path: default_path.to_vec(),
+                }),
+            }
+        }
+        for mut error in errors {
+            let entity_path = match (&entities_key, &error.path[..]) {
+                (
+                    Some(entities_key),
+                    [ResponseDataPathElement::Field(key), ResponseDataPathElement::ListIndex(index), ..],
+                ) if key == entities_key.as_str() => targets.paths.get(*index),
+                _ => None,
+            };
+            if let Some(entity_path) = entity_path {
+                error.path.splice(0..2, entity_path.iter().cloned());
+            } else {
+                error.path.splice(0..0, default_path.iter().cloned());
+            }
+            self.errors.push(error)
+        }
+        merge_errors
+    }
+}
+
+fn find_targets(
+    data: &JsonMap,
+    path: &[ResponseDataPathElement],
+    merge_errors: &mut Vec<ResponseMergeError>,
+) -> Targets {
+    let mut targets = Targets {
+        paths: Vec::new(),
+        fan_out: None,
+    };
+    let mut current = Vec::new();
+    match path.split_first() {
+        None => targets.paths.push(Vec::new()),
+        Some((ResponseDataPathElement::Field(key), rest)) => {
+            current.push(ResponseDataPathElement::Field(key.clone()));
+            match data.get(key.as_str()) {
+                Some(value) => {
+                    find_targets_in(value, rest, &mut current, &mut targets, merge_errors)
+                }
+                None => invalid_target(&current, merge_errors),
+            }
+        }
+        Some((ResponseDataPathElement::ListIndex(_), _)) => {
+            current.push(path[0].clone());
+            invalid_target(&current, merge_errors)
+        }
+    }
+    targets
+}
+
+fn find_targets_in(
+    value: &JsonValue,
+    path: &[ResponseDataPathElement],
+    current: &mut Vec<ResponseDataPathElement>,
+    targets: &mut Targets,
+    merge_errors: &mut Vec<ResponseMergeError>,
+) {
+    match (value, path.split_first()) {
+        // Null items of a list are skipped when fanning out
+        (JsonValue::Null, _) if targets.fan_out.is_some() => {}
+        (JsonValue::Object(_), None) => targets.paths.push(current.clone()),
+        (JsonValue::Object(object), Some((ResponseDataPathElement::Field(key), rest))) => {
+            current.push(ResponseDataPathElement::Field(key.clone()));
+            match object.get(key.as_str()) {
+                Some(value) => find_targets_in(value, rest, current, targets, merge_errors),
+                None => invalid_target(current, merge_errors),
+            }
+            current.pop();
+        }
+        (JsonValue::Array(items), Some((ResponseDataPathElement::ListIndex(index), rest))) => {
+            current.push(ResponseDataPathElement::ListIndex(*index));
+            match items.get(*index) {
+                Some(item) => find_targets_in(item, rest, current, targets, merge_errors),
+                None => invalid_target(current, merge_errors),
+            }
+            current.pop();
+        }
+        (JsonValue::Array(items), None | Some((ResponseDataPathElement::Field(_), _))) => {
+            if targets.fan_out.is_none() {
+                targets.fan_out = Some(current.clone())
+            }
+            for (index, item) in items.iter().enumerate() {
+                current.push(ResponseDataPathElement::ListIndex(index));
+                find_targets_in(item, path, current, targets, merge_errors);
+                current.pop();
+            }
+        }
+        _ => invalid_target(current, merge_errors),
+    }
+}
+
+fn invalid_target(path: &[ResponseDataPathElement], merge_errors: &mut Vec<ResponseMergeError>) {
+    merge_errors.push(ResponseMergeError {
+        kind: ResponseMergeErrorKind::InvalidTarget,
+        path: path.to_vec(),
+    })
+}
+
+/// Follows a concrete path found by [`find_targets`]
+fn object_at_path_mut<'a>(
+    data: &'a mut JsonMap,
+    path: &[ResponseDataPathElement],
+) -> Option<&'a mut JsonMap> {
+    let mut object = data;
+    let mut rest = path;
+    while let Some((ResponseDataPathElement::Field(key), after_key)) = rest.split_first() {
+        let mut value = object.get_mut(key.as_str())?;
+        rest = after_key;
+        while let Some((ResponseDataPathElement::ListIndex(index), after_index)) =
+            rest.split_first()
+        {
+            value = value.as_array_mut()?.get_mut(*index)?;
+            rest = after_index;
+        }
+        object = value.as_object_mut()?;
+    }
+    rest.is_empty().then_some(object)
+}
+
+fn deep_merge(
+    target: &mut JsonMap,
+    incoming: JsonMap,
+    path: &mut Vec<ResponseDataPathElement>,
+    merge_errors: &mut Vec<ResponseMergeError>,
+) {
+    for (key, incoming_value) in incoming {
+        let Some(existing) = target.get_mut(key.as_str()) else {
+            target.insert(key, incoming_value);
+            continue;
+        };
+        path.push(ResponseDataPathElement::Field(Name::new_unchecked(
+            key.as_str(),
+        )));
+        deep_merge_value(existing, incoming_value, path, merge_errors);
+        path.pop();
+    }
+}
+
+fn deep_merge_value(
+    existing: &mut JsonValue,
+    incoming: JsonValue,
+    path: &mut Vec<ResponseDataPathElement>,
+    merge_errors: &mut Vec<ResponseMergeError>,
+) {
+    match (existing, incoming) {
+        (JsonValue::Object(existing), JsonValue::Object(incoming)) => {
+            deep_merge(existing, incoming, path, merge_errors)
+        }
+        (JsonValue::Array(existing), JsonValue::Array(incoming))
+            if existing.len() == incoming.len() =>
+        {
+            for (index, (existing, incoming)) in existing.iter_mut().zip(incoming).enumerate() {
+                path.push(ResponseDataPathElement::ListIndex(index));
+                deep_merge_value(existing, incoming, path, merge_errors);
+                path.pop();
+            }
+        }
+        (existing, incoming) => {
+            if *existing != incoming {
+                merge_errors.push(ResponseMergeError {
+                    kind: ResponseMergeErrorKind::ConflictingValues {
+                        existing: existing.clone(),
+                        incoming,
+                    },
+                    path: path.clone(),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Field;
+use crate::executable::Operation;
+use crate::executable::Selection;
+use crate::execution::engine::collect_fields;
+use crate::execution::engine::path_to_vec;
+use crate::execution::engine::LinkedPath;
+use crate::execution::engine::LinkedPathElement;
+use crate::execution::result_coercion::coerce_leaf_value;
+use crate::execution::result_coercion::LeafValueError;
+use crate::execution::Executor;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::execution::ResponseData;
+use crate::execution::ResponseDataPathElement;
+use crate::name;
+use crate::parser::SourceSpan;
+use crate::schema::ExtendedType;
+use crate::schema::ObjectType;
+use crate::schema::Type;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
+use crate::Schema;
+use std::fmt;
+
+/// A place where response data does not match the shape expected by an operation,
+/// as returned by [`validate_response_data`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseShapeError {
+    /// What is wrong with the value at `path`
+    pub kind: ResponseShapeErrorKind,
+
+    /// The path of the relevant value in response data
+    pub path: Vec<ResponseDataPathElement>,
+
+    /// The location of the relevant field in the executable document,
+    /// or `None` for [`ResponseShapeErrorKind::UnexpectedKey`]
+    pub location: Option<SourceSpan>,
+}
+
+/// The kind of mismatch reported by a [`ResponseShapeError`]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ResponseShapeErrorKind {
+    /// A response key selected by the operation is missing from a response object
+    #[error("missing key")]
+    MissingKey,
+
+    /// A response object has a key that is not selected by the operation
+    #[error("unexpected key")]
+    UnexpectedKey,
+
+    /// A null value where the schema expects a non-null type
+    #[error("null value for non-null type {ty}")]
+    NullInNonNull { ty: Type },
+
+    /// A value that does not match the expected type,
+    /// according to its [result coercion] rules
+    ///
+    /// [result coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Result-Coercion
+    #[error(
+        "expected type {expected}, found {value}{}",
+        message.as_ref().map(|message| format!(": {message}")).unwrap_or_default()
+    )]
+    WrongType {
+        expected: Type,
+        value: JsonValue,
+        message: Option<String>,
+    },
+
+    /// A value that is not one of the values of the expected enum type
+    #[error("invalid value {value} for enum {ty}")]
+    InvalidEnumValue { ty: Name, value: JsonValue },
+}
+
+impl fmt::Display for ResponseShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at path ", self.kind)?;
+        fmt_path(&self.path, f)
+    }
+}
+
+/// Formats a response path as dot-separated keys and indices, such as `a.0.b`
+pub(crate) fn fmt_path(
+    path: &[ResponseDataPathElement],
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    for (index, element) in path.iter().enumerate() {
+        if index > 0 {
+            f.write_str(".")?
+        }
+        match element {
+            ResponseDataPathElement::Field(key) => f.write_str(key)?,
+            ResponseDataPathElement::ListIndex(index) => write!(f, "{index}")?,
+        }
+    }
+    Ok(())
+}
+
+impl std::error::Error for ResponseShapeError {}
+
+/// Checks that response data, typically received from another GraphQL service,
+/// has the shape expected by an operation.
+///
+/// Reports every place where a selected response key is missing or a key is unexpected,
+/// a value does not match the expected type (using the same [result coercion] rules
+/// as execution), an enum value is invalid, or a null value is in a non-null position.
+/// As an exception, a `Float` value may be an integer JSON number
+/// since a serialized response may print it without a fractional part.
+/// Returns an empty vector if the data matches.
+///
+/// Which response keys are expected depends on more than `operation`:
+/// fragment spreads are looked up in `document`,
+/// and `variable_values` are used to evaluate `@skip` and `@include` directives.
+/// For interface and union types, the concrete object type is read from the `__typename` key
+/// if present. Otherwise, the data is checked against each possible object type.
+///
+/// Custom scalars accept any value unless configured with
+/// [`Executor::scalar_coercion`], see [`Executor::validate_response_data`].
+///
+/// [result coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Result-Coercion
+pub fn validate_response_data(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Operation,
+    variable_values: &Valid<JsonMap>,
+    data: &JsonMap,
+) -> Vec<ResponseShapeError> {
+    Executor::default_ref().validate_response_data(
+        schema,
+        document,
+        operation,
+        variable_values,
+        data,
+    )
+}
+
+/// Checks that the data of a response has the shape expected by an operation,
+/// as with [`validate_response_data`].
+///
+/// Returns an empty vector if [`Response::data`] is null or absent.
+pub fn validate_response(
+    schema: &Valid<Schema>,
+    document: &Valid<ExecutableDocument>,
+    operation: &Operation,
+    variable_values: &Valid<JsonMap>,
+    response: &Response,
+) -> Vec<ResponseShapeError> {
+    match &response.data {
+        ResponseData::Object(data) => {
+            validate_response_data(schema, document, operation, variable_values, data)
+        }
+        ResponseData::Null | ResponseData::Absent => Vec::new(),
+    }
+}
+
+impl Executor {
+    /// Like [`validate_response_data`], with the configuration of `self`
+    pub fn validate_response_data(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Operation,
+        variable_values: &Valid<JsonMap>,
+        data: &JsonMap,
+    ) -> Vec<ResponseShapeError> {
+        let mut validation = ResponseValidation {
+            executor: self,
+            schema,
+            document,
+            variable_values,
+            errors: Vec::new(),
+        };
+        if let Some(object_type) = schema.get_object(operation.object_type()) {
+            validation.selection_set(None, object_type, &operation.selection_set.selections, data)
+        }
+        validation.errors
+    }
+}
+
+struct ResponseValidation<'a> {
+    executor: &'a Executor,
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    variable_values: &'a Valid<JsonMap>,
+    errors: Vec<ResponseShapeError>,
+}
+
+impl<'a> ResponseValidation<'a> {
+    fn error(
+        &mut self,
+        kind: ResponseShapeErrorKind,
+        path: LinkedPath<'_>,
+        location: Option<SourceSpan>,
+    ) {
+        self.errors.push(ResponseShapeError {
+            kind,
+            path: path_to_vec(path),
+            location,
+        })
+    }
+
+    fn selection_set(
+        &mut self,
+        path: LinkedPath<'_>,
+        object_type: &ObjectType,
+        selections: impl IntoIterator<Item = &'a Selection>,
+        data: &JsonMap,
+    ) {
+        let mut grouped_field_set = IndexMap::with_hasher(Default::default());
+        collect_fields(
+            self.schema,
+            self.document,
+            self.variable_values,
+            object_type,
+            selections,
+            &mut HashSet::default(),
+            &mut grouped_field_set,
+            None,
+        );
+        for (&response_key, fields) in &grouped_field_set {
+            let field_path = LinkedPathElement {
+                element: ResponseDataPathElement::Field(response_key.clone()),
+                next: path,
+            };
+            let location = fields[0].name.location();
+            let Some(value) = data.get(response_key.as_str()) else {
+                self.error(
+                    ResponseShapeErrorKind::MissingKey,
+                    Some(&field_path),
+                    location,
+                );
+                continue;
+            };
+            let field_name = &fields[0].name;
+            if field_name == "__typename" {
+                if value.as_str() != Some(object_type.name.as_str()) {
+                    self.error(
+                        ResponseShapeErrorKind::WrongType {
+                            expected: Type::Named(name!("String")).non_null(),
+                            value: value.clone(),
+                            message: Some(format!("expected __typename {}", object_type.name)),
+                        },
+                        Some(&field_path),
+                        location,
+                    )
+                }
+                continue;
+            }
+            let Ok(field_def) = self.schema.type_field(&object_type.name, field_name) else {
+                // Undefined fields would make the document invalid
+                continue;
+            };
+            self.value(Some(&field_path), &field_def.ty, fields, value)
+        }
+        for key in data.keys() {
+            let key = Name::new_unchecked(key.as_str());
+            if !grouped_field_set.contains_key(&key) {
+                let key_path = LinkedPathElement {
+                    element: ResponseDataPathElement::Field(key),
+                    next: path,
+                };
+                self.error(ResponseShapeErrorKind::UnexpectedKey, Some(&key_path), None)
+            }
+        }
+    }
+
+    fn value(
+        &mut self,
+        path: LinkedPath<'_>,
+        ty: &Type,
+        fields: &[&'a Node<Field>],
+        value: &JsonValue,
+    ) {
+        let location = fields[0].name.location();
+        let wrong_type = |message| ResponseShapeErrorKind::WrongType {
+            expected: ty.clone(),
+            value: value.clone(),
+            message,
+        };
+        if value.is_null() {
+            if ty.is_non_null() {
+                self.error(
+                    ResponseShapeErrorKind::NullInNonNull { ty: ty.clone() },
+                    path,
+                    location,
+                )
+            }
+            return;
+        }
+        let ty_name = match ty {
+            Type::List(inner_ty) | Type::NonNullList(inner_ty) => {
+                if let JsonValue::Array(items) = value {
+                    for (index, item) in items.iter().enumerate() {
+                        let item_path = LinkedPathElement {
+                            element: ResponseDataPathElement::ListIndex(index),
+                            next: path,
+                        };
+                        self.value(Some(&item_path), inner_ty, fields, item)
+                    }
+                } else {
+                    self.error(wrong_type(None), path, location)
+                }
+                return;
+            }
+            Type::Named(name) | Type::NonNullNamed(name) => name,
+        };
+        let Some(ty_def) = self.schema.types.get(ty_name) else {
+            // Undefined types would make the schema invalid
+            return;
+        };
+        let object = match (ty_def, value) {
+            (
+                ExtendedType::Object(_) | ExtendedType::Interface(_) | ExtendedType::Union(_),
+                JsonValue::Object(object),
+            ) => object,
+            (ExtendedType::Object(_) | ExtendedType::Interface(_) | ExtendedType::Union(_), _)
+            | (ExtendedType::InputObject(_), _) => {
+                return self.error(wrong_type(None), path, location)
+            }
+            (ExtendedType::Scalar(_), JsonValue::Number(_)) if ty_name == "Float" => {
+                // A serialized response may print a whole Float without a fractional part
+                return;
+            }
+            (ExtendedType::Scalar(_) | ExtendedType::Enum(_), _) => {
+                let kind = match coerce_leaf_value(self.executor, ty_name, ty_def, value) {
+                    Ok(_) => return,
+                    Err(LeafValueError::InvalidEnumValue) => {
+                        ResponseShapeErrorKind::InvalidEnumValue {
+                            ty: ty_name.clone(),
+                            value: value.clone(),
+                        }
+                    }
+                    Err(LeafValueError::IntOverflow) => {
+                        wrong_type(Some("overflows Int".to_owned()))
+                    }
+                    Err(LeafValueError::CustomScalar(message)) => wrong_type(Some(message)),
+                    Err(LeafValueError::ExpectedObject | LeafValueError::WrongScalarType) => {
+                        wrong_type(None)
+                    }
+                };
+                return self.error(kind, path, location);
+            }
+        };
+        let selections = fields
+            .iter()
+            .flat_map(|field| &field.selection_set.selections);
+        if let ExtendedType::Object(object_type) = ty_def {
+            return self.selection_set(path, object_type, selections, object);
+        }
+        let concrete_type = object
+            .get("__typename")
+            .and_then(JsonValue::as_str)
+            .filter(|type_name| self.schema.is_subtype(ty_name, type_name))
+            .and_then(|type_name| self.schema.get_object(type_name));
+        if let Some(object_type) = concrete_type {
+            return self.selection_set(path, object_type, selections, object);
+        }
+        // Without a usable `__typename`, accept the data if it matches any possible type.
+        // Otherwise report errors for the closest match.
+        let outer_errors = std::mem::take(&mut self.errors);
+        let mut closest_errors: Option<Vec<ResponseShapeError>> = None;
+        for ty_def in self.schema.types.values() {
+            let ExtendedType::Object(object_type) = ty_def else {
+                continue;
+            };
+            if !self.schema.is_subtype(ty_name, &object_type.name) {
+                continue;
+            }
+            self.selection_set(path, object_type, selections.clone(), object);
+            let errors = std::mem::take(&mut self.errors);
+            if closest_errors
+                .as_ref()
+                .is_none_or(|closest| errors.len() < closest.len())
+            {
+                closest_errors = Some(errors)
+            }
+            if closest_errors.as_ref().is_some_and(Vec::is_empty) {
+                break;
+            }
+        }
+        self.errors = outer_errors;
+        self.errors.extend(closest_errors.unwrap_or_default());
+    }
+}
@@ -1,4 +1,7 @@
 use crate::executable::Field;
+use crate::execution::batch::is_batchable;
+use crate::execution::batch::shorten;
+use crate::execution::batch::BatchedList;
 use crate::execution::engine::execute_selection_set;
 use crate::execution::engine::try_nullify;
 use crate::execution::engine::ExecutionMode;
@@ -7,14 +10,19 @@ use crate::execution::engine::LinkedPathElement;
 use crate::execution::engine::PropagateNull;
 use crate::execution::resolver::ResolvedValue;
 use crate::execution::response::ResponseDataPathElement;
+use crate::execution::Executor;
 use crate::execution::GraphQLError;
 use crate::execution::JsonMap;
 use crate::execution::JsonValue;
+use crate::parser::SourceSpan;
 use crate::schema::ExtendedType;
+use crate::schema::ObjectType;
 use crate::schema::Type;
 use crate::validation::SuspectedValidationBug;
 use crate::validation::Valid;
 use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
 use crate::Schema;
 
 /// <https://spec.graphql.org/October2021/#CompleteValue()>
@@ -22,15 +30,15 @@ use crate::Schema;
 /// Returns `Err` for a field error being propagated upwards to find a nullable place
 #[allow(clippy::too_many_arguments)] // yes it’s not a nice API but it’s internal
 pub(crate) fn complete_value<'a, 'b>(
+    executor: &Executor,
     schema: &'a Valid<Schema>,
     document: &'a Valid<ExecutableDocument>,
     variable_values: &'a Valid<JsonMap>,
     errors: &'b mut Vec<GraphQLError>,
     path: LinkedPath<'b>,
-    mode: ExecutionMode,
     ty: &'a Type,
     resolved: ResolvedValue<'a>,
-    fields: &'a [&'a Field],
+    fields: &'a [&'a Node<Field>],
 ) -> Result<JsonValue, PropagateNull> {
     let location = fields[0].name.location();
     macro_rules! field_error {
@@ -59,19 +67,38 @@ pub(crate) fn complete_value<'a, 'b>(
                 field_error!("Non-list type {ty} resolved to a list")
             }
             Type::List(inner_ty) | Type::NonNullList(inner_ty) => {
+                let iter = executor.take_list_items(iter);
+                let batched_list;
+                let batch_calls;
+                let iter: Box<dyn Iterator<Item = ResolvedValue<'_>>> =
+                    if is_batchable(executor, schema, inner_ty) {
+                        batched_list = BatchedList::new(iter);
+                        batch_calls = batched_list.plan(
+                            executor,
+                            schema,
+                            document,
+                            variable_values,
+                            inner_ty,
+                            fields,
+                        );
+                        batched_list.items(executor, path, &batch_calls)?
+                    } else {
+                        Box::new(iter.map(shorten))
+                    };
                 let mut completed_list = Vec::with_capacity(iter.size_hint().0);
                 for (index, inner_resolved) in iter.enumerate() {
                     let inner_path = LinkedPathElement {
                         element: ResponseDataPathElement::ListIndex(index),
                         next: path,
                     };
+                    executor.before_list_item(index, Some(&inner_path), location)?;
                     let inner_result = complete_value(
+                        executor,
                         schema,
                         document,
                         variable_values,
                         errors,
                         Some(&inner_path),
-                        mode,
                         inner_ty,
                         inner_resolved,
                         fields,
@@ -93,126 +120,37 @@ pub(crate) fn complete_value<'a, 'b>(
         }
         Type::Named(name) | Type::NonNullNamed(name) => name,
     };
-    let Some(ty_def) = schema.types.get(ty_name) else {
-        errors.push(
-            SuspectedValidationBug {
-                message: format!("Undefined type {ty_name}"),
-                location,
-            }
-            .into_field_error(&document.sources, path),
-        );
-        return Err(PropagateNull);
+    let ty_def = match output_type_def(schema, ty_name, location) {
+        Ok(ty_def) => ty_def,
+        Err(bug) => {
+            errors.push(bug.into_field_error(&document.sources, path));
+            return Err(PropagateNull);
+        }
     };
-    if let ExtendedType::InputObject(_) = ty_def {
-        errors.push(
-            SuspectedValidationBug {
-                message: format!("Field with input object type {ty_name}"),
-                location,
-            }
-            .into_field_error(&document.sources, path),
-        );
-        return Err(PropagateNull);
-    }
     let resolved_obj = match resolved {
         ResolvedValue::List(_) => unreachable!(), // early return above
         ResolvedValue::Leaf(json_value) => {
-            match ty_def {
-                ExtendedType::InputObject(_) => unreachable!(), // early return above
-                ExtendedType::Object(_) | ExtendedType::Interface(_) | ExtendedType::Union(_) => {
-                    field_error!(
-                        "Resolver returned a leaf value \
-                         but expected an object for type {ty_name}"
-                    )
-                }
-                ExtendedType::Enum(enum_def) => {
-                    // https://spec.graphql.org/October2021/#sec-Enums.Result-Coercion
-                    if !json_value
-                        .as_str()
-                        .is_some_and(|str| enum_def.values.contains_key(str))
-                    {
-                        field_error!("Resolver returned {json_value}, expected enum {ty_name}")
-                    }
-                }
-                ExtendedType::Scalar(_) => match ty_name.as_str() {
-                    "Int" => {
-                        // https://spec.graphql.org/October2021/#sec-Int.Result-Coercion
-                        // > GraphQL services may coerce non-integer internal values to integers
-                        // > when reasonable without losing information
-                        //
-                        // We choose not to, to keep with Rust’s strong typing
-                        if let Some(int) = json_value.as_i64() {
-                            if i32::try_from(int).is_err() {
-                                field_error!("Resolver returned {json_value} which overflows Int")
-                            }
-                        } else {
-                            field_error!("Resolver returned {json_value}, expected Int")
-                        }
-                    }
-                    "Float" => {
-                        // https://spec.graphql.org/October2021/#sec-Float.Result-Coercion
-                        if !json_value.is_f64() {
-                            field_error!("Resolver returned {json_value}, expected Float")
-                        }
-                    }
-                    "String" => {
-                        // https://spec.graphql.org/October2021/#sec-String.Result-Coercion
-                        if !json_value.is_string() {
-                            field_error!("Resolver returned {json_value}, expected String")
-                        }
-                    }
-                    "Boolean" => {
-                        // https://spec.graphql.org/October2021/#sec-Boolean.Result-Coercion
-                        if !json_value.is_boolean() {
-                            field_error!("Resolver returned {json_value}, expected Boolean")
-                        }
-                    }
-                    "ID" => {
-                        // https://spec.graphql.org/October2021/#sec-ID.Result-Coercion
-                        if !(json_value.is_string() || json_value.is_i64()) {
-                            field_error!("Resolver returned {json_value}, expected ID")
-                        }
-                    }
-                    _ => {
-                        // Custom scalar: accept any JSON value (including an array or object,
-                        // despite this being a "leaf" as far as GraphQL resolution is concerned)
-                    }
-                },
-            };
-            return Ok(json_value);
+            match complete_leaf_value(executor, ty_name, ty_def, json_value) {
+                Ok(json_value) => return Ok(json_value),
+                Err(message) => field_error!("{message}"),
+            }
         }
         ResolvedValue::Object(resolved_obj) => resolved_obj,
     };
-    let object_type = match ty_def {
-        ExtendedType::InputObject(_) => unreachable!(), // early return above
-        ExtendedType::Enum(_) | ExtendedType::Scalar(_) => {
-            field_error!(
-                "Resolver returned a an object of type {}, expected {ty_name}",
-                resolved_obj.type_name()
-            )
-        }
-        ExtendedType::Interface(_) | ExtendedType::Union(_) => {
-            let object_type_name = resolved_obj.type_name();
-            if let Some(def) = schema.get_object(object_type_name) {
-                def
-            } else {
-                field_error!(
-                    "Resolver returned an object of type {object_type_name} \
-                     not defined in the schema"
-                )
-            }
-        }
-        ExtendedType::Object(def) => {
-            debug_assert_eq!(ty_name, resolved_obj.type_name());
-            def
-        }
+    let object_type = match resolved_object_type(schema, ty_name, ty_def, resolved_obj.type_name())
+    {
+        Ok(object_type) => object_type,
+        Err(message) => field_error!("{message}"),
     };
     execute_selection_set(
+        executor,
         schema,
         document,
         variable_values,
         errors,
         path,
-        mode,
+        // Only the root selection set of a mutation is executed sequentially
+        ExecutionMode::Normal,
         object_type,
         &*resolved_obj,
         fields
@@ -221,3 +159,192 @@ pub(crate) fn complete_value<'a, 'b>(
     )
     .map(JsonValue::Object)
 }
+
+/// Returns the definition of the named type of a field,
+/// or an error if it is undefined or not an output type.
+pub(crate) fn output_type_def<'a>(
+    schema: &'a Schema,
+    ty_name: &Name,
+    location: Option<SourceSpan>,
+) -> Result<&'a ExtendedType, SuspectedValidationBug> {
+    let Some(ty_def) = schema.types.get(ty_name) else {
+        return Err(SuspectedValidationBug {
+            message: format!("Undefined type {ty_name}"),
+            location,
+        });
+    };
+    if let ExtendedType::InputObject(_) = ty_def {
+        return Err(SuspectedValidationBug {
+            message: format!("Field with input object type {ty_name}"),
+            location,
+        });
+    }
+    Ok(ty_def)
+}
+
+/// Checks that a leaf value returned by a resolver is valid for the expected type,
+/// and coerces it if the type is a custom scalar with registered [`ScalarCoercion`] rules.
+///
+/// Returns the message of a field error if it is not valid.
+///
+/// [`ScalarCoercion`]: crate::execution::ScalarCoercion
+pub(crate) fn complete_leaf_value(
+    executor: &Executor,
+    ty_name: &Name,
+    ty_def: &ExtendedType,
+    json_value: JsonValue,
+) -> Result<JsonValue, String> {
+    match coerce_leaf_value(executor, ty_name, ty_def, &json_value) {
+        Ok(coerced) => Ok(coerced.unwrap_or(json_value)),
+        Err(LeafValueError::ExpectedObject) => Err(format!(
+            "Resolver returned a leaf value \
+             but expected an object for type {ty_name}"
+        )),
+        Err(LeafValueError::InvalidEnumValue) => Err(format!(
+            "Resolver returned {json_value}, expected enum {ty_name}"
+        )),
+        Err(LeafValueError::IntOverflow) => Err(format!(
+            "Resolver returned {json_value} which overflows Int"
+        )),
+        Err(LeafValueError::WrongScalarType) => Err(format!(
+            "Resolver returned {json_value}, expected {ty_name}"
+        )),
+        Err(LeafValueError::CustomScalar(message)) => Err(format!(
+            "Resolver returned {json_value}, expected {ty_name}: {message}"
+        )),
+    }
+}
+
+/// Why [`coerce_leaf_value`] rejected a value
+pub(crate) enum LeafValueError {
+    /// The expected type is an object, interface, or union type
+    ExpectedObject,
+    /// Not a value of the expected enum type
+    InvalidEnumValue,
+    /// An integer outside of the range of `Int`
+    IntOverflow,
+    /// Not a value of the expected built-in scalar type
+    WrongScalarType,
+    /// Registered custom scalar rules returned an error with this message
+    CustomScalar(String),
+}
+
+/// [Result coercion] rules for leaf types, shared between execution and response validation
+///
+/// Returns `Ok(Some(_))` with a new value if coercion rules of a custom scalar
+/// replaced the value, or `Ok(None)` if the value is valid as-is.
+///
+/// [Result coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Result-Coercion
+pub(crate) fn coerce_leaf_value(
+    executor: &Executor,
+    ty_name: &Name,
+    ty_def: &ExtendedType,
+    json_value: &JsonValue,
+) -> Result<Option<JsonValue>, LeafValueError> {
+    match ty_def {
+        ExtendedType::InputObject(_) => unreachable!(), // checked in `output_type_def`
+        ExtendedType::Object(_) | ExtendedType::Interface(_) | ExtendedType::Union(_) => {
+            return Err(LeafValueError::ExpectedObject)
+        }
+        ExtendedType::Enum(enum_def) => {
+            // https://spec.graphql.org/October2021/#sec-Enums.Result-Coercion
+            if !json_value
+                .as_str()
+                .is_some_and(|str| enum_def.values.contains_key(str))
+            {
+                return Err(LeafValueError::InvalidEnumValue);
+            }
+        }
+        ExtendedType::Scalar(_) => match ty_name.as_str() {
+            "Int" => {
+                // https://spec.graphql.org/October2021/#sec-Int.Result-Coercion
+                // > GraphQL services may coerce non-integer internal values to integers
+                // > when reasonable without losing information
+                //
+                // We choose not to, to keep with Rust’s strong typing
+                if let Some(int) = json_value.as_i64() {
+                    if i32::try_from(int).is_err() {
+                        return Err(LeafValueError::IntOverflow);
+                    }
+                } else {
+                    return Err(LeafValueError::WrongScalarType);
+                }
+            }
+            "Float" => {
+                // https://spec.graphql.org/October2021/#sec-Float.Result-Coercion
+                if !json_value.is_f64() {
+                    return Err(LeafValueError::WrongScalarType);
+                }
+            }
+            "String" => {
+                // https://spec.graphql.org/October2021/#sec-String.Result-Coercion
+                if !json_value.is_string() {
+                    return Err(LeafValueError::WrongScalarType);
+                }
+            }
+            "Boolean" => {
+                // https://spec.graphql.org/October2021/#sec-Boolean.Result-Coercion
+                if !json_value.is_boolean() {
+                    return Err(LeafValueError::WrongScalarType);
+                }
+            }
+            "ID" => {
+                // https://spec.graphql.org/October2021/#sec-ID.Result-Coercion
+                if !(json_value.is_string() || json_value.is_i64()) {
+                    return Err(LeafValueError::WrongScalarType);
+                }
+            }
+            _ => {
+                // Custom scalar: without registered coercion rules, accept any JSON value
+                // (including an array or object,
+                // despite this being a "leaf" as far as GraphQL resolution is concerned)
+                if let Some(custom_scalar) = executor.scalar_coercion.for_type(ty_def) {
+                    return custom_scalar
+                        .coerce_result(json_value)
+                        .map(Some)
+                        .map_err(LeafValueError::CustomScalar);
+                }
+            }
+        },
+    }
+    Ok(None)
+}
+
+/// Returns the concrete object type for an object value returned by a resolver,
+/// or the message of a field error.
+pub(crate) fn resolved_object_type<'a>(
+    schema: &'a Schema,
+    ty_name: &Name,
+    ty_def: &'a ExtendedType,
+    resolved_type_name: &str,
+) -> Result<&'a ObjectType, String> {
+    match ty_def {
+        ExtendedType::InputObject(_) => unreachable!(), // checked in `output_type_def`
+        ExtendedType::Enum(_) | ExtendedType::Scalar(_) => Err(format!(
+            "Resolver returned an object of type {resolved_type_name}, expected {ty_name}"
+        )),
+        ExtendedType::Interface(_) | ExtendedType::Union(_) => {
+            let def = schema.get_object(resolved_type_name).ok_or_else(|| {
+                format!(
+                    "Resolver returned an object of type {resolved_type_name} \
+                     not defined in the schema"
+                )
+            })?;
+            if !schema.is_subtype(ty_name, resolved_type_name) {
+                return Err(format!(
+                    "Resolver returned an object of type {resolved_type_name}, \
+                     which is not a possible type of {ty_name}"
+                ));
+            }
+            Ok(def)
+        }
+        ExtendedType::Object(def) => {
+            if ty_name != resolved_type_name {
+                return Err(format!(
+                    "Resolver returned an object of type {resolved_type_name}, expected {ty_name}"
+                ));
+            }
+            Ok(def)
+        }
+    }
+}
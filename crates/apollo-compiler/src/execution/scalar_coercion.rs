@@ -0,0 +1,123 @@
+use crate::ast::Value;
+use crate::collections::HashMap;
+use crate::execution::input_coercion::graphql_value_to_json;
+use crate::execution::InputCoercionError;
+use crate::execution::JsonValue;
+use crate::schema::ExtendedType;
+use crate::Name;
+use crate::Node;
+use std::sync::Arc;
+
+/// Coercion rules for a [custom scalar] type, registered in a [`ScalarCoercion`].
+///
+/// Each method returns either the coerced value, or the message of an error.
+/// Input coercion errors become [request errors] for variables,
+/// and [field errors] for literal arguments. Result coercion errors become field errors.
+///
+/// [custom scalar]: https://spec.graphql.org/October2021/#sec-Scalars.Custom-Scalars
+/// [request errors]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+/// [field errors]: https://spec.graphql.org/October2021/#sec-Errors.Field-errors
+pub trait CustomScalar: Send + Sync {
+    /// [Input coercion] of a value provided for a variable in a GraphQL request,
+    /// or for a field of an input object variable.
+    ///
+    /// [Input coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Input-Coercion
+    fn coerce_variable_value(&self, value: &JsonValue) -> Result<JsonValue, String>;
+
+    /// [Input coercion] of a literal value in an executable document, such as a field argument.
+    ///
+    /// The default implementation converts the literal to JSON
+    /// (with enum values as strings, and numbers parsed by `serde_json`)
+    /// and calls [`coerce_variable_value`][Self::coerce_variable_value].
+    /// Implement this method to access the exact source text of an `Int` or `Float` literal,
+    /// for example for integers larger than 64 bits.
+    ///
+    /// [Input coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Input-Coercion
+    fn coerce_literal(&self, value: &Node<Value>) -> Result<JsonValue, String> {
+        let value =
+            graphql_value_to_json("literal", "", "", "", value).map_err(|err| match err {
+                InputCoercionError::SuspectedValidationBug(bug) => bug.message,
+                InputCoercionError::ValueError { message, .. } => message,
+            })?;
+        self.coerce_variable_value(&value)
+    }
+
+    /// [Result coercion] of a leaf value returned by a resolver.
+    ///
+    /// [Result coercion]: https://spec.graphql.org/October2021/#sec-Scalars.Result-Coercion
+    fn coerce_result(&self, value: &JsonValue) -> Result<JsonValue, String>;
+}
+
+/// A registry of [`CustomScalar`] coercion rules, keyed by scalar type name.
+///
+/// Custom scalars without an entry in the registry accept any JSON value,
+/// both as input and as a result.
+/// Built-in scalars always use the coercion rules of the GraphQL specification
+/// and cannot be registered.
+///
+/// Use with [`Executor::scalar_coercion`][crate::execution::Executor::scalar_coercion].
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::CustomScalar;
+/// use apollo_compiler::execution::JsonValue;
+/// use apollo_compiler::execution::ScalarCoercion;
+/// use apollo_compiler::name;
+///
+/// struct Uuid;
+///
+/// impl CustomScalar for Uuid {
+///     fn coerce_variable_value(&self, value: &JsonValue) -> Result<JsonValue, String> {
+///         match value.as_str() {
+///             Some(str) if str.len() == 36 => Ok(str.to_ascii_lowercase().into()),
+///             _ => Err(format!("expected a UUID string, found {value}")),
+///         }
+///     }
+///
+///     fn coerce_result(&self, value: &JsonValue) -> Result<JsonValue, String> {
+///         self.coerce_variable_value(value)
+///     }
+/// }
+///
+/// let mut scalars = ScalarCoercion::new();
+/// scalars.insert(name!("UUID"), Uuid);
+/// assert!(scalars.get("UUID").is_some());
+/// ```
+#[derive(Clone, Default)]
+pub struct ScalarCoercion {
+    scalars: HashMap<Name, Arc<dyn CustomScalar>>,
+}
+
+impl ScalarCoercion {
+    /// Returns an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register coercion rules for the custom scalar with the given name,
+    /// replacing any previous entry for the same name.
+    pub fn insert(&mut self, name: Name, scalar: impl CustomScalar + 'static) {
+        self.scalars.insert(name, Arc::new(scalar));
+    }
+
+    /// Returns the coercion rules registered for the given scalar name, if any
+    pub fn get(&self, name: &str) -> Option<&dyn CustomScalar> {
+        self.scalars.get(name).map(|scalar| &**scalar)
+    }
+
+    /// Returns the coercion rules for a custom scalar type, if any.
+    /// Always returns `None` for built-in scalars and other kinds of types.
+    pub(crate) fn for_type(&self, ty_def: &ExtendedType) -> Option<&dyn CustomScalar> {
+        match ty_def {
+            ExtendedType::Scalar(def) if !def.is_built_in() => self.get(&def.name),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Debug for ScalarCoercion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.scalars.keys()).finish()
+    }
+}
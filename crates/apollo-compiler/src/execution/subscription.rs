@@ -0,0 +1,264 @@
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Operation;
+use crate::executable::OperationType;
+use crate::execution::engine::collect_fields;
+use crate::execution::engine::ExecutionMode;
+use crate::execution::execute::execute_root_selection_set_async;
+use crate::execution::execute::prepare_operation;
+use crate::execution::input_coercion::coerce_argument_values;
+use crate::execution::resolver::SourceEventStream;
+use crate::execution::resolver::SubscriptionResolver;
+use crate::execution::Executor;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::Response;
+use crate::execution::ResponseData;
+use crate::validation::SuspectedValidationBug;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Node;
+use crate::Schema;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::sync::Arc;
+
+/// Execute a subscription operation, returning a stream of responses.
+///
+/// This is [Subscribe()](https://spec.graphql.org/October2021/#Subscribe())
+/// together with the request preparation steps of
+/// [ExecuteRequest()](https://spec.graphql.org/October2021/#ExecuteRequest()):
+///
+/// * The operation is selected from `document` with
+///   [`OperationMap::get`][crate::executable::OperationMap::get]
+///   and must be a subscription operation.
+/// * `variables` are coerced with [`coerce_variable_values`][crate::execution::coerce_variable_values]
+/// * [`create_source_event_stream`] resolves the single root field to a stream of events
+/// * [`map_source_to_response_event`] executes the operation for each event
+///
+/// Failure in any of the first three steps is returned as `Err`
+/// with a response containing a [request error].
+///
+/// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+pub async fn subscribe<'a>(
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    operation_name: Option<&str>,
+    variables: &JsonMap,
+    root_value: &'a dyn SubscriptionResolver,
+) -> Result<BoxStream<'a, Response>, Response> {
+    Executor::default_ref()
+        .subscribe(schema, document, operation_name, variables, root_value)
+        .await
+}
+
+/// Resolve the single root field of a subscription operation to a stream of source events.
+///
+/// Returns a response with [request errors] if the root field cannot be determined,
+/// if its arguments cannot be coerced, or if the resolver returns an error.
+///
+/// This is [CreateSourceEventStream()](https://spec.graphql.org/October2021/#CreateSourceEventStream())
+/// in the GraphQL specification.
+///
+/// [request errors]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+pub async fn create_source_event_stream<'a>(
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    operation: &'a Node<Operation>,
+    variable_values: &Valid<JsonMap>,
+    root_value: &'a dyn SubscriptionResolver,
+) -> Result<SourceEventStream<'a>, Response> {
+    Executor::default_ref()
+        .create_source_event_stream(schema, document, operation, variable_values, root_value)
+        .await
+}
+
+/// Execute the selection set of a subscription operation for each event of a source stream.
+///
+/// Each `Ok` event is used as the initial value for executing the selection set,
+/// in [normal] execution mode.
+/// [`ExecutionLimits`][crate::execution::ExecutionLimits] apply to the execution for each event
+/// separately, but not to the resolution of the source stream.
+/// Each `Err` event is turned into a response with a [request error].
+///
+/// This is [MapSourceToResponseEvent()](https://spec.graphql.org/October2021/#MapSourceToResponseEvent())
+/// in the GraphQL specification.
+///
+/// [normal]: https://spec.graphql.org/October2021/#sec-Normal-and-Serial-Execution
+/// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+pub fn map_source_to_response_event<'a>(
+    schema: &'a Valid<Schema>,
+    document: &'a Valid<ExecutableDocument>,
+    operation: &'a Operation,
+    variable_values: Valid<JsonMap>,
+    source_stream: SourceEventStream<'a>,
+) -> BoxStream<'a, Response> {
+    Executor::default_ref().map_source_to_response_event(
+        schema,
+        document,
+        operation,
+        variable_values,
+        source_stream,
+    )
+}
+
+impl Executor {
+    /// Like [`subscribe`], with the configuration of `self`
+    pub async fn subscribe<'a>(
+        &'a self,
+        schema: &'a Valid<Schema>,
+        document: &'a Valid<ExecutableDocument>,
+        operation_name: Option<&str>,
+        variables: &JsonMap,
+        root_value: &'a dyn SubscriptionResolver,
+    ) -> Result<BoxStream<'a, Response>, Response> {
+        let (operation, variable_values) =
+            prepare_operation(self, schema, document, operation_name, variables)?;
+        if operation.operation_type != OperationType::Subscription {
+            return Err(Response::from_request_error(GraphQLError::new(
+                format!(
+                    "subscribe called with a {}",
+                    operation.operation_type.name()
+                ),
+                operation.location(),
+                &document.sources,
+            )));
+        }
+        let source_stream = self
+            .create_source_event_stream(schema, document, operation, &variable_values, root_value)
+            .await?;
+        Ok(self.map_source_to_response_event(
+            schema,
+            document,
+            operation,
+            variable_values,
+            source_stream,
+        ))
+    }
+
+    /// Like [`create_source_event_stream`], with the configuration of `self`
+    pub async fn create_source_event_stream<'a>(
+        &'a self,
+        schema: &'a Valid<Schema>,
+        document: &'a Valid<ExecutableDocument>,
+        operation: &'a Node<Operation>,
+        variable_values: &Valid<JsonMap>,
+        root_value: &'a dyn SubscriptionResolver,
+    ) -> Result<SourceEventStream<'a>, Response> {
+        let request_error = |message: String, location| {
+            Response::from_request_error(GraphQLError::new(message, location, &document.sources))
+        };
+        let object_type_name = operation.object_type();
+        let Some(object_type_def) = schema.get_object(object_type_name) else {
+            return Err(request_error(
+                "Undefined root operation type".to_owned(),
+                object_type_name.location(),
+            ));
+        };
+        let mut grouped_field_set = IndexMap::with_hasher(Default::default());
+        collect_fields(
+            schema,
+            document,
+            variable_values,
+            object_type_def,
+            &operation.selection_set.selections,
+            &mut HashSet::default(),
+            &mut grouped_field_set,
+            None,
+        );
+        // Validation ensures a single root field,
+        // but `@skip` or `@include` could still make it go away
+        if grouped_field_set.len() != 1 {
+            return Err(request_error(
+                format!(
+                    "subscription must select exactly one root field, found {}",
+                    grouped_field_set.len()
+                ),
+                operation.location(),
+            ));
+        }
+        let (_response_key, fields) = grouped_field_set.first().unwrap();
+        // Indexing should not panic: `collect_fields` only creates a `Vec` to push to it
+        let field = fields[0];
+        let Ok(field_def) = schema.type_field(&object_type_def.name, &field.name) else {
+            return Err(SuspectedValidationBug {
+                message: format!("Undefined field {}.{}", object_type_def.name, field.name),
+                location: field.name.location(),
+            }
+            .into_response(&document.sources));
+        };
+        let mut errors = Vec::new();
+        let path = None;
+        let Ok(argument_values) = coerce_argument_values(
+            self,
+            schema,
+            document,
+            variable_values,
+            &mut errors,
+            path,
+            field_def,
+            field,
+        ) else {
+            return Err(Response {
+                errors,
+                data: ResponseData::Absent,
+                extensions: Default::default(),
+            });
+        };
+        root_value
+            .resolve_field_event_stream(&field.name, argument_values)
+            .await
+            .map_err(|err| {
+                request_error(
+                    format!("resolver error: {}", err.message),
+                    field.name.location(),
+                )
+            })
+    }
+
+    /// Like [`map_source_to_response_event`], with the configuration of `self`
+    pub fn map_source_to_response_event<'a>(
+        &'a self,
+        schema: &'a Valid<Schema>,
+        document: &'a Valid<ExecutableDocument>,
+        operation: &'a Operation,
+        variable_values: Valid<JsonMap>,
+        source_stream: SourceEventStream<'a>,
+    ) -> BoxStream<'a, Response> {
+        let variable_values = Arc::new(variable_values);
+        source_stream
+            .then(move |event| {
+                let variable_values = Arc::clone(&variable_values);
+                async move {
+                    match event {
+                        // https://spec.graphql.org/October2021/#ExecuteSubscriptionEvent()
+                        Ok(event) => {
+                            // Limits apply to each event separately
+                            let limited = self.with_limit_state();
+                            let executor = limited.as_ref().unwrap_or(self);
+                            let response = execute_root_selection_set_async(
+                                executor,
+                                schema,
+                                document,
+                                operation,
+                                &variable_values,
+                                ExecutionMode::Normal,
+                                &*event,
+                            )
+                            .await;
+                            match &executor.limit_state {
+                                Some(state) => state.finish(response, &document.sources),
+                                None => response,
+                            }
+                        }
+                        Err(err) => Response::from_request_error(GraphQLError::new(
+                            format!("source stream error: {}", err.message),
+                            None,
+                            &document.sources,
+                        )),
+                    }
+                }
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::collections::HashMap;
+use crate::execution::GraphQLError;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::execution::ResponseDataPathElement;
+use crate::Name;
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// The key of [`Response::extensions`] where an [`ExecutionTrace`] is serialized
+/// when [tracing is enabled][crate::execution::Executor::tracing]
+pub const TRACE_EXTENSION_KEY: &str = "trace";
+
+/// Timing information for the execution of a request,
+/// as a tree of fields mirroring the shape of response data.
+///
+/// When [enabled][crate::execution::Executor::tracing], this is serialized as JSON in [`Response::extensions`]
+/// under the [`TRACE_EXTENSION_KEY`] key, and can be deserialized from there.
+/// The structure is similar to that of [Apollo usage reporting traces] (FTV1),
+/// and each field can be converted to an [OpenTelemetry] span.
+///
+/// [Apollo usage reporting traces]: https://www.apollographql.com/docs/graphos/metrics/sending-operation-metrics
+/// [OpenTelemetry]: https://opentelemetry.io/docs/concepts/signals/traces/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTrace {
+    /// Wall-clock time when execution started, in nanoseconds since the Unix epoch
+    pub start_time_unix_nano: u64,
+
+    /// Duration of execution, in nanoseconds
+    pub duration_nano: u64,
+
+    /// Traces of root fields, in the order they started
+    pub fields: Vec<FieldTrace>,
+}
+
+/// Timing information for the execution of one field in an [`ExecutionTrace`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldTrace {
+    /// The path of the field in response data
+    pub path: Vec<ResponseDataPathElement>,
+
+    /// The object type that the field belongs to
+    pub parent_type: Name,
+
+    /// The name of the field in the schema, which may differ from its response key
+    pub field_name: Name,
+
+    /// The type of the field, such as `[User!]!`
+    pub return_type: String,
+
+    /// When resolution of this field started,
+    /// in nanoseconds since [`ExecutionTrace::start_time_unix_nano`]
+    pub start_offset_nano: u64,
+
+    /// When completion of this field ended including its nested fields,
+    /// in nanoseconds since [`ExecutionTrace::start_time_unix_nano`]
+    pub end_offset_nano: u64,
+
+    /// Field errors raised for this field or for its list items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+
+    /// Traces of nested fields, including fields of list items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub children: Vec<FieldTrace>,
+}
+
+/// Field traces recorded so far for one request
+#[derive(Debug)]
+pub(crate) struct TraceRecorder {
+    start: Instant,
+    start_time: SystemTime,
+    /// Flat list in the order fields ended, assembled into a tree by [`Self::trace`]
+    fields: Mutex<Vec<FieldTrace>>,
+}
+
+impl TraceRecorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_time: SystemTime::now(),
+            fields: Default::default(),
+        }
+    }
+
+    /// Adds the recorded trace to a response
+    pub(crate) fn finish(&self, mut response: Response) -> Response {
+        if let Ok(trace) = serde_json_bytes::to_value(self.trace()) {
+            response.extensions.insert(TRACE_EXTENSION_KEY, trace);
+        }
+        response
+    }
+
+    /// Nanoseconds since the start of the request
+    pub(crate) fn offset(&self) -> u64 {
+        self.start
+            .elapsed()
+            .as_nanos()
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    pub(crate) fn record(&self, field: FieldTrace) {
+        self.fields.lock().unwrap().push(field)
+    }
+
+    fn trace(&self) -> ExecutionTrace {
+        let duration_nano = self.offset();
+        let mut fields = std::mem::take(&mut *self.fields.lock().unwrap());
+        // A parent field starts before its nested fields, or at the same time with a shorter path
+        fields.sort_by_key(|field| (field.start_offset_nano, field.path.len()));
+        let mut children = HashMap::<Vec<ResponseDataPathElement>, Vec<FieldTrace>>::default();
+        let mut roots = Vec::new();
+        for field in fields.into_iter().rev() {
+            let mut field = field;
+            if let Some(mut nested) = children.remove(&field.path) {
+                nested.reverse();
+                field.children = nested;
+            }
+            match parent_field_path(&field.path) {
+                Some(parent) => children.entry(parent.to_vec()).or_default().push(field),
+                None => roots.push(field),
+            }
+        }
+        roots.reverse();
+        ExecutionTrace {
+            start_time_unix_nano: self
+                .start_time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |duration| {
+                    duration.as_nanos().try_into().unwrap_or(u64::MAX)
+                }),
+            duration_nano,
+            fields: roots,
+        }
+    }
+}
+
+/// Removes the response key of a field and any list indices before it.
+/// Returns `None` for a root field.
+fn parent_field_path(path: &[ResponseDataPathElement]) -> Option<&[ResponseDataPathElement]> {
+    let (_key, mut parent) = path.split_last()?;
+    while let Some((ResponseDataPathElement::ListIndex(_), rest)) = parent.split_last() {
+        parent = rest
+    }
+    (!parent.is_empty()).then_some(parent)
+}
+
+impl ExecutionTrace {
+    /// Deserializes a trace from the extensions of a response, if present
+    pub fn from_response(response: &Response) -> Option<Self> {
+        let trace: &JsonValue = response.extensions.get(TRACE_EXTENSION_KEY)?;
+        serde_json_bytes::from_value(trace.clone()).ok()
+    }
+}
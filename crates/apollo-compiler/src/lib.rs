@@ -4,6 +4,7 @@
 mod macros;
 pub mod ast;
 pub mod collections;
+mod content_hash;
 pub mod coordinate;
 pub mod diagnostic;
 pub mod executable;
@@ -0,0 +1,158 @@
+use super::*;
+use crate::content_hash::normalize_arguments;
+use crate::content_hash::normalize_ast_directives;
+use crate::content_hash::normalize_value;
+use crate::content_hash::sha256_hex;
+
+impl Schema {
+    /// Returns a content hash of the schema, for example to detect schema changes
+    /// or as part of cache keys.
+    ///
+    /// The hash is the lowercase hexadecimal SHA-256 of a canonical serialization
+    /// where type extensions are merged into their definitions,
+    /// and where elements whose order is not significant are sorted by name:
+    /// type and directive definitions, fields, arguments, enum values,
+    /// implemented interfaces, union members, directive locations,
+    /// and input object values.
+    /// The order of directive applications is kept.
+    /// Descriptions are part of the hash.
+    ///
+    /// As a result the hash does not depend on whitespace, comments, definition order,
+    /// or on how definitions are split across files and extensions,
+    /// and it is stable across process runs.
+    /// Changes to the canonical serialization are considered breaking changes of this crate.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use apollo_compiler::Schema;
+    ///
+    /// let schema_1 = Schema::parse_and_validate(r#"
+    ///     type Query { a: Int, b: String }
+    /// "#, "schema.graphql").unwrap();
+    /// let schema_2 = Schema::parse_and_validate(r#"
+    ///     extend type Query { a: Int } # Comment
+    ///     type Query { b: String }
+    /// "#, "schema.graphql").unwrap();
+    /// assert_eq!(schema_1.content_hash(), schema_2.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> String {
+        let mut schema = self.clone();
+        schema.sources = Default::default();
+
+        let definition = schema.schema_definition.make_mut();
+        flatten_directives(&mut definition.directives);
+        for root_operation in [
+            &mut definition.query,
+            &mut definition.mutation,
+            &mut definition.subscription,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            root_operation.origin = ComponentOrigin::Definition
+        }
+
+        schema
+            .directive_definitions
+            .sort_unstable_by(|a, _, b, _| a.as_str().cmp(b.as_str()));
+        for definition in schema.directive_definitions.values_mut() {
+            if definition.is_built_in() {
+                continue;
+            }
+            let definition = definition.make_mut();
+            normalize_argument_definitions(&mut definition.arguments);
+            definition.locations.sort_by(|a, b| a.name().cmp(b.name()));
+        }
+
+        schema
+            .types
+            .sort_unstable_by(|a, _, b, _| a.as_str().cmp(b.as_str()));
+        for ty in schema.types.values_mut() {
+            // The serialization of built-in types only includes their extensions
+            if ty.is_built_in() {
+                continue;
+            }
+            match ty {
+                ExtendedType::Scalar(ty) => flatten_directives(&mut ty.make_mut().directives),
+                ExtendedType::Object(ty) => {
+                    let ty = ty.make_mut();
+                    flatten_directives(&mut ty.directives);
+                    flatten_names(&mut ty.implements_interfaces);
+                    normalize_field_definitions(&mut ty.fields);
+                }
+                ExtendedType::Interface(ty) => {
+                    let ty = ty.make_mut();
+                    flatten_directives(&mut ty.directives);
+                    flatten_names(&mut ty.implements_interfaces);
+                    normalize_field_definitions(&mut ty.fields);
+                }
+                ExtendedType::Union(ty) => {
+                    let ty = ty.make_mut();
+                    flatten_directives(&mut ty.directives);
+                    flatten_names(&mut ty.members);
+                }
+                ExtendedType::Enum(ty) => {
+                    let ty = ty.make_mut();
+                    flatten_directives(&mut ty.directives);
+                    ty.values
+                        .sort_unstable_by(|a, _, b, _| a.as_str().cmp(b.as_str()));
+                    for value in ty.values.values_mut() {
+                        value.origin = ComponentOrigin::Definition;
+                        normalize_ast_directives(&mut value.make_mut().directives)
+                    }
+                }
+                ExtendedType::InputObject(ty) => {
+                    let ty = ty.make_mut();
+                    flatten_directives(&mut ty.directives);
+                    ty.fields
+                        .sort_unstable_by(|a, _, b, _| a.as_str().cmp(b.as_str()));
+                    for field in ty.fields.values_mut() {
+                        field.origin = ComponentOrigin::Definition;
+                        normalize_argument_definition(field.make_mut())
+                    }
+                }
+            }
+        }
+        sha256_hex(schema.serialize().no_indent().to_string().as_bytes())
+    }
+}
+
+fn flatten_directives(directives: &mut DirectiveList) {
+    for directive in directives.iter_mut() {
+        directive.origin = ComponentOrigin::Definition;
+        normalize_arguments(&mut directive.make_mut().arguments)
+    }
+}
+
+fn flatten_names(names: &mut IndexSet<ComponentName>) {
+    *names = std::mem::take(names)
+        .into_iter()
+        .map(|name| name.name.to_component(ComponentOrigin::Definition))
+        .collect();
+    names.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+}
+
+fn normalize_field_definitions(fields: &mut IndexMap<Name, Component<FieldDefinition>>) {
+    fields.sort_unstable_by(|a, _, b, _| a.as_str().cmp(b.as_str()));
+    for field in fields.values_mut() {
+        field.origin = ComponentOrigin::Definition;
+        let field = field.make_mut();
+        normalize_argument_definitions(&mut field.arguments);
+        normalize_ast_directives(&mut field.directives)
+    }
+}
+
+fn normalize_argument_definitions(arguments: &mut [Node<InputValueDefinition>]) {
+    for argument in arguments.iter_mut() {
+        normalize_argument_definition(argument.make_mut())
+    }
+    arguments.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+}
+
+fn normalize_argument_definition(argument: &mut InputValueDefinition) {
+    if let Some(default) = &mut argument.default_value {
+        normalize_value(default.make_mut())
+    }
+    normalize_ast_directives(&mut argument.directives)
+}
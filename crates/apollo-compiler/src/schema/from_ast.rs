@@ -27,8 +27,23 @@ impl Default for SchemaBuilder {
     }
 }
 
+/// Defined in `built_in_types.graphql` but only added by
+/// [`SchemaBuilder::enable_incremental_delivery`]
+const INCREMENTAL_DELIVERY_DIRECTIVES: [&str; 2] = ["defer", "stream"];
+
 impl SchemaBuilder {
     pub(crate) fn built_in() -> &'static Self {
+        static BUILT_IN: std::sync::OnceLock<SchemaBuilder> = std::sync::OnceLock::new();
+        BUILT_IN.get_or_init(|| {
+            let mut builder = Self::built_in_with_incremental_delivery().clone();
+            for name in INCREMENTAL_DELIVERY_DIRECTIVES {
+                builder.schema.directive_definitions.shift_remove(name);
+            }
+            builder
+        })
+    }
+
+    fn built_in_with_incremental_delivery() -> &'static Self {
         static BUILT_IN: std::sync::OnceLock<SchemaBuilder> = std::sync::OnceLock::new();
         BUILT_IN.get_or_init(|| {
             let mut builder = SchemaBuilder {
@@ -68,6 +83,29 @@ impl SchemaBuilder {
         Self::built_in().clone()
     }
 
+    /// Add built-in definitions of the `@defer` and `@stream` directives
+    /// for [incremental delivery], unless the schema already defines them.
+    ///
+    /// Validation of executable documents includes rules specific to these directives.
+    /// See [`execute_incremental_request`][crate::execution::execute_incremental_request]
+    /// for execution.
+    ///
+    /// [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+    pub fn enable_incremental_delivery(mut self) -> Self {
+        let built_in = &Self::built_in_with_incremental_delivery()
+            .schema
+            .directive_definitions;
+        for name in INCREMENTAL_DELIVERY_DIRECTIVES {
+            if let Some((name, def)) = built_in.get_key_value(name) {
+                self.schema
+                    .directive_definitions
+                    .entry(name.clone())
+                    .or_insert_with(|| def.clone());
+            }
+        }
+        self
+    }
+
     /// Configure the builder so that “orphan” schema extensions and type extensions
     /// (without a corresponding definition) are “adopted”:
     /// accepted as if extending an empty definition instead of being rejected as errors.
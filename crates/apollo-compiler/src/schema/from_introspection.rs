@@ -0,0 +1,513 @@
+use super::*;
+use crate::ast::OperationType;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::parser::SourceFile;
+use crate::validation::Details;
+use std::sync::Arc;
+
+impl Schema {
+    /// Builds and validates a schema from the result of a [schema introspection] query,
+    /// such as one fetched from a remote endpoint.
+    ///
+    /// `introspection` is either the response data, an object with a `__schema` key,
+    /// or a whole response with such an object under `data`.
+    /// Built-in scalars, built-in directives, and introspection types present in the result
+    /// are ignored in favor of their built-in definitions.
+    ///
+    /// Missing or malformed fields of the result are reported as diagnostics,
+    /// together with any validation error of the resulting schema.
+    /// Introspection does not expose applications of directives other than
+    /// `@deprecated`, `@specifiedBy`, and `@oneOf`, so they are absent from the schema.
+    ///
+    /// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+    #[allow(clippy::result_large_err)] // same as `parse_and_validate`
+    pub fn from_introspection(introspection: &JsonValue) -> Result<Valid<Self>, WithErrors<Self>> {
+        let mut errors = DiagnosticList::new(Default::default());
+        let mut sources = IndexMap::with_hasher(Default::default());
+        let mut converter = FromIntrospection {
+            errors: &mut errors,
+            sources: &mut sources,
+        };
+        let definitions = converter.document(introspection);
+        let document = ast::Document {
+            sources: Arc::new(sources),
+            definitions,
+        };
+        let (mut schema, mut build_errors) = Schema::builder().add_ast(&document).build_inner();
+        build_errors.merge(errors);
+        validation::validate_schema(&mut build_errors, &mut schema);
+        build_errors.into_valid_result(schema)
+    }
+}
+
+const DIRECTIVE_LOCATIONS: [ast::DirectiveLocation; 19] = [
+    ast::DirectiveLocation::Query,
+    ast::DirectiveLocation::Mutation,
+    ast::DirectiveLocation::Subscription,
+    ast::DirectiveLocation::Field,
+    ast::DirectiveLocation::FragmentDefinition,
+    ast::DirectiveLocation::FragmentSpread,
+    ast::DirectiveLocation::InlineFragment,
+    ast::DirectiveLocation::VariableDefinition,
+    ast::DirectiveLocation::Schema,
+    ast::DirectiveLocation::Scalar,
+    ast::DirectiveLocation::Object,
+    ast::DirectiveLocation::FieldDefinition,
+    ast::DirectiveLocation::ArgumentDefinition,
+    ast::DirectiveLocation::Interface,
+    ast::DirectiveLocation::Union,
+    ast::DirectiveLocation::Enum,
+    ast::DirectiveLocation::EnumValue,
+    ast::DirectiveLocation::InputObject,
+    ast::DirectiveLocation::InputFieldDefinition,
+];
+
+/// Converts an introspection result to AST definitions.
+///
+/// Paths in diagnostics are dot-separated keys and indices, such as `__schema.types.3.name`
+struct FromIntrospection<'a> {
+    errors: &'a mut DiagnosticList,
+    /// Sources of parsed default values
+    sources: &'a mut IndexMap<FileId, Arc<SourceFile>>,
+}
+
+impl FromIntrospection<'_> {
+    fn document(&mut self, introspection: &JsonValue) -> Vec<ast::Definition> {
+        let mut definitions = Vec::new();
+        let Some(object) = self.object(introspection, "") else {
+            return definitions;
+        };
+        let object = match object.get("data").and_then(JsonValue::as_object) {
+            Some(data) => data,
+            None => object,
+        };
+        let Some(schema) = self.required(object, "__schema", "") else {
+            return definitions;
+        };
+        let Some(schema) = self.object(schema, "__schema") else {
+            return definitions;
+        };
+        let path = "__schema";
+
+        let mut root_operations = Vec::new();
+        for (key, operation_type) in [
+            ("queryType", OperationType::Query),
+            ("mutationType", OperationType::Mutation),
+            ("subscriptionType", OperationType::Subscription),
+        ] {
+            let root = match schema.get(key) {
+                Some(JsonValue::Null) | None if operation_type != OperationType::Query => continue,
+                _ => self.required(schema, key, path),
+            };
+            let key_path = format!("{path}.{key}");
+            if let Some(name) = root
+                .and_then(|root| self.object(root, &key_path))
+                .and_then(|root| self.name(root, "name", &key_path))
+            {
+                root_operations.push(Node::new((operation_type, name)))
+            }
+        }
+        definitions.push(ast::Definition::SchemaDefinition(Node::new(
+            ast::SchemaDefinition {
+                description: self.description(schema, path),
+                directives: Default::default(),
+                root_operations,
+            },
+        )));
+
+        let built_in = &SchemaBuilder::built_in().schema;
+        let types_path = format!("{path}.types");
+        for (index, ty) in self.list(schema, "types", path).iter().enumerate() {
+            let type_path = format!("{types_path}.{index}");
+            let Some(ty) = self.object(ty, &type_path) else {
+                continue;
+            };
+            let Some(name) = self.name(ty, "name", &type_path) else {
+                continue;
+            };
+            if built_in.types.contains_key(&name) {
+                continue;
+            }
+            if let Some(definition) = self.type_definition(ty, name, &type_path) {
+                definitions.push(definition)
+            }
+        }
+
+        let directives_path = format!("{path}.directives");
+        for (index, directive) in self.list(schema, "directives", path).iter().enumerate() {
+            let directive_path = format!("{directives_path}.{index}");
+            let Some(directive) = self.object(directive, &directive_path) else {
+                continue;
+            };
+            let Some(name) = self.name(directive, "name", &directive_path) else {
+                continue;
+            };
+            if built_in.directive_definitions.contains_key(&name) {
+                continue;
+            }
+            let locations_path = format!("{directive_path}.locations");
+            let locations = self
+                .list(directive, "locations", &directive_path)
+                .iter()
+                .enumerate()
+                .filter_map(|(index, location)| {
+                    let location_path = format!("{locations_path}.{index}");
+                    let name = location.as_str();
+                    let location = DIRECTIVE_LOCATIONS
+                        .into_iter()
+                        .find(|location| Some(location.name()) == name);
+                    if location.is_none() {
+                        self.error(&location_path, "expected a directive location")
+                    }
+                    location
+                })
+                .collect();
+            definitions.push(ast::Definition::DirectiveDefinition(Node::new(
+                ast::DirectiveDefinition {
+                    description: self.description(directive, &directive_path),
+                    name,
+                    arguments: self.input_values(directive, "args", &directive_path),
+                    repeatable: self.bool(directive, "isRepeatable", &directive_path),
+                    locations,
+                },
+            )))
+        }
+        definitions
+    }
+
+    fn type_definition(&mut self, ty: &JsonMap, name: Name, path: &str) -> Option<ast::Definition> {
+        let description = self.description(ty, path);
+        let definition = match self.string(ty, "kind", path)? {
+            "SCALAR" => {
+                let mut directives = ast::DirectiveList::new();
+                if let Some(url) = self.optional_string(ty, "specifiedByURL", path) {
+                    directives.push(directive(
+                        name!("specifiedBy"),
+                        Some((name!("url"), url.to_owned())),
+                    ))
+                }
+                ast::Definition::ScalarTypeDefinition(Node::new(ast::ScalarTypeDefinition {
+                    description,
+                    name,
+                    directives,
+                }))
+            }
+            "OBJECT" => {
+                ast::Definition::ObjectTypeDefinition(Node::new(ast::ObjectTypeDefinition {
+                    description,
+                    name,
+                    implements_interfaces: self.type_names(ty, "interfaces", path),
+                    directives: Default::default(),
+                    fields: self.fields(ty, path),
+                }))
+            }
+            "INTERFACE" => {
+                ast::Definition::InterfaceTypeDefinition(Node::new(ast::InterfaceTypeDefinition {
+                    description,
+                    name,
+                    implements_interfaces: self.type_names(ty, "interfaces", path),
+                    directives: Default::default(),
+                    fields: self.fields(ty, path),
+                }))
+            }
+            "UNION" => ast::Definition::UnionTypeDefinition(Node::new(ast::UnionTypeDefinition {
+                description,
+                name,
+                directives: Default::default(),
+                members: self.type_names(ty, "possibleTypes", path),
+            })),
+            "ENUM" => {
+                let values_path = format!("{path}.enumValues");
+                let values = self
+                    .list(ty, "enumValues", path)
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, value)| {
+                        let value_path = format!("{values_path}.{index}");
+                        let value = self.object(value, &value_path)?;
+                        Some(Node::new(ast::EnumValueDefinition {
+                            description: self.description(value, &value_path),
+                            value: self.name(value, "name", &value_path)?,
+                            directives: self.deprecated(value, &value_path),
+                        }))
+                    })
+                    .collect();
+                ast::Definition::EnumTypeDefinition(Node::new(ast::EnumTypeDefinition {
+                    description,
+                    name,
+                    directives: Default::default(),
+                    values,
+                }))
+            }
+            "INPUT_OBJECT" => {
+                let mut directives = ast::DirectiveList::new();
+                if self.bool(ty, "isOneOf", path) {
+                    directives.push(directive(name!("oneOf"), None))
+                }
+                ast::Definition::InputObjectTypeDefinition(Node::new(
+                    ast::InputObjectTypeDefinition {
+                        description,
+                        name,
+                        directives,
+                        fields: self.input_values(ty, "inputFields", path),
+                    },
+                ))
+            }
+            kind => {
+                self.error(
+                    &format!("{path}.kind"),
+                    format!("expected a named type kind, found `{kind}`"),
+                );
+                return None;
+            }
+        };
+        Some(definition)
+    }
+
+    fn fields(&mut self, ty: &JsonMap, path: &str) -> Vec<Node<ast::FieldDefinition>> {
+        let fields_path = format!("{path}.fields");
+        self.list(ty, "fields", path)
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                let field_path = format!("{fields_path}.{index}");
+                let field = self.object(field, &field_path)?;
+                Some(Node::new(ast::FieldDefinition {
+                    description: self.description(field, &field_path),
+                    name: self.name(field, "name", &field_path)?,
+                    arguments: self.input_values(field, "args", &field_path),
+                    ty: self.type_reference(field, &field_path)?,
+                    directives: self.deprecated(field, &field_path),
+                }))
+            })
+            .collect()
+    }
+
+    fn input_values(
+        &mut self,
+        parent: &JsonMap,
+        key: &str,
+        path: &str,
+    ) -> Vec<Node<ast::InputValueDefinition>> {
+        let values_path = format!("{path}.{key}");
+        self.list(parent, key, path)
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let value_path = format!("{values_path}.{index}");
+                let value = self.object(value, &value_path)?;
+                let default_value = self
+                    .optional_string(value, "defaultValue", &value_path)
+                    .and_then(|default| {
+                        self.default_value(default, &format!("{value_path}.defaultValue"))
+                    });
+                Some(Node::new(ast::InputValueDefinition {
+                    description: self.description(value, &value_path),
+                    name: self.name(value, "name", &value_path)?,
+                    ty: Node::new(self.type_reference(value, &value_path)?),
+                    default_value,
+                    directives: self.deprecated(value, &value_path),
+                }))
+            })
+            .collect()
+    }
+
+    /// Parses a default value, which is serialized as GraphQL syntax.
+    /// There is no parser entry point for a single value,
+    /// so it is parsed as part of a minimal input object definition.
+    fn default_value(&mut self, default: &str, path: &str) -> Option<Node<ast::Value>> {
+        let source = format!("input DefaultValue {{ value: Int = {default} }}");
+        let document = match Parser::new().parse_ast(source, path) {
+            Ok(document) => document,
+            Err(with_errors) => {
+                self.errors.merge(with_errors.errors);
+                return None;
+            }
+        };
+        let value = match &document.definitions[..] {
+            [ast::Definition::InputObjectTypeDefinition(def)] if def.fields.len() == 1 => {
+                def.fields[0].default_value.clone()
+            }
+            _ => None,
+        };
+        if value.is_none() {
+            self.error(
+                path,
+                format!("expected a single GraphQL value, found `{default}`"),
+            );
+        } else {
+            self.sources
+                .extend(document.sources.iter().map(|(k, v)| (*k, v.clone())));
+        }
+        value
+    }
+
+    fn type_reference(&mut self, parent: &JsonMap, path: &str) -> Option<ast::Type> {
+        let ty = self.required(parent, "type", path)?;
+        self.type_reference_inner(ty, &format!("{path}.type"))
+    }
+
+    fn type_reference_inner(&mut self, ty: &JsonValue, path: &str) -> Option<ast::Type> {
+        let ty = self.object(ty, path)?;
+        let kind = self.string(ty, "kind", path)?;
+        if kind != "NON_NULL" && kind != "LIST" {
+            return Some(ast::Type::Named(self.name(ty, "name", path)?));
+        }
+        let of_type = self.required(ty, "ofType", path)?;
+        let inner = self.type_reference_inner(of_type, &format!("{path}.ofType"))?;
+        if kind == "LIST" {
+            return Some(ast::Type::List(Box::new(inner)));
+        }
+        match inner {
+            ast::Type::Named(name) => Some(ast::Type::NonNullNamed(name)),
+            ast::Type::List(inner) => Some(ast::Type::NonNullList(inner)),
+            ast::Type::NonNullNamed(_) | ast::Type::NonNullList(_) => {
+                self.error(path, "a non-null type cannot wrap another non-null type");
+                None
+            }
+        }
+    }
+
+    /// Names of type references in a list, such as `interfaces` or `possibleTypes`
+    fn type_names(&mut self, ty: &JsonMap, key: &str, path: &str) -> Vec<Name> {
+        let list_path = format!("{path}.{key}");
+        self.list(ty, key, path)
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let item_path = format!("{list_path}.{index}");
+                let item = self.object(item, &item_path)?;
+                self.name(item, "name", &item_path)
+            })
+            .collect()
+    }
+
+    fn deprecated(&mut self, parent: &JsonMap, path: &str) -> ast::DirectiveList {
+        let mut directives = ast::DirectiveList::new();
+        let reason = self.optional_string(parent, "deprecationReason", path);
+        if self.bool(parent, "isDeprecated", path) || reason.is_some() {
+            // Equivalent to the default value of the `reason` argument
+            let reason = reason.filter(|reason| *reason != "No longer supported");
+            directives.push(directive(
+                name!("deprecated"),
+                reason.map(|reason| (name!("reason"), reason.to_owned())),
+            ))
+        }
+        directives
+    }
+
+    fn description(&mut self, parent: &JsonMap, path: &str) -> Option<Node<str>> {
+        self.optional_string(parent, "description", path)
+            .map(Node::new_str)
+    }
+
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(
+            None,
+            Details::InvalidIntrospection {
+                path: path.to_owned(),
+                message: message.into(),
+            },
+        )
+    }
+
+    fn object<'v>(&mut self, value: &'v JsonValue, path: &str) -> Option<&'v JsonMap> {
+        let object = value.as_object();
+        if object.is_none() {
+            self.error(path, "expected an object")
+        }
+        object
+    }
+
+    /// Returns a non-null value
+    fn required<'v>(
+        &mut self,
+        parent: &'v JsonMap,
+        key: &str,
+        path: &str,
+    ) -> Option<&'v JsonValue> {
+        match parent.get(key) {
+            None | Some(JsonValue::Null) => {
+                self.error(path, format!("missing `{key}`"));
+                None
+            }
+            Some(value) => Some(value),
+        }
+    }
+
+    fn string<'v>(&mut self, parent: &'v JsonMap, key: &str, path: &str) -> Option<&'v str> {
+        let value = self.required(parent, key, path)?;
+        let string = value.as_str();
+        if string.is_none() {
+            self.error(path, format!("expected `{key}` to be a string"))
+        }
+        string
+    }
+
+    fn optional_string<'v>(
+        &mut self,
+        parent: &'v JsonMap,
+        key: &str,
+        path: &str,
+    ) -> Option<&'v str> {
+        match parent.get(key) {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(string)) => Some(string.as_str()),
+            Some(_) => {
+                self.error(path, format!("expected `{key}` to be a string or null"));
+                None
+            }
+        }
+    }
+
+    fn name(&mut self, parent: &JsonMap, key: &str, path: &str) -> Option<Name> {
+        let string = self.string(parent, key, path)?;
+        match Name::new(string) {
+            Ok(name) => Some(name),
+            Err(_) => {
+                self.error(path, format!("`{string}` is not a valid GraphQL name"));
+                None
+            }
+        }
+    }
+
+    /// Returns `false` if null or missing
+    fn bool(&mut self, parent: &JsonMap, key: &str, path: &str) -> bool {
+        match parent.get(key) {
+            None | Some(JsonValue::Null) => false,
+            Some(JsonValue::Bool(value)) => *value,
+            Some(_) => {
+                self.error(path, format!("expected `{key}` to be a boolean"));
+                false
+            }
+        }
+    }
+
+    /// Returns an empty slice if null or missing
+    fn list<'v>(&mut self, parent: &'v JsonMap, key: &str, path: &str) -> &'v [JsonValue] {
+        match parent.get(key) {
+            None | Some(JsonValue::Null) => &[],
+            Some(JsonValue::Array(list)) => list,
+            Some(_) => {
+                self.error(path, format!("expected `{key}` to be a list"));
+                &[]
+            }
+        }
+    }
+}
+
+fn directive(name: Name, argument: Option<(Name, String)>) -> Node<ast::Directive> {
+    Node::new(ast::Directive {
+        name,
+        arguments: argument
+            .into_iter()
+            .map(|(name, value)| {
+                Node::new(ast::Argument {
+                    name,
+                    value: Node::new(ast::Value::String(value)),
+                })
+            })
+            .collect(),
+    })
+}
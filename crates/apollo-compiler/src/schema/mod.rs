@@ -18,15 +18,21 @@ use std::path::Path;
 use std::sync::OnceLock;
 
 mod component;
+mod content_hash;
 mod from_ast;
+mod from_introspection;
 mod serialize;
 pub(crate) mod validation;
+mod visibility;
 
 pub use self::component::Component;
 pub use self::component::ComponentName;
 pub use self::component::ComponentOrigin;
 pub use self::component::ExtensionId;
 pub use self::from_ast::SchemaBuilder;
+pub use self::visibility::HiddenByDirective;
+pub use self::visibility::SchemaVisibility;
+pub(crate) use self::visibility::VisibilityCheck;
 pub use crate::ast::Directive;
 pub use crate::ast::DirectiveDefinition;
 pub use crate::ast::DirectiveLocation;
@@ -860,6 +866,14 @@ impl InputObjectType {
             .collect()
     }
 
+    /// Returns whether this is a [OneOf Input Object](https://github.com/graphql/graphql-spec/pull/825),
+    /// marked with the `@oneOf` directive.
+    ///
+    /// Exactly one field of a OneOf Input Object must be given a value, and that value must not be null.
+    pub fn is_one_of(&self) -> bool {
+        self.directives.has("oneOf")
+    }
+
     serialize_method!();
 }
 
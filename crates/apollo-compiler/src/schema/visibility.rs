@@ -0,0 +1,135 @@
+use super::*;
+
+/// Decides which elements of a schema are visible to a client,
+/// for example based on an `@inaccessible` directive or on the roles of the caller.
+///
+/// Hidden elements are absent from [schema introspection] results
+/// when configured with [`Executor::schema_visibility`][crate::execution::Executor::schema_visibility],
+/// and selecting hidden fields is an error with
+/// [`ExecutableDocument::validate_with_visibility`][crate::ExecutableDocument::validate_with_visibility].
+///
+/// All methods have default implementations that return `true`.
+/// In addition to what these methods return, a field, argument, or input field
+/// is hidden if its type is hidden, and a field is hidden if its parent type is hidden.
+///
+/// [schema introspection]: https://spec.graphql.org/October2021/#sec-Schema-Introspection
+pub trait SchemaVisibility: Send + Sync {
+    fn is_type_visible(&self, ty: &ExtendedType) -> bool {
+        let _ = ty;
+        true
+    }
+
+    /// Called for fields of object and interface types
+    fn is_field_visible(&self, parent_type: &ExtendedType, field: &FieldDefinition) -> bool {
+        let _ = (parent_type, field);
+        true
+    }
+
+    /// Called for arguments of fields and directives
+    fn is_argument_visible(&self, argument: &InputValueDefinition) -> bool {
+        let _ = argument;
+        true
+    }
+
+    fn is_input_field_visible(
+        &self,
+        parent_type: &ExtendedType,
+        field: &InputValueDefinition,
+    ) -> bool {
+        let _ = (parent_type, field);
+        true
+    }
+
+    fn is_enum_value_visible(
+        &self,
+        parent_type: &ExtendedType,
+        value: &EnumValueDefinition,
+    ) -> bool {
+        let _ = (parent_type, value);
+        true
+    }
+
+    fn is_directive_visible(&self, directive: &DirectiveDefinition) -> bool {
+        let _ = directive;
+        true
+    }
+}
+
+/// Hides every schema element that has a given directive applied, such as `@inaccessible`
+#[derive(Debug, Clone)]
+pub struct HiddenByDirective {
+    directive_name: Name,
+}
+
+impl HiddenByDirective {
+    pub fn new(directive_name: Name) -> Self {
+        Self { directive_name }
+    }
+}
+
+impl SchemaVisibility for HiddenByDirective {
+    fn is_type_visible(&self, ty: &ExtendedType) -> bool {
+        !ty.directives().has(&self.directive_name)
+    }
+
+    fn is_field_visible(&self, _parent_type: &ExtendedType, field: &FieldDefinition) -> bool {
+        !field.directives.has(&self.directive_name)
+    }
+
+    fn is_argument_visible(&self, argument: &InputValueDefinition) -> bool {
+        !argument.directives.has(&self.directive_name)
+    }
+
+    fn is_input_field_visible(
+        &self,
+        _parent_type: &ExtendedType,
+        field: &InputValueDefinition,
+    ) -> bool {
+        !field.directives.has(&self.directive_name)
+    }
+
+    fn is_enum_value_visible(
+        &self,
+        _parent_type: &ExtendedType,
+        value: &EnumValueDefinition,
+    ) -> bool {
+        !value.directives.has(&self.directive_name)
+    }
+}
+
+/// Applies the implied rules documented on [`SchemaVisibility`]
+#[derive(Clone, Copy)]
+pub(crate) struct VisibilityCheck<'a> {
+    pub(crate) schema: &'a Schema,
+    pub(crate) visibility: &'a dyn SchemaVisibility,
+}
+
+impl VisibilityCheck<'_> {
+    /// Types not defined in the schema are considered visible
+    pub(crate) fn type_name(&self, name: &str) -> bool {
+        self.schema
+            .types
+            .get(name)
+            .is_none_or(|def| self.visibility.is_type_visible(def))
+    }
+
+    pub(crate) fn field(&self, parent_type: &ExtendedType, field: &FieldDefinition) -> bool {
+        self.visibility.is_type_visible(parent_type)
+            && self.visibility.is_field_visible(parent_type, field)
+            && self.type_name(field.ty.inner_named_type())
+    }
+
+    pub(crate) fn argument(&self, argument: &InputValueDefinition) -> bool {
+        self.visibility.is_argument_visible(argument)
+            && self.type_name(argument.ty.inner_named_type())
+    }
+
+    pub(crate) fn input_field(
+        &self,
+        parent_type: &ExtendedType,
+        field: &InputValueDefinition,
+    ) -> bool {
+        self.visibility.is_input_field_visible(parent_type, field)
+            && self.type_name(field.ty.inner_named_type())
+    }
+}
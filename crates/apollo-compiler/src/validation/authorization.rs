@@ -0,0 +1,417 @@
+use crate::ast::Directive;
+use crate::collections::HashMap;
+use crate::collections::HashSet;
+use crate::collections::IndexMap;
+use crate::executable::Field;
+use crate::executable::Fragment;
+use crate::executable::Operation;
+use crate::executable::Selection;
+use crate::executable::SelectionSet;
+use crate::execution::operation_split::try_make_single_operation_document;
+use crate::execution::GraphQLError;
+use crate::execution::ResponseDataPathElement;
+use crate::parser::SourceSpan;
+use crate::schema::ExtendedType;
+use crate::schema::FieldDefinition;
+use crate::schema::Implementers;
+use crate::schema::Name;
+use crate::validation::variable::variables_in_arguments;
+use crate::validation::variable::variables_in_directives;
+use crate::validation::SuspectedValidationBug;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Node;
+use crate::Schema;
+
+/// Removes the parts of an operation that a client is not authorized to access,
+/// based on `@authenticated`, `@requiresScopes`, and `@policy` directives in the schema.
+///
+/// These directives are expected to be defined as in Apollo Federation:
+///
+/// ```graphql
+/// directive @authenticated on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+/// directive @requiresScopes(scopes: [[Scope!]!]!)
+///   on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+/// directive @policy(policies: [[Policy!]!]!)
+///   on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+/// ```
+///
+/// `@requiresScopes` is satisfied if all scopes of at least one inner list are granted.
+/// `@policy` is satisfied if all policies of at least one inner list were evaluated as successful
+/// by the caller, for example with an external service.
+///
+/// A field is unauthorized if its definition, its type, or its parent type
+/// has an unsatisfied directive.
+/// A field of an interface is also unauthorized if the corresponding field of any
+/// implementing type is, since it could be returned in its place.
+/// An inline fragment or fragment spread is unauthorized if its type condition is.
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::validation::AuthorizationFilter;
+/// use apollo_compiler::ExecutableDocument;
+/// use apollo_compiler::Schema;
+///
+/// let schema = Schema::parse_and_validate(r#"
+///     directive @authenticated on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+///     type Query { public: Int, private: Int @authenticated }
+/// "#, "schema.graphql").unwrap();
+/// let document = ExecutableDocument::parse_and_validate(
+///     &schema,
+///     "{ public private }",
+///     "query.graphql",
+/// ).unwrap();
+/// let operation = document.operations.get(None).unwrap();
+///
+/// let filtered = AuthorizationFilter::new().filter(&schema, &document, operation);
+/// assert_eq!(filtered.document.unwrap().to_string(), "{\n  public\n}\n");
+/// assert_eq!(filtered.errors[0].message, "Unauthorized field or type");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AuthorizationFilter {
+    authenticated: bool,
+    scopes: HashSet<String>,
+    policies: HashSet<String>,
+}
+
+/// The result of [`AuthorizationFilter::filter`]
+#[derive(Debug, Clone)]
+pub struct AuthorizedOperation {
+    /// A document with exactly one operation and the fragments it uses,
+    /// or `None` if nothing in the operation is authorized.
+    /// This is also `None` if filtering resulted in an invalid document,
+    /// with a [`SuspectedValidationBug`] error in `errors`.
+    ///
+    /// The operation definition name is preserved,
+    /// so either `None` or the original `Option<&str>` name request can be passed
+    /// to [`OperationMap::get`][crate::executable::OperationMap::get]
+    /// to obtain the one operation.
+    pub document: Option<Valid<ExecutableDocument>>,
+
+    /// One error for each field, inline fragment, or fragment spread that was removed
+    /// because it is unauthorized.
+    ///
+    /// Paths contain the response keys of fields, but no list indices
+    /// since they are not known before execution.
+    pub errors: Vec<GraphQLError>,
+}
+
+impl AuthorizationFilter {
+    /// Returns a filter for an unauthenticated client with no granted scopes or policies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the client is authenticated, satisfying `@authenticated`
+    pub fn authenticated(mut self, authenticated: bool) -> Self {
+        self.authenticated = authenticated;
+        self
+    }
+
+    /// Add scopes granted to the client, for `@requiresScopes`
+    pub fn scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes.extend(scopes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add policies that were evaluated as successful for the client, for `@policy`
+    pub fn policies(mut self, policies: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.policies.extend(policies.into_iter().map(Into::into));
+        self
+    }
+
+    /// Removes unauthorized fields, inline fragments, and fragment spreads from `operation`.
+    ///
+    /// Selection sets that become empty are removed together with their parent field
+    /// or fragment, without an additional error.
+    /// Fragment definitions and variable definitions that are no longer used are removed,
+    /// so that the resulting document is valid.
+    pub fn filter(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Node<Operation>,
+    ) -> AuthorizedOperation {
+        let mut filter = Filter {
+            authorization: self,
+            schema,
+            document,
+            implementers_map: schema.implementers_map(),
+            fragments: Default::default(),
+            errors: Vec::new(),
+        };
+        let mut path = Vec::new();
+        let selection_set = filter.selection_set(&operation.selection_set, &mut path);
+        let mut errors = std::mem::take(&mut filter.errors)
+            .into_iter()
+            .map(|error| error.into_graphql_error(document))
+            .collect::<Vec<_>>();
+        if selection_set.selections.is_empty() {
+            return AuthorizedOperation {
+                document: None,
+                errors,
+            };
+        }
+        // Fragments are only filtered when a spread is reached,
+        // so all non-empty filtered fragments are used.
+        let fragments: IndexMap<_, _> = document
+            .fragments
+            .keys()
+            .filter_map(|name| {
+                let (fragment, _) = filter.fragments.remove(name)?;
+                Some((name.clone(), fragment?))
+            })
+            .collect();
+        let mut variables_used = HashSet::default();
+        variables_used.extend(variables_in_directives(&operation.directives).cloned());
+        collect_variables(&selection_set, &mut variables_used);
+        for fragment in fragments.values() {
+            variables_used.extend(variables_in_directives(&fragment.directives).cloned());
+            collect_variables(&fragment.selection_set, &mut variables_used);
+        }
+        let new_operation = operation.same_location(Operation {
+            operation_type: operation.operation_type,
+            name: operation.name.clone(),
+            variables: operation
+                .variables
+                .iter()
+                .filter(|var| variables_used.contains(&var.name))
+                .cloned()
+                .collect(),
+            directives: operation.directives.clone(),
+            selection_set,
+        });
+        let location = operation.location();
+        match try_make_single_operation_document(schema, document, new_operation, fragments) {
+            Ok(new_document) => AuthorizedOperation {
+                document: Some(new_document),
+                errors,
+            },
+            Err(_) => {
+                let bug = SuspectedValidationBug {
+                    message: "filtering a valid document resulted in an invalid document".into(),
+                    location,
+                };
+                errors.push(bug.into_graphql_error(&document.sources));
+                AuthorizedOperation {
+                    document: None,
+                    errors,
+                }
+            }
+        }
+    }
+
+    fn is_satisfied(&self, directive: &Directive) -> bool {
+        match directive.name.as_str() {
+            "authenticated" => self.authenticated,
+            "requiresScopes" => any_all_granted(directive, "scopes", &self.scopes),
+            "policy" => any_all_granted(directive, "policies", &self.policies),
+            _ => true,
+        }
+    }
+}
+
+/// Whether all items of at least one inner list of a `[[String!]!]!` argument are granted.
+/// A malformed argument is never satisfied.
+fn any_all_granted(directive: &Directive, argument: &str, granted: &HashSet<String>) -> bool {
+    let Some(lists) = directive
+        .specified_argument_by_name(argument)
+        .and_then(|value| value.as_list())
+    else {
+        return false;
+    };
+    lists.iter().any(|list| {
+        list.as_list().is_some_and(|items| {
+            items
+                .iter()
+                .all(|item| item.as_str().is_some_and(|item| granted.contains(item)))
+        })
+    })
+}
+
+/// An unauthorized selection, with a path relative to
+/// the operation or fragment definition where it was found
+struct Unauthorized {
+    path: Vec<ResponseDataPathElement>,
+    location: Option<SourceSpan>,
+}
+
+impl Unauthorized {
+    fn into_graphql_error(self, document: &ExecutableDocument) -> GraphQLError {
+        let mut error = GraphQLError::new(
+            "Unauthorized field or type",
+            self.location,
+            &document.sources,
+        );
+        error.path = self.path;
+        error
+            .extensions
+            .insert("code", "UNAUTHORIZED_FIELD_OR_TYPE".into());
+        error
+    }
+}
+
+struct Filter<'a> {
+    authorization: &'a AuthorizationFilter,
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    implementers_map: HashMap<Name, Implementers>,
+    /// Filtered fragment definitions, `None` if removed entirely,
+    /// with errors relative to the fragment
+    fragments: HashMap<Name, (Option<Node<Fragment>>, Vec<Unauthorized>)>,
+    errors: Vec<Unauthorized>,
+}
+
+impl Filter<'_> {
+    fn is_type_authorized(&self, name: &str) -> bool {
+        self.schema.types.get(name).is_none_or(|ty| {
+            ty.directives()
+                .iter()
+                .all(|directive| self.authorization.is_satisfied(directive))
+        })
+    }
+
+    fn is_field_definition_authorized(&self, parent_type: &str, field: &FieldDefinition) -> bool {
+        self.is_type_authorized(parent_type)
+            && field
+                .directives
+                .iter()
+                .all(|directive| self.authorization.is_satisfied(directive))
+            && self.is_type_authorized(field.ty.inner_named_type())
+    }
+
+    fn is_field_authorized(&self, parent_type: &Name, field: &Field) -> bool {
+        if !self.is_field_definition_authorized(parent_type, &field.definition) {
+            return false;
+        }
+        let Some(ExtendedType::Interface(_)) = self.schema.types.get(parent_type) else {
+            return true;
+        };
+        // Meta-field, the same for every implementing type
+        if field.name == "__typename" {
+            return true;
+        }
+        let Some(implementers) = self.implementers_map.get(parent_type) else {
+            return true;
+        };
+        implementers
+            .objects
+            .iter()
+            .chain(&implementers.interfaces)
+            .filter_map(|ty| Some((ty, self.schema.type_field(ty, &field.name).ok()?)))
+            .all(|(ty, def)| self.is_field_definition_authorized(ty, def))
+    }
+
+    fn unauthorized(&mut self, path: &[ResponseDataPathElement], location: Option<SourceSpan>) {
+        self.errors.push(Unauthorized {
+            path: path.to_vec(),
+            location,
+        })
+    }
+
+    fn selection_set(
+        &mut self,
+        selection_set: &SelectionSet,
+        path: &mut Vec<ResponseDataPathElement>,
+    ) -> SelectionSet {
+        let mut new_selection_set = SelectionSet::new(selection_set.ty.clone());
+        for selection in &selection_set.selections {
+            match selection {
+                Selection::Field(field) => {
+                    path.push(ResponseDataPathElement::Field(field.response_key().clone()));
+                    if !self.is_field_authorized(&selection_set.ty, field) {
+                        self.unauthorized(path, field.location());
+                        path.pop();
+                        continue;
+                    }
+                    let nested = self.selection_set(&field.selection_set, path);
+                    path.pop();
+                    if !field.selection_set.selections.is_empty() && nested.selections.is_empty() {
+                        continue;
+                    }
+                    let mut field = field.clone();
+                    field.make_mut().selection_set = nested;
+                    new_selection_set.push(field)
+                }
+                Selection::InlineFragment(inline) => {
+                    if let Some(type_condition) = &inline.type_condition {
+                        if !self.is_type_authorized(type_condition) {
+                            self.unauthorized(path, inline.location());
+                            continue;
+                        }
+                    }
+                    let nested = self.selection_set(&inline.selection_set, path);
+                    if nested.selections.is_empty() {
+                        continue;
+                    }
+                    let mut inline = inline.clone();
+                    inline.make_mut().selection_set = nested;
+                    new_selection_set.push(inline)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = &spread.fragment_name;
+                    if !self.fragments.contains_key(name) {
+                        let filtered = self.fragment(name);
+                        self.fragments.insert(name.clone(), filtered);
+                    }
+                    let (fragment, errors) = &self.fragments[name];
+                    let fragment_is_kept = fragment.is_some();
+                    let errors = errors
+                        .iter()
+                        .map(|error| Unauthorized {
+                            path: path.iter().chain(&error.path).cloned().collect(),
+                            location: error.location.or(spread.location()),
+                        })
+                        .collect::<Vec<_>>();
+                    self.errors.extend(errors);
+                    if fragment_is_kept {
+                        new_selection_set.push(spread.clone())
+                    }
+                }
+            }
+        }
+        new_selection_set
+    }
+
+    /// Filters a fragment definition, with errors relative to a spread of it
+    fn fragment(&mut self, name: &Name) -> (Option<Node<Fragment>>, Vec<Unauthorized>) {
+        // Fragments of a valid document are defined
+        let fragment = &self.document.fragments[name];
+        let outer_errors = std::mem::take(&mut self.errors);
+        let filtered = if self.is_type_authorized(fragment.type_condition()) {
+            let nested = self.selection_set(&fragment.selection_set, &mut Vec::new());
+            (!nested.selections.is_empty()).then(|| {
+                let mut fragment = fragment.clone();
+                fragment.make_mut().selection_set = nested;
+                fragment
+            })
+        } else {
+            // Reported at the location of each spread
+            self.unauthorized(&[], None);
+            None
+        };
+        let errors = std::mem::replace(&mut self.errors, outer_errors);
+        (filtered, errors)
+    }
+}
+
+/// Collects variables used in `selection_set`, without following fragment spreads
+fn collect_variables(selection_set: &SelectionSet, variables: &mut HashSet<Name>) {
+    for selection in &selection_set.selections {
+        match selection {
+            Selection::Field(field) => {
+                variables.extend(variables_in_arguments(&field.arguments).cloned());
+                variables.extend(variables_in_directives(&field.directives).cloned());
+                collect_variables(&field.selection_set, variables)
+            }
+            Selection::InlineFragment(inline) => {
+                variables.extend(variables_in_directives(&inline.directives).cloned());
+                collect_variables(&inline.selection_set, variables)
+            }
+            Selection::FragmentSpread(spread) => {
+                variables.extend(variables_in_directives(&spread.directives).cloned());
+            }
+        }
+    }
+}
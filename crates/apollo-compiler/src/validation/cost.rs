@@ -0,0 +1,465 @@
+use crate::ast::Directive;
+use crate::ast::OperationType;
+use crate::ast::Value;
+use crate::coordinate::TypeAttributeCoordinate;
+use crate::executable::Field;
+use crate::executable::Operation;
+use crate::executable::Selection;
+use crate::executable::SelectionSet;
+use crate::execution::engine::eval_if_arg;
+use crate::execution::GraphQLError;
+use crate::execution::JsonMap;
+use crate::execution::JsonValue;
+use crate::execution::Response;
+use crate::schema::InputValueDefinition;
+use crate::schema::Name;
+use crate::validation::Valid;
+use crate::ExecutableDocument;
+use crate::Schema;
+
+/// The cost of a mutation operation, in addition to the cost of its fields
+const MUTATION_COST: f64 = 10.0;
+
+/// The default weight of object, interface, and union types
+const COMPOSITE_TYPE_WEIGHT: f64 = 1.0;
+
+/// The default weight of scalar and enum types
+const LEAF_TYPE_WEIGHT: f64 = 0.0;
+
+/// Static cost analysis of operations, based on `@cost` and `@listSize` directives in the schema
+/// as in the [cost directive specification][spec].
+///
+/// These directives are expected to be defined as:
+///
+/// ```graphql
+/// directive @cost(weight: Int!)
+///   on ARGUMENT_DEFINITION | ENUM | FIELD_DEFINITION | INPUT_FIELD_DEFINITION | OBJECT | SCALAR
+/// directive @listSize(
+///   assumedSize: Int
+///   slicingArguments: [String!]
+///   sizedFields: [String!]
+///   requireOneSlicingArgument: Boolean = true
+/// ) on FIELD_DEFINITION
+/// ```
+///
+/// The cost of a field is its weight plus the cost of its selections,
+/// multiplied by its list size if it returns a list, plus the cost of its arguments:
+///
+/// * The weight is given by `@cost` on the field definition, or else on its type.
+///   Without `@cost`, objects, interfaces, and unions weigh 1, and scalars and enums weigh 0.
+/// * The list size is the largest value of the slicing arguments of `@listSize`,
+///   read from the operation, from variables, or from argument default values,
+///   then its `assumedSize`, then [`default_list_size`][Self::default_list_size].
+///   Slicing arguments can be a path in an input object, such as `"page.first"`.
+///   With `sizedFields`, the size applies to the listed subfields instead of the field itself,
+///   for example with cursor-based pagination.
+/// * The cost of an argument or input object field is the weight given by `@cost`
+///   on its definition, if any, for each time it occurs.
+///
+/// Fragments are expanded, and fields excluded by `@skip` or `@include` are not counted.
+/// Mutations cost an additional 10.
+///
+/// [spec]: https://ibm.github.io/graphql-specs/cost-spec.html
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::execution::coerce_variable_values;
+/// use apollo_compiler::execution::JsonMap;
+/// use apollo_compiler::validation::CostCalculator;
+/// use apollo_compiler::ExecutableDocument;
+/// use apollo_compiler::Schema;
+///
+/// let schema = Schema::parse_and_validate(r#"
+///     directive @cost(weight: Int!) on FIELD_DEFINITION | OBJECT
+///     directive @listSize(slicingArguments: [String!]) on FIELD_DEFINITION
+///     type Query { users(first: Int = 5): [User] @listSize(slicingArguments: ["first"]) }
+///     type User { name: String, friends: [User] @cost(weight: 3) }
+/// "#, "schema.graphql").unwrap();
+/// let document = ExecutableDocument::parse_and_validate(
+///     &schema,
+///     "{ users(first: 2) { name friends { name } } }",
+///     "query.graphql",
+/// ).unwrap();
+/// let operation = document.operations.get(None).unwrap();
+/// let variables = coerce_variable_values(&schema, operation, &JsonMap::new()).unwrap();
+///
+/// let calculator = CostCalculator::new().default_list_size(10);
+/// // 2 users × (1 + 10 friends × 3)
+/// assert_eq!(calculator.calculate(&schema, &document, operation, &variables).total, 62.0);
+/// assert!(calculator.check(&schema, &document, operation, &variables, 50.0).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CostCalculator {
+    default_list_size: u64,
+}
+
+/// The result of [`CostCalculator::calculate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationCost {
+    /// The estimated cost of the whole operation
+    pub total: f64,
+
+    /// The estimated cost of each field of the operation, in pre-order.
+    /// Fields selected through fragments are included at each place they are used.
+    pub fields: Vec<FieldCost>,
+}
+
+/// The estimated cost of one field of an operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCost {
+    /// The response keys of this field and its ancestor fields.
+    /// List indices are not included.
+    pub path: Vec<Name>,
+
+    /// The field definition in the schema
+    pub coordinate: TypeAttributeCoordinate,
+
+    /// The cost of this field including its arguments and selections,
+    /// multiplied by its list size.
+    pub cost: f64,
+}
+
+/// An operation rejected by [`CostCalculator::check`]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("estimated cost {estimated_cost} exceeds the maximum cost of {max_cost}")]
+pub struct CostLimitExceeded {
+    /// The estimated cost of the operation, as in [`OperationCost::total`]
+    pub estimated_cost: f64,
+
+    /// The maximum cost that was passed to [`CostCalculator::check`]
+    pub max_cost: f64,
+}
+
+struct Calculation<'a> {
+    default_list_size: f64,
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    variables: &'a Valid<JsonMap>,
+    path: Vec<Name>,
+    fields: Vec<FieldCost>,
+}
+
+impl Default for CostCalculator {
+    fn default() -> Self {
+        Self {
+            default_list_size: 10,
+        }
+    }
+}
+
+impl CostCalculator {
+    /// Returns a calculator with a default list size of 10
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the list size to use for fields that return a list
+    /// when it is not given by `@listSize`. Defaults to 10.
+    pub fn default_list_size(mut self, value: u64) -> Self {
+        self.default_list_size = value;
+        self
+    }
+
+    /// Estimates the cost of `operation` with the given variable values,
+    /// typically from [`coerce_variable_values`][crate::execution::coerce_variable_values].
+    pub fn calculate(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Operation,
+        variables: &Valid<JsonMap>,
+    ) -> OperationCost {
+        let mut calculation = Calculation {
+            default_list_size: self.default_list_size as f64,
+            schema,
+            document,
+            variables,
+            path: Vec::new(),
+            fields: Vec::new(),
+        };
+        let mut total = calculation.selection_set(&operation.selection_set, None);
+        if operation.operation_type == OperationType::Mutation {
+            total += MUTATION_COST
+        }
+        OperationCost {
+            total,
+            fields: calculation.fields,
+        }
+    }
+
+    /// Estimates the cost of `operation`,
+    /// and returns an error if it is greater than `max_cost`.
+    pub fn check(
+        &self,
+        schema: &Valid<Schema>,
+        document: &Valid<ExecutableDocument>,
+        operation: &Operation,
+        variables: &Valid<JsonMap>,
+        max_cost: f64,
+    ) -> Result<OperationCost, CostLimitExceeded> {
+        let cost = self.calculate(schema, document, operation, variables);
+        if cost.total > max_cost {
+            Err(CostLimitExceeded {
+                estimated_cost: cost.total,
+                max_cost,
+            })
+        } else {
+            Ok(cost)
+        }
+    }
+}
+
+impl CostLimitExceeded {
+    /// The error code to use in the `extensions` of a GraphQL response
+    pub fn code(&self) -> &'static str {
+        "COST_ESTIMATED_TOO_EXPENSIVE"
+    }
+
+    /// Convert into a JSON-serializable error as represented in a GraphQL response
+    pub fn into_graphql_error(self) -> GraphQLError {
+        let mut error = GraphQLError::new(self.to_string(), None, &Default::default());
+        error.extensions.insert("code", self.code().into());
+        error
+    }
+
+    /// Convert into a response with this error as a [request error]
+    /// that prevented execution from starting.
+    ///
+    /// [request error]: https://spec.graphql.org/October2021/#sec-Errors.Request-errors
+    pub fn into_response(self) -> Response {
+        Response::from_request_error(self.into_graphql_error())
+    }
+}
+
+impl Calculation<'_> {
+    /// `sized_fields` are names of fields in this selection set
+    /// whose list size was given by `@listSize(sizedFields:)` on the parent field
+    fn selection_set(
+        &mut self,
+        selection_set: &SelectionSet,
+        sized_fields: Option<(&[String], f64)>,
+    ) -> f64 {
+        let mut cost = 0.0;
+        for selection in &selection_set.selections {
+            let directives = selection.directives();
+            if eval_if_arg(directives, "skip", self.variables).unwrap_or(false)
+                || !eval_if_arg(directives, "include", self.variables).unwrap_or(true)
+            {
+                continue;
+            }
+            match selection {
+                Selection::Field(field) => {
+                    let list_size = sized_fields.and_then(|(names, size)| {
+                        names
+                            .iter()
+                            .any(|name| field.name == name.as_str())
+                            .then_some(size)
+                    });
+                    cost += self.field(&selection_set.ty, field, list_size)
+                }
+                Selection::InlineFragment(inline) => {
+                    cost += self.selection_set(&inline.selection_set, sized_fields)
+                }
+                Selection::FragmentSpread(spread) => {
+                    if let Some(fragment) = self.document.fragments.get(&spread.fragment_name) {
+                        cost += self.selection_set(&fragment.selection_set, sized_fields)
+                    }
+                }
+            }
+        }
+        cost
+    }
+
+    fn field(&mut self, parent_type: &Name, field: &Field, list_size: Option<f64>) -> f64 {
+        let definition = &field.definition;
+        let ty = self.schema.types.get(field.ty().inner_named_type());
+        let weight = weight(definition.directives.get("cost"))
+            .or_else(|| ty.and_then(|ty| weight(ty.directives().get("cost"))))
+            .unwrap_or(if ty.is_some_and(|ty| ty.is_leaf()) {
+                LEAF_TYPE_WEIGHT
+            } else {
+                COMPOSITE_TYPE_WEIGHT
+            });
+
+        let list_size_directive = definition.directives.get("listSize");
+        let size = list_size_directive
+            .and_then(|directive| self.list_size(directive, field))
+            .unwrap_or(self.default_list_size);
+        let sized_fields = list_size_directive
+            .and_then(|directive| directive.specified_argument_by_name("sizedFields"))
+            .map(|value| string_list(value))
+            .filter(|names| !names.is_empty());
+        let instance_count = if field.ty().is_list() {
+            list_size.unwrap_or(if sized_fields.is_some() {
+                self.default_list_size
+            } else {
+                size
+            })
+        } else {
+            1.0
+        };
+
+        let index = self.fields.len();
+        self.path.push(field.response_key().clone());
+        self.fields.push(FieldCost {
+            path: self.path.clone(),
+            coordinate: TypeAttributeCoordinate {
+                ty: parent_type.clone(),
+                attribute: field.name.clone(),
+            },
+            cost: 0.0,
+        });
+        let selections = self.selection_set(
+            &field.selection_set,
+            sized_fields.as_deref().map(|names| (names, size)),
+        );
+        self.path.pop();
+
+        let mut arguments = 0.0;
+        for argument in &field.arguments {
+            if let Some(argument_definition) = definition.argument_by_name(&argument.name) {
+                arguments += self.input_value(argument_definition, &argument.value)
+            }
+        }
+        let cost = instance_count * (weight + selections) + arguments;
+        self.fields[index].cost = cost;
+        cost
+    }
+
+    /// Returns the largest value of slicing arguments, or else the assumed size
+    fn list_size(&self, directive: &Directive, field: &Field) -> Option<f64> {
+        let slicing_arguments = directive
+            .specified_argument_by_name("slicingArguments")
+            .map(|value| string_list(value))
+            .unwrap_or_default();
+        slicing_arguments
+            .iter()
+            .filter_map(|path| {
+                let mut path = path.split('.');
+                let name = path.next()?;
+                // Like in execution, an argument with an unset variable is not provided
+                let value = field
+                    .specified_argument_by_name(name)
+                    .filter(|value| match value.as_variable() {
+                        Some(variable) => self.variables.contains_key(variable.as_str()),
+                        None => true,
+                    })
+                    .or_else(|| {
+                        field
+                            .definition
+                            .argument_by_name(name)?
+                            .default_value
+                            .as_ref()
+                    })?;
+                self.number(value, path)
+            })
+            .reduce(f64::max)
+            .or_else(|| {
+                directive
+                    .specified_argument_by_name("assumedSize")?
+                    .to_i32()
+                    .map(f64::from)
+            })
+            .map(|size| size.max(0.0))
+    }
+
+    /// Evaluates a number at `path` in a (possibly nested) input object value
+    fn number<'a>(&self, value: &Value, mut path: impl Iterator<Item = &'a str>) -> Option<f64> {
+        match value {
+            Value::Variable(name) => {
+                let mut value = self.variables.get(name.as_str())?;
+                for key in path {
+                    value = value.as_object()?.get(key)?
+                }
+                value.as_f64()
+            }
+            Value::Object(fields) => {
+                let key = path.next()?;
+                let (_, value) = fields.iter().find(|(name, _)| name == key)?;
+                self.number(value, path)
+            }
+            Value::Int(int) if path.next().is_none() => int.try_to_f64().ok(),
+            _ => None,
+        }
+    }
+
+    fn input_value(&self, definition: &InputValueDefinition, value: &Value) -> f64 {
+        let weight = weight(definition.directives.get("cost")).unwrap_or(0.0);
+        match value {
+            Value::List(items) => items
+                .iter()
+                .map(|item| self.input_value(definition, item))
+                .sum(),
+            Value::Object(fields) => {
+                let ty = definition.ty.inner_named_type();
+                let Some(ty) = self.schema.get_input_object(ty) else {
+                    return weight;
+                };
+                weight
+                    + fields
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some(self.input_value(ty.fields.get(name)?, value))
+                        })
+                        .sum::<f64>()
+            }
+            Value::Variable(name) => match self.variables.get(name.as_str()) {
+                Some(value) => self.variable_value(definition, value),
+                None => weight,
+            },
+            _ => weight,
+        }
+    }
+
+    /// Like [`Self::input_value`], for the coerced value of a variable
+    fn variable_value(&self, definition: &InputValueDefinition, value: &JsonValue) -> f64 {
+        let weight = weight(definition.directives.get("cost")).unwrap_or(0.0);
+        match value {
+            JsonValue::Array(items) => items
+                .iter()
+                .map(|item| self.variable_value(definition, item))
+                .sum(),
+            JsonValue::Object(fields) => {
+                let ty = definition.ty.inner_named_type();
+                let Some(ty) = self.schema.get_input_object(ty) else {
+                    return weight;
+                };
+                weight
+                    + fields
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some(self.variable_value(ty.fields.get(name.as_str())?, value))
+                        })
+                        .sum::<f64>()
+            }
+            _ => weight,
+        }
+    }
+}
+
+/// Returns the `weight` argument of a `@cost` directive
+fn weight(directive: Option<&impl AsRef<Directive>>) -> Option<f64> {
+    match directive?
+        .as_ref()
+        .specified_argument_by_name("weight")?
+        .as_ref()
+    {
+        Value::Int(int) => int.try_to_f64().ok(),
+        Value::Float(float) => float.try_to_f64().ok(),
+        // Weights are strings in the original specification
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    match value {
+        Value::List(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(ToOwned::to_owned))
+            .collect(),
+        Value::String(string) => vec![string.clone()],
+        _ => Vec::new(),
+    }
+}
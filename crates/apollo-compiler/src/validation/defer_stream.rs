@@ -0,0 +1,145 @@
+//! Validation rules for the `@defer` and `@stream` directives of [incremental delivery].
+//!
+//! These only have an effect when the directives are used,
+//! which requires the schema to define them.
+//!
+//! [incremental delivery]: https://github.com/graphql/graphql-spec/pull/1110
+
+use crate::ast;
+use crate::collections::HashMap;
+use crate::coordinate::TypeAttributeCoordinate;
+use crate::executable;
+use crate::validation::diagnostics::DiagnosticData;
+use crate::validation::DiagnosticList;
+use crate::validation::SourceSpan;
+use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
+use crate::Schema;
+
+struct DeferStreamValidation<'a> {
+    diagnostics: &'a mut DiagnosticList,
+    subscription_root_type: Option<&'a Name>,
+    labels: HashMap<&'a str, Option<SourceSpan>>,
+}
+
+/// Checks that:
+///
+/// * `label` arguments are static strings, unique across the document
+/// * `@stream` is only used on list fields
+/// * Neither directive is used on root fields of a subscription operation,
+///   unless disabled with a literal `if: false` argument
+///
+/// Each operation and fragment definition is visited once, without following fragment spreads.
+pub(crate) fn validate_defer_stream(
+    diagnostics: &mut DiagnosticList,
+    schema: &Schema,
+    document: &ExecutableDocument,
+) {
+    let mut validation = DeferStreamValidation {
+        diagnostics,
+        subscription_root_type: schema.root_operation(ast::OperationType::Subscription),
+        labels: HashMap::default(),
+    };
+    for operation in document.operations.iter() {
+        validation.selection_set(&operation.selection_set);
+    }
+    for fragment in document.fragments.values() {
+        validation.selection_set(&fragment.selection_set);
+    }
+}
+
+impl<'a> DeferStreamValidation<'a> {
+    fn selection_set(&mut self, selection_set: &'a executable::SelectionSet) {
+        let is_subscription_root = self.subscription_root_type == Some(&selection_set.ty);
+        for selection in &selection_set.selections {
+            let directive_name = match selection {
+                executable::Selection::Field(_) => "stream",
+                executable::Selection::FragmentSpread(_)
+                | executable::Selection::InlineFragment(_) => "defer",
+            };
+            if let Some(directive) = selection.directives().get(directive_name) {
+                self.directive(directive, is_subscription_root, &selection_set.ty);
+                if let executable::Selection::Field(field) = selection {
+                    self.stream_on_list_field(directive, field, &selection_set.ty);
+                }
+            }
+            match selection {
+                executable::Selection::Field(field) => self.selection_set(&field.selection_set),
+                executable::Selection::FragmentSpread(_) => {}
+                executable::Selection::InlineFragment(inline) => {
+                    self.selection_set(&inline.selection_set)
+                }
+            }
+        }
+    }
+
+    fn directive(
+        &mut self,
+        directive: &'a Node<ast::Directive>,
+        is_subscription_root: bool,
+        parent_type: &Name,
+    ) {
+        if let Some(label) = directive.specified_argument_by_name("label") {
+            match label.as_ref() {
+                ast::Value::String(label_str) => {
+                    let location = label.location();
+                    if let Some(&original_application) = self.labels.get(label_str.as_str()) {
+                        self.diagnostics.push(
+                            location,
+                            DiagnosticData::UniqueDeferStreamLabel {
+                                label: label_str.clone(),
+                                original_application,
+                            },
+                        )
+                    } else {
+                        self.labels.insert(label_str, location);
+                    }
+                }
+                ast::Value::Variable(_) => self.diagnostics.push(
+                    label.location(),
+                    DiagnosticData::NonStaticDeferStreamLabel {
+                        name: directive.name.clone(),
+                    },
+                ),
+                // Other types are reported by value validation
+                _ => {}
+            }
+        }
+        if is_subscription_root {
+            let disabled = directive
+                .specified_argument_by_name("if")
+                .is_some_and(|value| matches!(value.as_ref(), ast::Value::Boolean(false)));
+            if !disabled {
+                self.diagnostics.push(
+                    directive.location(),
+                    DiagnosticData::DeferStreamOnSubscriptionRoot {
+                        name: directive.name.clone(),
+                        root_type: parent_type.clone(),
+                    },
+                )
+            }
+        }
+    }
+
+    fn stream_on_list_field(
+        &mut self,
+        directive: &Node<ast::Directive>,
+        field: &executable::Field,
+        parent_type: &Name,
+    ) {
+        if !field.ty().is_list() {
+            self.diagnostics.push(
+                directive.location(),
+                DiagnosticData::StreamOnNonListField {
+                    coordinate: TypeAttributeCoordinate {
+                        ty: parent_type.clone(),
+                        attribute: field.name.clone(),
+                    },
+                    ty: Node::new(field.ty().clone()),
+                    definition_location: field.definition.location(),
+                },
+            )
+        }
+    }
+}
@@ -288,6 +288,75 @@ pub(crate) enum DiagnosticData {
         type_location: Option<SourceSpan>,
         extensions_locations: Vec<Option<SourceSpan>>,
     },
+    #[error("the label `{label}` is used by multiple `@defer` or `@stream` directives")]
+    UniqueDeferStreamLabel {
+        label: String,
+        original_application: Option<SourceSpan>,
+    },
+    #[error("the `label` argument of `@{name}` must be a static string")]
+    NonStaticDeferStreamLabel {
+        /// Name of the directive, `defer` or `stream`
+        name: Name,
+    },
+    #[error("`@stream` cannot be used on non-list field `{coordinate}`")]
+    StreamOnNonListField {
+        coordinate: TypeAttributeCoordinate,
+        ty: Node<Type>,
+        definition_location: Option<SourceSpan>,
+    },
+    #[error("`@{name}` cannot be used on root fields of a subscription operation")]
+    DeferStreamOnSubscriptionRoot {
+        /// Name of the directive, `defer` or `stream`
+        name: Name,
+        /// Name of the subscription root operation type
+        root_type: Name,
+    },
+    #[error("field `{coordinate}` of a oneOf input object must be nullable")]
+    NonNullOneOfInputField {
+        coordinate: TypeAttributeCoordinate,
+        ty: Node<Type>,
+    },
+    #[error("field `{coordinate}` of a oneOf input object cannot have a default value")]
+    OneOfInputFieldDefaultValue {
+        coordinate: TypeAttributeCoordinate,
+        default_value_location: Option<SourceSpan>,
+    },
+    #[error("oneOf input object `{name}` must specify exactly one field")]
+    OneOfInputObjectFieldCount {
+        /// Name of the input object type
+        name: Name,
+        /// Number of fields specified by the value
+        count: usize,
+        definition_location: Option<SourceSpan>,
+    },
+    #[error("field `{coordinate}` of a oneOf input object cannot be null")]
+    NullOneOfInputField { coordinate: TypeAttributeCoordinate },
+    #[error("variable `${variable}` of nullable type `{variable_type}` cannot be used for oneOf input field `{coordinate}`")]
+    NullableVariableForOneOfInputField {
+        /// Name of the variable
+        variable: Name,
+        variable_type: Node<Type>,
+        coordinate: TypeAttributeCoordinate,
+        variable_location: Option<SourceSpan>,
+    },
+    #[error("the operation has a selection depth of {depth}, exceeding the limit of {max}")]
+    MaxDepthExceeded {
+        depth: usize,
+        max: usize,
+        operation_location: Option<SourceSpan>,
+    },
+    #[error("the operation uses {count} aliases, exceeding the limit of {max}")]
+    MaxAliasesExceeded { count: usize, max: usize },
+    #[error("the operation selects {count} root fields, exceeding the limit of {max}")]
+    MaxRootFieldsExceeded { count: usize, max: usize },
+    #[error("the field `{name}` has {count} directives, exceeding the limit of {max}")]
+    MaxDirectivesPerFieldExceeded {
+        name: Name,
+        count: usize,
+        max: usize,
+    },
+    #[error("the operation selects {count} fields, exceeding the limit of {max}")]
+    MaxFieldsExceeded { count: usize, max: usize },
 }
 
 impl DiagnosticData {
@@ -714,6 +783,109 @@ impl DiagnosticData {
                     "input values",
                 );
             }
+            DiagnosticData::UniqueDeferStreamLabel {
+                label,
+                original_application,
+            } => {
+                report.with_label_opt(
+                    *original_application,
+                    format_args!("label `{label}` first used here"),
+                );
+                report.with_label_opt(
+                    main_location,
+                    format_args!("label `{label}` used again here"),
+                );
+                report.with_help("labels identify deferred or streamed results, so they must be unique in the document");
+            }
+            DiagnosticData::NonStaticDeferStreamLabel { .. } => {
+                report.with_label_opt(main_location, "label provided here");
+                report.with_help("use a string literal instead of a variable");
+            }
+            DiagnosticData::StreamOnNonListField {
+                coordinate,
+                ty,
+                definition_location,
+            } => {
+                report.with_label_opt(main_location, "directive used here");
+                report.with_label_opt(
+                    *definition_location,
+                    format_args!("`{coordinate}` has type `{ty}` defined here"),
+                );
+            }
+            DiagnosticData::DeferStreamOnSubscriptionRoot { name, root_type } => {
+                report.with_label_opt(
+                    main_location,
+                    format_args!("`@{name}` used within a selection on `{root_type}`"),
+                );
+                report.with_help("a subscription sends one response per event, which cannot be split into incremental payloads");
+            }
+            DiagnosticData::NonNullOneOfInputField { coordinate: _, ty } => {
+                report.with_label_opt(
+                    main_location,
+                    format_args!("field declared with non-null type `{ty}`"),
+                );
+                report.with_help("exactly one field of a oneOf input object is given a value, so all of its fields must be nullable");
+            }
+            DiagnosticData::OneOfInputFieldDefaultValue {
+                coordinate: _,
+                default_value_location,
+            } => {
+                report.with_label_opt(main_location, "field defined here");
+                report.with_label_opt(*default_value_location, "remove this default value");
+            }
+            DiagnosticData::OneOfInputObjectFieldCount {
+                name,
+                count,
+                definition_location,
+            } => {
+                report.with_label_opt(main_location, format_args!("{count} fields specified here"));
+                report.with_label_opt(
+                    *definition_location,
+                    format_args!("`{name}` is marked with `@oneOf` here"),
+                );
+            }
+            DiagnosticData::NullOneOfInputField { .. } => {
+                report.with_label_opt(main_location, "null value provided here");
+            }
+            DiagnosticData::NullableVariableForOneOfInputField {
+                variable,
+                variable_type: _,
+                coordinate: _,
+                variable_location,
+            } => {
+                report.with_label_opt(main_location, "variable used here");
+                report.with_label_opt(
+                    *variable_location,
+                    format_args!("`${variable}` declared here"),
+                );
+                report.with_help(
+                    "the variable could be null, which a oneOf input object does not allow",
+                );
+            }
+            DiagnosticData::MaxDepthExceeded {
+                depth,
+                operation_location,
+                ..
+            } => {
+                report.with_label_opt(main_location, format_args!("field at depth {depth}"));
+                report.with_label_opt(*operation_location, "in this operation");
+            }
+            DiagnosticData::MaxAliasesExceeded { count, .. } => {
+                report.with_label_opt(main_location, format_args!("{count} aliases used here"));
+            }
+            DiagnosticData::MaxRootFieldsExceeded { count, .. } => {
+                report.with_label_opt(
+                    main_location,
+                    format_args!("{count} root fields selected here"),
+                );
+            }
+            DiagnosticData::MaxDirectivesPerFieldExceeded { count, .. } => {
+                report.with_label_opt(main_location, format_args!("{count} directives used here"));
+            }
+            DiagnosticData::MaxFieldsExceeded { count, .. } => {
+                report.with_label_opt(main_location, format_args!("{count} fields selected here"));
+                report.with_help("fields of fragments are counted each time the fragment is used");
+            }
         }
     }
 
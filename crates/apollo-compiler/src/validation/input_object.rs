@@ -1,5 +1,6 @@
 use crate::ast;
 use crate::collections::HashMap;
+use crate::coordinate::TypeAttributeCoordinate;
 use crate::schema::validation::BuiltInScalars;
 use crate::schema::InputObjectType;
 use crate::validation::diagnostics::DiagnosticData;
@@ -116,6 +117,35 @@ pub(crate) fn validate_input_object_definition(
         ast::DirectiveLocation::InputFieldDefinition,
     );
 
+    // Fields of a OneOf Input Object must be nullable and not have a default value
+    // https://github.com/graphql/graphql-spec/pull/825
+    if input_object.is_one_of() {
+        for field in input_object.fields.values() {
+            let coordinate = TypeAttributeCoordinate {
+                ty: input_object.name.clone(),
+                attribute: field.name.clone(),
+            };
+            if field.ty.is_non_null() {
+                diagnostics.push(
+                    field.ty.location(),
+                    DiagnosticData::NonNullOneOfInputField {
+                        coordinate: coordinate.clone(),
+                        ty: field.ty.clone(),
+                    },
+                );
+            }
+            if let Some(default_value) = &field.default_value {
+                diagnostics.push(
+                    field.location(),
+                    DiagnosticData::OneOfInputFieldDefaultValue {
+                        coordinate,
+                        default_value_location: default_value.location(),
+                    },
+                );
+            }
+        }
+    }
+
     // validate there is at least one input value on the input object type
     // https://spec.graphql.org/draft/#sel-HAHhBXDBABAB5BvgD
     if input_object.fields.is_empty() {
@@ -7,6 +7,9 @@ use crate::ExecutableDocument;
 use crate::Schema;
 
 pub(crate) mod argument;
+pub(crate) mod authorization;
+pub(crate) mod cost;
+pub(crate) mod defer_stream;
 pub(crate) mod diagnostics;
 pub(crate) mod directive;
 pub(crate) mod enum_;
@@ -16,12 +19,14 @@ pub(crate) mod input_object;
 pub(crate) mod interface;
 pub(crate) mod object;
 pub(crate) mod operation;
+pub(crate) mod operation_limits;
 pub(crate) mod scalar;
 pub(crate) mod schema;
 pub(crate) mod selection;
 pub(crate) mod union_;
 pub(crate) mod value;
 pub(crate) mod variable;
+pub(crate) mod visibility;
 
 use crate::collections::HashMap;
 use crate::collections::IndexSet;
@@ -45,6 +50,14 @@ use std::fmt;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
+pub use self::authorization::AuthorizationFilter;
+pub use self::authorization::AuthorizedOperation;
+pub use self::cost::CostCalculator;
+pub use self::cost::CostLimitExceeded;
+pub use self::cost::FieldCost;
+pub use self::cost::OperationCost;
+pub use self::operation_limits::OperationLimits;
+
 /// Wraps a [`Schema`] or [`ExecutableDocument`] to mark it
 /// as [valid](https://spec.graphql.org/October2021/#sec-Validation).
 ///
@@ -279,6 +292,8 @@ pub(crate) enum Details {
     CompilerDiagnostic(diagnostics::DiagnosticData),
     #[error("too much recursion")]
     RecursionLimitError,
+    #[error("invalid introspection result at `{path}`: {message}")]
+    InvalidIntrospection { path: String, message: String },
 }
 
 impl DiagnosticData {
@@ -331,6 +346,22 @@ impl DiagnosticData {
                     EmptyValueSet { .. } => "EmptyValueSet",
                     EmptyMemberSet { .. } => "EmptyMemberSet",
                     EmptyInputValueSet { .. } => "EmptyInputValueSet",
+                    UniqueDeferStreamLabel { .. } => "UniqueDeferStreamLabel",
+                    NonStaticDeferStreamLabel { .. } => "NonStaticDeferStreamLabel",
+                    StreamOnNonListField { .. } => "StreamOnNonListField",
+                    DeferStreamOnSubscriptionRoot { .. } => "DeferStreamOnSubscriptionRoot",
+                    NonNullOneOfInputField { .. } => "NonNullOneOfInputField",
+                    OneOfInputFieldDefaultValue { .. } => "OneOfInputFieldDefaultValue",
+                    OneOfInputObjectFieldCount { .. } => "OneOfInputObjectFieldCount",
+                    NullOneOfInputField { .. } => "NullOneOfInputField",
+                    NullableVariableForOneOfInputField { .. } => {
+                        "NullableVariableForOneOfInputField"
+                    }
+                    MaxDepthExceeded { .. } => "MaxDepthExceeded",
+                    MaxAliasesExceeded { .. } => "MaxAliasesExceeded",
+                    MaxRootFieldsExceeded { .. } => "MaxRootFieldsExceeded",
+                    MaxDirectivesPerFieldExceeded { .. } => "MaxDirectivesPerFieldExceeded",
+                    MaxFieldsExceeded { .. } => "MaxFieldsExceeded",
                 })
             }
             Details::ExecutableBuildError(error) => Some(match error {
@@ -527,6 +558,39 @@ impl DiagnosticData {
                     EmptyValueSet { .. } => None,
                     EmptyMemberSet { .. } => None,
                     EmptyInputValueSet { .. } => None,
+                    UniqueDeferStreamLabel { .. } => {
+                        Some("Defer/Stream directive label argument must be unique.".to_string())
+                    }
+                    NonStaticDeferStreamLabel { name } => Some(format!(
+                        r#"Argument "@{name}(label:)" must be a static string."#
+                    )),
+                    StreamOnNonListField { coordinate, .. } => Some(format!(
+                        r#"Directive "@stream" cannot be used on non-list field "{coordinate}"."#
+                    )),
+                    DeferStreamOnSubscriptionRoot { name, root_type } => Some(format!(
+                        r#"Defer/Stream directive "@{name}" cannot be used on root subscription type "{root_type}"."#
+                    )),
+                    NonNullOneOfInputField { .. } => None,
+                    OneOfInputFieldDefaultValue { .. } => None,
+                    OneOfInputObjectFieldCount { name, .. } => Some(format!(
+                        r#"OneOf Input Object "{name}" must specify exactly one key."#
+                    )),
+                    NullOneOfInputField { coordinate } => {
+                        Some(format!(r#"Field "{coordinate}" must be non-null."#))
+                    }
+                    NullableVariableForOneOfInputField {
+                        variable,
+                        coordinate,
+                        ..
+                    } => Some(format!(
+                        r#"Variable "${variable}" must be non-nullable to be used for OneOf Input Object "{}"."#,
+                        coordinate.ty
+                    )),
+                    MaxDepthExceeded { .. } => None,
+                    MaxAliasesExceeded { .. } => None,
+                    MaxRootFieldsExceeded { .. } => None,
+                    MaxDirectivesPerFieldExceeded { .. } => None,
+                    MaxFieldsExceeded { .. } => None,
                 }
             }
             Details::ExecutableBuildError(error) => match error {
@@ -946,6 +1010,9 @@ impl ToCliReport for DiagnosticData {
                 }
             },
             Details::RecursionLimitError => {}
+            Details::InvalidIntrospection { message, .. } => {
+                report.with_label_opt(self.location, message)
+            }
         }
     }
 }
@@ -0,0 +1,232 @@
+//! Configurable limits on the size and shape of operations,
+//! to reject expensive operations before executing them.
+
+use crate::collections::HashMap;
+use crate::executable;
+use crate::validation::diagnostics::DiagnosticData;
+use crate::validation::DiagnosticList;
+use crate::validation::SourceSpan;
+use crate::ExecutableDocument;
+use crate::Name;
+use crate::Node;
+
+/// Limits checked by [`ExecutableDocument::validate_with_limits`].
+///
+/// All limits are disabled by default.
+/// Fragments are expanded: a field selected in a fragment counts each time the fragment is used.
+/// Each operation that exceeds a limit is reported with a separate kind of diagnostic.
+///
+/// ## Example
+///
+/// ```
+/// use apollo_compiler::validation::OperationLimits;
+/// use apollo_compiler::ExecutableDocument;
+/// use apollo_compiler::Schema;
+///
+/// let schema = Schema::parse_and_validate(
+///     "type Query { me: User } type User { name: String, friends: [User] }",
+///     "schema.graphql",
+/// ).unwrap();
+/// let limits = OperationLimits::new().max_depth(3).max_aliases(1);
+///
+/// let document = ExecutableDocument::parse(
+///     &schema,
+///     "{ me { friends { friends { name } } } }",
+///     "query.graphql",
+/// ).unwrap();
+/// let errors = document.validate_with_limits(&schema, &limits).unwrap_err().errors;
+/// assert_eq!(
+///     errors.iter().next().unwrap().error.to_string(),
+///     "the operation has a selection depth of 4, exceeding the limit of 3",
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OperationLimits {
+    max_depth: Option<usize>,
+    max_aliases: Option<usize>,
+    max_root_fields: Option<usize>,
+    max_directives_per_field: Option<usize>,
+    max_fields: Option<usize>,
+}
+
+impl OperationLimits {
+    /// Returns limits with nothing limited
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of nested fields. Root fields are at depth 1.
+    pub fn max_depth(mut self, value: usize) -> Self {
+        self.max_depth = Some(value);
+        self
+    }
+
+    /// Set the maximum number of fields with an alias in an operation
+    pub fn max_aliases(mut self, value: usize) -> Self {
+        self.max_aliases = Some(value);
+        self
+    }
+
+    /// Set the maximum number of fields selected on the root operation type
+    pub fn max_root_fields(mut self, value: usize) -> Self {
+        self.max_root_fields = Some(value);
+        self
+    }
+
+    /// Set the maximum number of directives used on one field selection
+    pub fn max_directives_per_field(mut self, value: usize) -> Self {
+        self.max_directives_per_field = Some(value);
+        self
+    }
+
+    /// Set the maximum total number of fields in an operation
+    pub fn max_fields(mut self, value: usize) -> Self {
+        self.max_fields = Some(value);
+        self
+    }
+}
+
+/// Measurements of a selection set, with fragments expanded
+#[derive(Debug, Clone, Copy, Default)]
+struct Stats {
+    depth: usize,
+    /// Location of a field at `depth`
+    deepest_field: Option<SourceSpan>,
+    fields: usize,
+    aliases: usize,
+    /// Fields directly in the selection set or in its fragments, but not nested in other fields
+    direct_fields: usize,
+}
+
+struct OperationLimitsValidation<'a> {
+    diagnostics: &'a mut DiagnosticList,
+    document: &'a ExecutableDocument,
+    limits: &'a OperationLimits,
+    /// Computed once per fragment, so that deeply nested fragment spreads stay cheap to check
+    fragments: HashMap<&'a Name, Stats>,
+}
+
+pub(crate) fn validate_operation_limits(
+    diagnostics: &mut DiagnosticList,
+    document: &ExecutableDocument,
+    limits: &OperationLimits,
+) {
+    let mut validation = OperationLimitsValidation {
+        diagnostics,
+        document,
+        limits,
+        fragments: HashMap::default(),
+    };
+    for operation in document.operations.iter() {
+        let stats = validation.selection_set(&operation.selection_set);
+        let exceeds = |max: Option<usize>, value: usize| max.filter(|&max| value > max);
+        if let Some(max) = exceeds(limits.max_depth, stats.depth) {
+            validation.diagnostics.push(
+                stats.deepest_field,
+                DiagnosticData::MaxDepthExceeded {
+                    depth: stats.depth,
+                    max,
+                    operation_location: operation.location(),
+                },
+            )
+        }
+        if let Some(max) = exceeds(limits.max_aliases, stats.aliases) {
+            validation.diagnostics.push(
+                operation.location(),
+                DiagnosticData::MaxAliasesExceeded {
+                    count: stats.aliases,
+                    max,
+                },
+            )
+        }
+        if let Some(max) = exceeds(limits.max_root_fields, stats.direct_fields) {
+            validation.diagnostics.push(
+                operation.location(),
+                DiagnosticData::MaxRootFieldsExceeded {
+                    count: stats.direct_fields,
+                    max,
+                },
+            )
+        }
+        if let Some(max) = exceeds(limits.max_fields, stats.fields) {
+            validation.diagnostics.push(
+                operation.location(),
+                DiagnosticData::MaxFieldsExceeded {
+                    count: stats.fields,
+                    max,
+                },
+            )
+        }
+    }
+}
+
+impl<'a> OperationLimitsValidation<'a> {
+    fn selection_set(&mut self, selection_set: &'a executable::SelectionSet) -> Stats {
+        let mut stats = Stats::default();
+        for selection in &selection_set.selections {
+            let nested = match selection {
+                executable::Selection::Field(field) => self.field(field),
+                executable::Selection::InlineFragment(inline) => {
+                    self.selection_set(&inline.selection_set)
+                }
+                executable::Selection::FragmentSpread(spread) => {
+                    self.fragment(&spread.fragment_name)
+                }
+            };
+            if nested.depth > stats.depth {
+                stats.depth = nested.depth;
+                stats.deepest_field = nested.deepest_field;
+            }
+            stats.fields = stats.fields.saturating_add(nested.fields);
+            stats.aliases = stats.aliases.saturating_add(nested.aliases);
+            stats.direct_fields = stats.direct_fields.saturating_add(nested.direct_fields);
+        }
+        stats
+    }
+
+    fn field(&mut self, field: &'a Node<executable::Field>) -> Stats {
+        let count = field.directives.len();
+        if let Some(max) = self
+            .limits
+            .max_directives_per_field
+            .filter(|&max| count > max)
+        {
+            self.diagnostics.push(
+                field.location(),
+                DiagnosticData::MaxDirectivesPerFieldExceeded {
+                    name: field.name.clone(),
+                    count,
+                    max,
+                },
+            )
+        }
+        let nested = self.selection_set(&field.selection_set);
+        Stats {
+            depth: nested.depth + 1,
+            deepest_field: if nested.depth == 0 {
+                field.location()
+            } else {
+                nested.deepest_field
+            },
+            fields: nested.fields.saturating_add(1),
+            aliases: nested
+                .aliases
+                .saturating_add(usize::from(field.alias.is_some())),
+            direct_fields: 1,
+        }
+    }
+
+    fn fragment(&mut self, name: &'a Name) -> Stats {
+        if let Some(stats) = self.fragments.get(name) {
+            return *stats;
+        }
+        // Placeholder in case of a cycle, which is reported by other validation rules
+        self.fragments.insert(name, Stats::default());
+        let Some(fragment) = self.document.fragments.get(name) else {
+            return Stats::default();
+        };
+        let stats = self.selection_set(&fragment.selection_set);
+        self.fragments.insert(name, stats);
+        stats
+    }
+}
@@ -3,6 +3,7 @@ use crate::coordinate::TypeAttributeCoordinate;
 use crate::schema;
 use crate::validation::diagnostics::DiagnosticData;
 use crate::validation::DiagnosticList;
+use crate::Name;
 use crate::Node;
 
 fn unsupported_type(
@@ -231,6 +232,10 @@ pub(crate) fn value_of_correct_type(
                     );
                 }
 
+                if input_obj.is_one_of() {
+                    validate_one_of_value(diagnostics, input_obj, arg_value, obj, var_defs);
+                }
+
                 input_obj.fields.iter().for_each(|(input_name, f)| {
                     let ty = &f.ty;
                     let is_missing = !obj.iter().any(|(value_name, ..)| input_name == value_name);
@@ -268,3 +273,55 @@ pub(crate) fn value_of_correct_type(
         },
     }
 }
+
+/// <https://github.com/graphql/graphql-spec/pull/825>
+fn validate_one_of_value(
+    diagnostics: &mut DiagnosticList,
+    input_obj: &Node<schema::InputObjectType>,
+    arg_value: &Node<ast::Value>,
+    obj: &[(Name, Node<ast::Value>)],
+    var_defs: &[Node<ast::VariableDefinition>],
+) {
+    let [(name, value)] = obj else {
+        diagnostics.push(
+            arg_value.location(),
+            DiagnosticData::OneOfInputObjectFieldCount {
+                name: input_obj.name.clone(),
+                count: obj.len(),
+                definition_location: input_obj
+                    .directives
+                    .get("oneOf")
+                    .and_then(|directive| directive.location()),
+            },
+        );
+        return;
+    };
+    let coordinate = TypeAttributeCoordinate {
+        ty: input_obj.name.clone(),
+        attribute: name.clone(),
+    };
+    match &**value {
+        ast::Value::Null => diagnostics.push(
+            value.location(),
+            DiagnosticData::NullOneOfInputField { coordinate },
+        ),
+        ast::Value::Variable(var_name) => {
+            // Undefined variables are reported by `value_of_correct_type`
+            let Some(var_def) = var_defs.iter().find(|v| v.name == *var_name) else {
+                return;
+            };
+            if !var_def.ty.is_non_null() {
+                diagnostics.push(
+                    value.location(),
+                    DiagnosticData::NullableVariableForOneOfInputField {
+                        variable: var_name.clone(),
+                        variable_type: var_def.ty.clone(),
+                        coordinate,
+                        variable_location: var_def.location(),
+                    },
+                )
+            }
+        }
+        _ => {}
+    }
+}
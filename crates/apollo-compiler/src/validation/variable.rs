@@ -126,7 +126,7 @@ fn walk_selections<'doc>(
     result
 }
 
-fn variables_in_value(value: &ast::Value) -> impl Iterator<Item = &Name> + '_ {
+pub(crate) fn variables_in_value(value: &ast::Value) -> impl Iterator<Item = &Name> + '_ {
     let mut value_stack = vec![value];
     std::iter::from_fn(move || {
         while let Some(value) = value_stack.pop() {
@@ -143,11 +143,13 @@ fn variables_in_value(value: &ast::Value) -> impl Iterator<Item = &Name> + '_ {
     })
 }
 
-fn variables_in_arguments(args: &[Node<ast::Argument>]) -> impl Iterator<Item = &Name> + '_ {
+pub(crate) fn variables_in_arguments(
+    args: &[Node<ast::Argument>],
+) -> impl Iterator<Item = &Name> + '_ {
     args.iter().flat_map(|arg| variables_in_value(&arg.value))
 }
 
-fn variables_in_directives(
+pub(crate) fn variables_in_directives(
     directives: &[Node<ast::Directive>],
 ) -> impl Iterator<Item = &Name> + '_ {
     directives
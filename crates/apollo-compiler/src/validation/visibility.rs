@@ -0,0 +1,98 @@
+use crate::executable;
+use crate::executable::BuildError;
+use crate::executable::ExecutableDefinitionName;
+use crate::executable::SelectionPath;
+use crate::schema::SchemaVisibility;
+use crate::schema::VisibilityCheck;
+use crate::validation::DiagnosticList;
+use crate::ExecutableDocument;
+use crate::Schema;
+
+/// Reports selections of hidden fields and type conditions on hidden types
+/// the same way as undefined fields and types, so that errors don’t reveal hidden elements
+pub(crate) fn validate_visibility(
+    errors: &mut DiagnosticList,
+    schema: &Schema,
+    document: &ExecutableDocument,
+    visibility: &dyn SchemaVisibility,
+) {
+    let check = VisibilityCheck { schema, visibility };
+    for operation in document.operations.iter() {
+        let root = match &operation.name {
+            Some(name) => {
+                ExecutableDefinitionName::NamedOperation(operation.operation_type, name.clone())
+            }
+            None => ExecutableDefinitionName::AnonymousOperation(operation.operation_type),
+        };
+        let mut path = SelectionPath {
+            root,
+            nested_fields: Vec::new(),
+        };
+        validate_selection_set(errors, check, &operation.selection_set, &mut path)
+    }
+    for fragment in document.fragments.values() {
+        let type_name = fragment.type_condition();
+        if !check.type_name(type_name) {
+            errors.push(
+                fragment.location(),
+                BuildError::UndefinedTypeInNamedFragmentTypeCondition {
+                    type_name: type_name.clone(),
+                    fragment_name: fragment.name.clone(),
+                },
+            );
+            continue;
+        }
+        let mut path = SelectionPath {
+            root: ExecutableDefinitionName::Fragment(fragment.name.clone()),
+            nested_fields: Vec::new(),
+        };
+        validate_selection_set(errors, check, &fragment.selection_set, &mut path)
+    }
+}
+
+fn validate_selection_set(
+    errors: &mut DiagnosticList,
+    check: VisibilityCheck<'_>,
+    selection_set: &executable::SelectionSet,
+    path: &mut SelectionPath,
+) {
+    for selection in &selection_set.selections {
+        match selection {
+            executable::Selection::Field(field) => {
+                if let Some(parent_type) = check.schema.types.get(&selection_set.ty) {
+                    if !check.field(parent_type, &field.definition) {
+                        errors.push(
+                            field.name.location(),
+                            BuildError::UndefinedField {
+                                type_name: selection_set.ty.clone(),
+                                field_name: field.name.clone(),
+                                path: path.clone(),
+                            },
+                        );
+                        continue;
+                    }
+                }
+                path.nested_fields.push(field.response_key().clone());
+                validate_selection_set(errors, check, &field.selection_set, path);
+                path.nested_fields.pop();
+            }
+            executable::Selection::InlineFragment(inline) => {
+                if let Some(type_name) = &inline.type_condition {
+                    if !check.type_name(type_name) {
+                        errors.push(
+                            inline.location(),
+                            BuildError::UndefinedTypeInInlineFragmentTypeCondition {
+                                type_name: type_name.clone(),
+                                path: path.clone(),
+                            },
+                        );
+                        continue;
+                    }
+                }
+                validate_selection_set(errors, check, &inline.selection_set, path)
+            }
+            // Fragment definitions are checked separately
+            executable::Selection::FragmentSpread(_) => {}
+        }
+    }
+}
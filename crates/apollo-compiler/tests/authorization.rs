@@ -0,0 +1,248 @@
+use apollo_compiler::validation::AuthorizationFilter;
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+
+const SCHEMA: &str = r#"
+    directive @authenticated on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+    directive @requiresScopes(scopes: [[Scope!]!]!)
+        on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+    directive @policy(policies: [[Policy!]!]!)
+        on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+    scalar Scope
+    scalar Policy
+
+    type Query {
+        me: User @authenticated
+        user(id: ID!): User
+        node(id: ID!): Node
+        posts(first: Int): [Post]
+    }
+
+    interface Node { id: ID! }
+
+    type User implements Node {
+        id: ID!
+        name: String
+        email: String @requiresScopes(scopes: [["user:email"], ["admin"]])
+        secret: Secret
+    }
+
+    type Post implements Node {
+        id: ID!
+        title: String
+        draft: Boolean @policy(policies: [["author", "editor"]])
+    }
+
+    type Admin implements Node @requiresScopes(scopes: [["admin"]]) {
+        id: ID!
+    }
+
+    scalar Secret @authenticated
+"#;
+
+const QUERY: &str = r#"
+    query Q($id: ID!, $n: Int, $skip: Boolean!) {
+        me { name }
+        user(id: $id) { ...UserFields }
+        node(id: $id) {
+            __typename
+            ... on Admin { id }
+            ... on Post @skip(if: $skip) { draft }
+            ...AdminFields
+        }
+        posts(first: $n) { id ...PostFields }
+    }
+
+    fragment UserFields on User { name email secret }
+    fragment PostFields on Post { title draft }
+    fragment AdminFields on Admin { id }
+"#;
+
+#[test]
+fn unauthenticated() {
+    let expected = expect![[r#"
+        query Q($id: ID!, $n: Int) {
+          user(id: $id) {
+            ...UserFields
+          }
+          node(id: $id) {
+            __typename
+          }
+          posts(first: $n) {
+            id
+            ...PostFields
+          }
+        }
+
+        fragment UserFields on User {
+          name
+        }
+
+        fragment PostFields on Post {
+          title
+        }
+
+        {"message":"Unauthorized field or type","locations":[{"line":3,"column":9}],"path":["me"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":14,"column":40}],"path":["user","email"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":14,"column":46}],"path":["user","secret"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":7,"column":13}],"path":["node"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":8,"column":44}],"path":["node","draft"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":9,"column":13}],"path":["node"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":15,"column":41}],"path":["posts","draft"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+    "#]];
+    assert_filter(AuthorizationFilter::new(), expected);
+}
+
+#[test]
+fn authenticated_with_scopes() {
+    let filter = AuthorizationFilter::new()
+        .authenticated(true)
+        .scopes(["user:email"])
+        .policies(["author"]);
+    let expected = expect![[r#"
+        query Q($id: ID!, $n: Int) {
+          me {
+            name
+          }
+          user(id: $id) {
+            ...UserFields
+          }
+          node(id: $id) {
+            __typename
+          }
+          posts(first: $n) {
+            id
+            ...PostFields
+          }
+        }
+
+        fragment UserFields on User {
+          name
+          email
+          secret
+        }
+
+        fragment PostFields on Post {
+          title
+        }
+
+        {"message":"Unauthorized field or type","locations":[{"line":7,"column":13}],"path":["node"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":8,"column":44}],"path":["node","draft"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":9,"column":13}],"path":["node"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+        {"message":"Unauthorized field or type","locations":[{"line":15,"column":41}],"path":["posts","draft"],"extensions":{"code":"UNAUTHORIZED_FIELD_OR_TYPE"}}
+    "#]];
+    assert_filter(filter, expected);
+}
+
+#[test]
+fn fully_authorized() {
+    let filter = AuthorizationFilter::new()
+        .authenticated(true)
+        .scopes(["admin"])
+        .policies(["author", "editor"]);
+    let expected = expect![[r#"
+        query Q($id: ID!, $n: Int, $skip: Boolean!) {
+          me {
+            name
+          }
+          user(id: $id) {
+            ...UserFields
+          }
+          node(id: $id) {
+            __typename
+            ... on Admin {
+              id
+            }
+            ... on Post @skip(if: $skip) {
+              draft
+            }
+            ...AdminFields
+          }
+          posts(first: $n) {
+            id
+            ...PostFields
+          }
+        }
+
+        fragment UserFields on User {
+          name
+          email
+          secret
+        }
+
+        fragment PostFields on Post {
+          title
+          draft
+        }
+
+        fragment AdminFields on Admin {
+          id
+        }
+
+    "#]];
+    assert_filter(filter, expected);
+}
+
+#[test]
+fn nothing_authorized() {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let query = "query Me($id: ID!) { me { id } node(id: $id) { id } }";
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let filtered = AuthorizationFilter::new().filter(&schema, &document, operation);
+    assert!(filtered.document.is_none());
+    let expected = expect![[r#"
+        [
+          {
+            "message": "Unauthorized field or type",
+            "locations": [
+              {
+                "line": 1,
+                "column": 22
+              }
+            ],
+            "path": [
+              "me"
+            ],
+            "extensions": {
+              "code": "UNAUTHORIZED_FIELD_OR_TYPE"
+            }
+          },
+          {
+            "message": "Unauthorized field or type",
+            "locations": [
+              {
+                "line": 1,
+                "column": 48
+              }
+            ],
+            "path": [
+              "node",
+              "id"
+            ],
+            "extensions": {
+              "code": "UNAUTHORIZED_FIELD_OR_TYPE"
+            }
+          }
+        ]"#]];
+    expected.assert_eq(&serde_json::to_string_pretty(&filtered.errors).unwrap());
+}
+
+#[track_caller]
+fn assert_filter(filter: AuthorizationFilter, expected: expect_test::Expect) {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let document = ExecutableDocument::parse_and_validate(&schema, QUERY, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let filtered = filter.filter(&schema, &document, operation);
+    let document = match &filtered.document {
+        Some(document) => document.to_string(),
+        None => "None\n".to_owned(),
+    };
+    let errors = filtered
+        .errors
+        .iter()
+        .map(|error| format!("{}\n", serde_json::to_string(error).unwrap()))
+        .collect::<String>();
+    expected.assert_eq(&format!("{document}\n{errors}"));
+}
@@ -0,0 +1,212 @@
+use apollo_compiler::execution::coerce_variable_values;
+use apollo_compiler::execution::JsonMap;
+use apollo_compiler::validation::CostCalculator;
+use apollo_compiler::validation::Valid;
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+
+fn schema() -> Valid<Schema> {
+    Schema::parse_and_validate(
+        r#"
+        directive @cost(weight: Int!)
+          on ARGUMENT_DEFINITION | ENUM | FIELD_DEFINITION | INPUT_FIELD_DEFINITION | OBJECT | SCALAR
+        directive @listSize(
+          assumedSize: Int
+          slicingArguments: [String!]
+          sizedFields: [String!]
+          requireOneSlicingArgument: Boolean = true
+        ) on FIELD_DEFINITION
+
+        type Query {
+          user(id: ID!): User
+          users(first: Int = 5, last: Int): [User]
+            @listSize(slicingArguments: ["first", "last"], requireOneSlicingArgument: false)
+          search(filter: Filter, page: Page): [Product]
+            @listSize(slicingArguments: ["page.size"], assumedSize: 20)
+          products(first: Int): ProductConnection
+            @listSize(slicingArguments: ["first"], sizedFields: ["edges"])
+          tags: [String]
+          expensive: Int @cost(weight: 50)
+        }
+        type Mutation { addProduct(name: String @cost(weight: 2)): Product }
+        type User { name: String, friends: [User] @listSize(assumedSize: 3), avatar: Image }
+        type Product @cost(weight: 4) { name: String }
+        type ProductConnection { edges: [ProductEdge], count: Int }
+        type ProductEdge { node: Product }
+        scalar Image @cost(weight: 2)
+        input Filter { name: String, regex: String @cost(weight: 10) }
+        input Page { size: Int }
+        "#,
+        "schema.graphql",
+    )
+    .unwrap()
+}
+
+fn cost(query: &str, variables: serde_json::Value) -> f64 {
+    let schema = schema();
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let variables: JsonMap = serde_json::from_value(variables).unwrap();
+    let variables = coerce_variable_values(&schema, operation, &variables).unwrap();
+    CostCalculator::new()
+        .calculate(&schema, &document, operation, &variables)
+        .total
+}
+
+#[test]
+fn default_weights() {
+    let no_variables = serde_json::json!({});
+    assert_eq!(cost("{ user(id: 1) { name } }", no_variables.clone()), 1.0);
+    assert_eq!(cost("{ tags }", no_variables.clone()), 0.0);
+    assert_eq!(cost("{ __typename }", no_variables.clone()), 0.0);
+    // Weights of scalar and object types, and of fields
+    assert_eq!(
+        cost("{ user(id: 1) { avatar } }", no_variables.clone()),
+        3.0
+    );
+    assert_eq!(cost("{ expensive }", no_variables.clone()), 50.0);
+    // Mutations cost 10 more, and so do arguments with `@cost`
+    assert_eq!(
+        cost(
+            "mutation { addProduct(name: \"a\") { name } }",
+            no_variables
+        ),
+        16.0
+    );
+}
+
+#[test]
+fn list_sizes() {
+    let no_variables = serde_json::json!({});
+    // Default value of a slicing argument
+    assert_eq!(cost("{ users { name } }", no_variables.clone()), 5.0);
+    // Largest slicing argument
+    assert_eq!(
+        cost(
+            "{ users(first: 2, last: 7) { name } }",
+            no_variables.clone()
+        ),
+        7.0
+    );
+    // Nested assumed size: 2 × (1 + 3 × 1)
+    assert_eq!(
+        cost(
+            "{ users(first: 2) { friends { name } } }",
+            no_variables.clone()
+        ),
+        8.0
+    );
+    // Slicing argument in an input object, or assumed size if missing
+    assert_eq!(
+        cost("{ search(page: {size: 2}) { name } }", no_variables.clone()),
+        8.0
+    );
+    assert_eq!(cost("{ search { name } }", no_variables.clone()), 80.0);
+    // Default list size
+    assert_eq!(
+        cost(
+            "{ tags user(id: 1) { friends { friends { name } } } }",
+            no_variables.clone()
+        ),
+        13.0
+    );
+    // Sized fields: 1 + 3 × (1 + 4)
+    assert_eq!(
+        cost(
+            "{ products(first: 3) { count edges { node { name } } } }",
+            no_variables
+        ),
+        16.0
+    );
+}
+
+#[test]
+fn variables() {
+    let query = r#"
+        query($first: Int, $size: Int, $skip: Boolean!, $filter: Filter) {
+          users(first: $first) { name }
+          search(page: {size: $size}, filter: $filter) { name }
+          expensive @skip(if: $skip)
+        }
+    "#;
+    let variables = serde_json::json!({ "first": 10, "size": 1, "skip": true });
+    assert_eq!(cost(query, variables), 14.0);
+    let variables = serde_json::json!({ "size": 1, "skip": false, "filter": {} });
+    // Without `$first` the default value of the argument is used
+    assert_eq!(cost(query, variables), 59.0);
+    // `@cost` of input object fields also applies within variable values
+    let variables = serde_json::json!({ "size": 1, "skip": true, "filter": {"regex": "a"} });
+    assert_eq!(cost(query, variables), 19.0);
+
+    let query = r#"
+        query($page: Page) {
+          search(page: $page, filter: {regex: "a", name: "b"}) { name }
+        }
+    "#;
+    let variables = serde_json::json!({ "page": { "size": 100 } });
+    assert_eq!(cost(query, variables), 410.0);
+}
+
+#[test]
+fn breakdown_and_limit() {
+    let schema = schema();
+    let query = r#"
+        {
+          a: users(first: 2) { ...F }
+          b: user(id: 1) { ...F }
+          expensive @include(if: false)
+        }
+        fragment F on User { name friends { avatar } }
+    "#;
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let variables = coerce_variable_values(&schema, operation, &JsonMap::new()).unwrap();
+    let calculator = CostCalculator::new();
+    let cost = calculator.calculate(&schema, &document, operation, &variables);
+    let breakdown = cost
+        .fields
+        .iter()
+        .map(|field| {
+            let path = field
+                .path
+                .iter()
+                .map(|key| key.as_str())
+                .collect::<Vec<_>>()
+                .join(".");
+            format!("{path} ({}): {}\n", field.coordinate, field.cost)
+        })
+        .collect::<String>();
+    expect![[r#"
+        a (Query.users): 20
+        a.name (User.name): 0
+        a.friends (User.friends): 9
+        a.friends.avatar (User.avatar): 2
+        b (Query.user): 10
+        b.name (User.name): 0
+        b.friends (User.friends): 9
+        b.friends.avatar (User.avatar): 2
+    "#]]
+    .assert_eq(&breakdown);
+    assert_eq!(cost.total, 30.0);
+
+    assert!(calculator
+        .check(&schema, &document, operation, &variables, 30.0)
+        .is_ok());
+    let error = calculator
+        .check(&schema, &document, operation, &variables, 29.0)
+        .unwrap_err();
+    let response = serde_json::to_string_pretty(&error.into_response()).unwrap();
+    expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "estimated cost 30 exceeds the maximum cost of 29",
+              "extensions": {
+                "code": "COST_ESTIMATED_TOO_EXPENSIVE"
+              }
+            }
+          ]
+        }"#]]
+    .assert_eq(&response);
+}
@@ -1,6 +1,7 @@
 use apollo_compiler::parse_mixed_validate;
 use apollo_compiler::ExecutableDocument;
 use apollo_compiler::Schema;
+use expect_test::expect;
 
 #[test]
 fn get_operations() {
@@ -287,3 +288,141 @@ fn iter_all_fields() {
         ["f1", "inner", "f2", "f3", "f3"]
     );
 }
+
+#[test]
+fn operation_signature() {
+    let schema = Schema::parse_and_validate(
+        r#"
+        directive @a(x: Int) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+        directive @b on FIELD
+        type Query {
+          user(id: ID!, filter: Filter, tags: [String]): User
+          node: Node
+        }
+        interface Node { id: ID! }
+        type User implements Node { id: ID! name: String friends(first: Int = 10): [User] }
+        type Post implements Node { id: ID! title: String }
+        input Filter { active: Boolean, limit: Float }
+        enum Role { ADMIN }
+        "#,
+        "schema.graphql",
+    )
+    .unwrap();
+    let first = r#"
+        query Unused { node { id } user(id: 1) { ...Unused } }
+
+        query Users($id: ID!, $active: Boolean = true) {
+          node { ... on User { name } id ... on Post { title } }
+          me: user(id: $id, tags: ["a", "b"], filter: {active: $active, limit: 1.5}) {
+            ...Friends @a(x: 3)
+            name @b @a(x: 1)
+          }
+        }
+
+        fragment Friends on User { friends(first: 5) { id ...Names } }
+        fragment Names on User { name }
+        fragment Unused on User { id }
+    "#;
+    let second = r#"
+        fragment Names on User { name }
+        fragment Friends on User { friends(first: 20) { ...Names, id } }
+        query Users($active: Boolean = true, $id: ID!) {
+          user(
+            filter: {limit: 3, active: $active}
+            tags: []
+            id: $id
+          ) { name @a(x: 2) @b ...Friends @a(x: 4) }
+          node { id ... on Post { title } ... on User { name } }
+        }
+    "#;
+    let signature = |source| {
+        ExecutableDocument::parse_and_validate(&schema, source, "query.graphql")
+            .unwrap()
+            .operation_signature(Some("Users"))
+            .unwrap()
+    };
+    let expected = expect!["fragment Friends on User{friends(first:0){id...Names}}fragment Names on User{name}query Users($active:Boolean=true,$id:ID!){node{id...on Post{title}...on User{name}}user(filter:{},id:$id,tags:[]){name@a(x:0)@b...Friends@a(x:0)}}"];
+    expected.assert_eq(&signature(first));
+    assert_eq!(signature(first), signature(second));
+
+    let doc = ExecutableDocument::parse_and_validate(&schema, first, "query.graphql").unwrap();
+    assert!(doc.operation_signature(None).is_err());
+    assert!(doc.operation_signature(Some("Other")).is_err());
+}
+
+#[test]
+fn operation_hash() {
+    let schema = Schema::parse_and_validate(
+        r#"
+        directive @a(x: Int) on FIELD
+        type Query { user(id: ID!, filter: Filter): User }
+        type User { name: String, age: Int }
+        input Filter { min: Float, max: Float }
+        "#,
+        "schema.graphql",
+    )
+    .unwrap();
+    let hash = |source: &str| {
+        ExecutableDocument::parse_and_validate(&schema, source, "query.graphql")
+            .unwrap()
+            .operation_hash(Some("Q"))
+            .unwrap()
+    };
+    let reference = hash(
+        r#"
+        query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { ...F age @a(x: 1) }
+        }
+        fragment F on User { name }
+        "#,
+    );
+    expect!["ea3fe647bb94cb1379eac076fd636935200ac8261ca026ab7cafdde6706ec3c1"]
+        .assert_eq(&reference);
+
+    // Formatting, comments, definition order, unused definitions,
+    // and the order of variables, arguments, and input object fields don’t matter
+    let equivalent = hash(
+        r#"
+        fragment Unused on User { age }
+        # Comment
+        fragment F on User { name }
+        query Other { user(id: 1) { ...Unused } }
+        query Q($max: Float = 2.00, $id: ID!) {
+          user(filter: {max: $max, min: 1e0}, id: $id) {
+            ...F
+            age @a(x: 1)
+          }
+        }
+        "#,
+    );
+    assert_eq!(equivalent, reference);
+
+    // Aliases, argument values, selection order, and directives do
+    for different in [
+        r#"query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { ...F years: age @a(x: 1) }
+        } fragment F on User { name }"#,
+        r#"query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.5, max: $max}) { ...F age @a(x: 1) }
+        } fragment F on User { name }"#,
+        r#"query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { age @a(x: 1) ...F }
+        } fragment F on User { name }"#,
+        r#"query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { ...F age @a(x: 2) }
+        } fragment F on User { name }"#,
+        r#"query Q($id: ID!, $max: Float = 3.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { ...F age @a(x: 1) }
+        } fragment F on User { name }"#,
+        r#"query Q($id: ID!, $max: Float = 2.0) {
+          user(id: $id, filter: {min: 1.0, max: $max}) { ...F age @a(x: 1) }
+        } fragment F on User { name age }"#,
+    ] {
+        assert_ne!(hash(different), reference, "{different}");
+    }
+
+    let doc =
+        ExecutableDocument::parse_and_validate(&schema, "{ user(id: 1) { name } }", "q").unwrap();
+    assert!(doc.operation_hash(None).is_ok());
+    assert!(doc.operation_hash(Some("Q")).is_err());
+}
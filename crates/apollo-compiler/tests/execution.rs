@@ -0,0 +1,3821 @@
+use apollo_compiler::execution::execute_request;
+use apollo_compiler::execution::JsonMap;
+use apollo_compiler::execution::ResolvedValue;
+use apollo_compiler::execution::ResolverError;
+use apollo_compiler::impl_resolver;
+use apollo_compiler::validation::Valid;
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+use expect_test::Expect;
+
+const SCHEMA: &str = r#"
+    type Query {
+        hello(name: String = "World"): String!
+        user(id: ID!): User
+        users: [User!]!
+        node(id: ID!): Node
+        fail: Int
+    }
+
+    interface Node {
+        id: ID!
+    }
+
+    type User implements Node {
+        id: ID!
+        name: String!
+        friends: [User!]!
+        nonNullFail: Int!
+    }
+
+    type Post implements Node {
+        id: ID!
+        title: String
+    }
+"#;
+
+struct UserData {
+    id: &'static str,
+    name: &'static str,
+    friends: &'static [&'static str],
+}
+
+struct Db {
+    users: Vec<UserData>,
+}
+
+impl Db {
+    fn new() -> Self {
+        Self {
+            users: vec![
+                UserData {
+                    id: "1",
+                    name: "Alice",
+                    friends: &["2"],
+                },
+                UserData {
+                    id: "2",
+                    name: "Bob",
+                    friends: &["1", "3"],
+                },
+                UserData {
+                    id: "3",
+                    name: "Eve",
+                    friends: &[],
+                },
+            ],
+        }
+    }
+
+    fn user(&self, id: &str) -> Option<UserResolver<'_>> {
+        self.users
+            .iter()
+            .find(|user| user.id == id)
+            .map(|data| UserResolver { db: self, data })
+    }
+}
+
+struct QueryResolver<'a> {
+    db: &'a Db,
+}
+
+struct UserResolver<'a> {
+    db: &'a Db,
+    data: &'a UserData,
+}
+
+impl_resolver! {
+    for QueryResolver<'_>:
+
+    __typename = "Query";
+
+    fn hello(&_self, args) {
+        Ok(ResolvedValue::leaf(format!("Hello {}!", args["name"].as_str().unwrap())))
+    }
+
+    fn user(&self_, args) {
+        Ok(ResolvedValue::opt_object(self_.db.user(args["id"].as_str().unwrap())))
+    }
+
+    fn users(&self_) {
+        Ok(ResolvedValue::list(self_.db.users.iter().map(|data| {
+            ResolvedValue::object(UserResolver { db: self_.db, data })
+        })))
+    }
+
+    fn node(&self_, args) {
+        Ok(ResolvedValue::opt_object(self_.db.user(args["id"].as_str().unwrap())))
+    }
+
+    fn fail() {
+        Err(ResolverError::new("something went wrong"))
+    }
+}
+
+impl_resolver! {
+    for UserResolver<'_>:
+
+    __typename = "User";
+
+    fn id(&self_) {
+        Ok(ResolvedValue::leaf(self_.data.id))
+    }
+
+    fn name(&self_) {
+        Ok(ResolvedValue::leaf(self_.data.name))
+    }
+
+    fn friends(&self_) {
+        Ok(ResolvedValue::list(self_.data.friends.iter().map(|id| {
+            ResolvedValue::opt_object(self_.db.user(id))
+        })))
+    }
+
+    fn nonNullFail() {
+        Ok(ResolvedValue::null())
+    }
+}
+
+fn schema() -> Valid<Schema> {
+    Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap()
+}
+
+fn assert_execute(
+    query: &str,
+    operation_name: Option<&str>,
+    variables: serde_json::Value,
+    expected: Expect,
+) {
+    let schema = schema();
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let variables: JsonMap = serde_json::from_value(variables).unwrap();
+    let db = Db::new();
+    let root = QueryResolver { db: &db };
+    let response = execute_request(&schema, &document, operation_name, &variables, &root);
+    expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+}
+
+#[test]
+fn test_query() {
+    let query = r#"
+        query GetUsers($id: ID!) {
+            hello
+            greeting: hello(name: "Bob")
+            user(id: $id) {
+                ...UserFields
+                friends { name }
+            }
+            users { id }
+        }
+
+        fragment UserFields on User {
+            __typename
+            id
+            name
+        }
+    "#;
+    let expected = expect![[r#"
+        {
+          "data": {
+            "hello": "Hello World!",
+            "greeting": "Hello Bob!",
+            "user": {
+              "__typename": "User",
+              "id": "2",
+              "name": "Bob",
+              "friends": [
+                {
+                  "name": "Alice"
+                },
+                {
+                  "name": "Eve"
+                }
+              ]
+            },
+            "users": [
+              {
+                "id": "1"
+              },
+              {
+                "id": "2"
+              },
+              {
+                "id": "3"
+              }
+            ]
+          }
+        }"#]];
+    assert_execute(query, None, serde_json::json!({"id": "2"}), expected);
+}
+
+#[test]
+fn test_abstract_type() {
+    let query = r#"
+        {
+            node(id: "1") {
+                __typename
+                id
+                ... on User { name }
+                ... on Post { title }
+            }
+        }
+    "#;
+    let expected = expect![[r#"
+        {
+          "data": {
+            "node": {
+              "__typename": "User",
+              "id": "1",
+              "name": "Alice"
+            }
+          }
+        }"#]];
+    assert_execute(query, None, serde_json::json!({}), expected);
+}
+
+#[test]
+fn test_field_errors() {
+    let query = r#"
+        {
+            fail
+            user(id: "1") { name nonNullFail }
+            hello
+        }
+    "#;
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "resolver error: something went wrong",
+              "locations": [
+                {
+                  "line": 3,
+                  "column": 13
+                }
+              ],
+              "path": [
+                "fail"
+              ]
+            },
+            {
+              "message": "Non-null type Int! resolved to null",
+              "locations": [
+                {
+                  "line": 4,
+                  "column": 34
+                }
+              ],
+              "path": [
+                "user",
+                "nonNullFail"
+              ]
+            }
+          ],
+          "data": {
+            "fail": null,
+            "user": null,
+            "hello": "Hello World!"
+          }
+        }"#]];
+    assert_execute(query, None, serde_json::json!({}), expected);
+}
+
+#[test]
+fn test_wrong_object_type() {
+    let schema = Schema::parse_and_validate(
+        r#"
+            type Query { user: User, node: Node, animal: Animal }
+            interface Node { id: ID! }
+            type User implements Node { id: ID! }
+            type Pet { id: ID! }
+            union Animal = Pet
+        "#,
+        "schema.graphql",
+    )
+    .unwrap();
+    let query = "{ user { id } node { id } animal { __typename } }";
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+
+    /// Resolves every field to an object of the given type
+    struct Typed(&'static str);
+
+    impl apollo_compiler::execution::Resolver for Typed {
+        fn type_name(&self) -> &str {
+            self.0
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> Result<ResolvedValue<'a>, ResolverError> {
+            match field_name {
+                "user" | "node" => Ok(ResolvedValue::object(Typed("Pet"))),
+                "animal" => Ok(ResolvedValue::object(Typed("User"))),
+                _ => Ok(ResolvedValue::leaf("1")),
+            }
+        }
+    }
+
+    let response = execute_request(&schema, &document, None, &JsonMap::new(), &Typed("Query"));
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "Resolver returned an object of type Pet, expected User",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 3
+                }
+              ],
+              "path": [
+                "user"
+              ]
+            },
+            {
+              "message": "Resolver returned an object of type Pet, which is not a possible type of Node",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 15
+                }
+              ],
+              "path": [
+                "node"
+              ]
+            },
+            {
+              "message": "Resolver returned an object of type User, which is not a possible type of Animal",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 27
+                }
+              ],
+              "path": [
+                "animal"
+              ]
+            }
+          ],
+          "data": {
+            "user": null,
+            "node": null,
+            "animal": null
+          }
+        }"#]];
+    expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+}
+
+#[test]
+fn test_with_introspection() {
+    let query = r#"
+        {
+            __typename
+            hello
+            __type(name: "Node") {
+                possibleTypes { name }
+            }
+        }
+    "#;
+    let expected = expect![[r#"
+        {
+          "data": {
+            "__typename": "Query",
+            "hello": "Hello World!",
+            "__type": {
+              "possibleTypes": [
+                {
+                  "name": "User"
+                },
+                {
+                  "name": "Post"
+                }
+              ]
+            }
+          }
+        }"#]];
+    assert_execute(query, None, serde_json::json!({}), expected);
+}
+
+#[test]
+fn test_request_errors() {
+    let query = r#"
+        query A($id: ID!) { user(id: $id) { name } }
+        query B { hello }
+    "#;
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "An operation name is required when the document does not contain exactly one operation"
+            }
+          ]
+        }"#]];
+    assert_execute(query, None, serde_json::json!({}), expected);
+
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "No operation named 'C'"
+            }
+          ]
+        }"#]];
+    assert_execute(query, Some("C"), serde_json::json!({}), expected);
+
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "missing value for non-null variable 'id'",
+              "locations": [
+                {
+                  "line": 2,
+                  "column": 17
+                }
+              ]
+            }
+          ]
+        }"#]];
+    assert_execute(query, Some("A"), serde_json::json!({}), expected);
+}
+
+mod one_of {
+    use apollo_compiler::execution::coerce_variable_values;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            pet(by: PetBy!): String
+        }
+
+        input PetBy @oneOf {
+            id: ID
+            name: String
+        }
+    "#;
+
+    fn coerce(variables: serde_json::Value) -> Result<JsonMap, String> {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let query = "query($by: PetBy!) { pet(by: $by) }";
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let operation = document.operations.get(None).unwrap();
+        let variables: JsonMap = serde_json::from_value(variables).unwrap();
+        coerce_variable_values(&schema, operation, &variables)
+            .map(|valid| valid.into_inner())
+            .map_err(|err| err.into_graphql_error(&document.sources).message)
+    }
+
+    #[test]
+    fn variable_values() {
+        let coerced = coerce(serde_json::json!({"by": {"name": "Fido"}})).unwrap();
+        assert_eq!(
+            serde_json::to_value(&coerced["by"]).unwrap(),
+            serde_json::json!({"name": "Fido"})
+        );
+
+        let err = coerce(serde_json::json!({"by": {}})).unwrap_err();
+        assert_eq!(
+            err,
+            "OneOf input object PetBy must specify exactly one key, found 0"
+        );
+        let err = coerce(serde_json::json!({"by": {"id": 1, "name": "Fido"}})).unwrap_err();
+        assert_eq!(
+            err,
+            "OneOf input object PetBy must specify exactly one key, found 2"
+        );
+        let err = coerce(serde_json::json!({"by": {"name": null}})).unwrap_err();
+        assert_eq!(err, "null value for field PetBy.name of OneOf input object");
+    }
+}
+
+mod scalar_coercion {
+    use apollo_compiler::execution::CustomScalar;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::JsonValue;
+    use apollo_compiler::execution::ResolvedValue;
+    use apollo_compiler::execution::ScalarCoercion;
+    use apollo_compiler::impl_resolver;
+    use apollo_compiler::name;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Node;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            big(value: BigInt): BigInt
+            defaultBig(value: BigInt = 123456789012345678901234567890): BigInt
+            uuid(value: UUID): UUID
+            badUuid: UUID
+            json(value: JSON): JSON
+        }
+
+        scalar BigInt
+        scalar UUID
+        scalar JSON
+    "#;
+
+    /// Represented as a string of decimal digits in JSON
+    struct BigInt;
+
+    impl CustomScalar for BigInt {
+        fn coerce_variable_value(&self, value: &JsonValue) -> Result<JsonValue, String> {
+            match value {
+                JsonValue::Number(number) if number.is_i64() || number.is_u64() => {
+                    Ok(number.to_string().into())
+                }
+                JsonValue::String(str) if str.as_str().chars().all(|c| c.is_ascii_digit()) => {
+                    Ok(value.clone())
+                }
+                _ => Err("expected an integer or a string of digits".into()),
+            }
+        }
+
+        fn coerce_literal(
+            &self,
+            value: &Node<apollo_compiler::ast::Value>,
+        ) -> Result<JsonValue, String> {
+            match value.as_ref() {
+                // Keep all digits, even beyond 64 bits
+                apollo_compiler::ast::Value::Int(int) => Ok(int.as_str().into()),
+                _ => Err("expected an integer".into()),
+            }
+        }
+
+        fn coerce_result(&self, value: &JsonValue) -> Result<JsonValue, String> {
+            self.coerce_variable_value(value)
+        }
+    }
+
+    struct Uuid;
+
+    impl CustomScalar for Uuid {
+        fn coerce_variable_value(&self, value: &JsonValue) -> Result<JsonValue, String> {
+            match value.as_str() {
+                Some(str) if str.len() == 36 => Ok(str.to_ascii_lowercase().into()),
+                _ => Err("expected a UUID string".into()),
+            }
+        }
+
+        fn coerce_result(&self, value: &JsonValue) -> Result<JsonValue, String> {
+            self.coerce_variable_value(value)
+        }
+    }
+
+    struct QueryResolver;
+
+    impl_resolver! {
+        for QueryResolver:
+
+        __typename = "Query";
+
+        fn big(&_self, args) {
+            Ok(ResolvedValue::leaf(args["value"].clone()))
+        }
+
+        fn defaultBig(&_self, args) {
+            Ok(ResolvedValue::leaf(args["value"].clone()))
+        }
+
+        fn uuid(&_self, args) {
+            Ok(ResolvedValue::leaf(args["value"].clone()))
+        }
+
+        fn badUuid() {
+            Ok(ResolvedValue::leaf("nope"))
+        }
+
+        fn json(&_self, args) {
+            Ok(ResolvedValue::leaf(args["value"].clone()))
+        }
+    }
+
+    fn assert_execute(query: &str, variables: serde_json::Value, expected: Expect) {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let variables = serde_json::from_value(variables).unwrap();
+        let mut scalars = ScalarCoercion::new();
+        scalars.insert(name!("BigInt"), BigInt);
+        scalars.insert(name!("UUID"), Uuid);
+        let executor = Executor::new().scalar_coercion(scalars);
+        let response =
+            executor.execute_request(&schema, &document, None, &variables, &QueryResolver);
+        expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+    }
+
+    #[test]
+    fn variables() {
+        let query = r#"
+            query($big: BigInt, $uuid: UUID, $json: JSON) {
+                big(value: $big)
+                uuid(value: $uuid)
+                json(value: $json)
+            }
+        "#;
+        let variables = serde_json::json!({
+            "big": 42,
+            "uuid": "1B4E28BA-2FA1-11D2-883F-0016D3CCA427",
+            "json": {"anything": [true]},
+        });
+        let expected = expect![[r#"
+            {
+              "data": {
+                "big": "42",
+                "uuid": "1b4e28ba-2fa1-11d2-883f-0016d3cca427",
+                "json": {
+                  "anything": [
+                    true
+                  ]
+                }
+              }
+            }"#]];
+        assert_execute(query, variables, expected);
+
+        let variables = serde_json::json!({"big": "forty-two"});
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Could not coerce variable big: \"forty-two\" to type BigInt: expected an integer or a string of digits",
+                  "locations": [
+                    {
+                      "line": 2,
+                      "column": 19
+                    }
+                  ]
+                }
+              ]
+            }"#]];
+        assert_execute(query, variables, expected);
+    }
+
+    #[test]
+    fn literals() {
+        let query = r#"
+            {
+                big(value: 123456789012345678901234567890)
+                uuid(value: "1B4E28BA-2FA1-11D2-883F-0016D3CCA427")
+                json(value: { anything: [true] })
+            }
+        "#;
+        let expected = expect![[r#"
+            {
+              "data": {
+                "big": "123456789012345678901234567890",
+                "uuid": "1b4e28ba-2fa1-11d2-883f-0016d3cca427",
+                "json": {
+                  "anything": [
+                    true
+                  ]
+                }
+              }
+            }"#]];
+        assert_execute(query, serde_json::json!({}), expected);
+
+        let query = r#"
+            {
+                big(value: "42")
+                uuid(value: "1B4E28BA")
+            }
+        "#;
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Could not coerce argument value: \"42\" to type BigInt: expected an integer",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 28
+                    }
+                  ],
+                  "path": [
+                    "big"
+                  ]
+                },
+                {
+                  "message": "Could not coerce argument value: \"1B4E28BA\" to type UUID: expected a UUID string",
+                  "locations": [
+                    {
+                      "line": 4,
+                      "column": 29
+                    }
+                  ],
+                  "path": [
+                    "uuid"
+                  ]
+                }
+              ],
+              "data": {
+                "big": null,
+                "uuid": null
+              }
+            }"#]];
+        assert_execute(query, serde_json::json!({}), expected);
+    }
+
+    #[test]
+    fn default_values() {
+        let query = r#"
+            query($big: BigInt = 98765432109876543210987654321) {
+                big(value: $big)
+                defaultBig
+            }
+        "#;
+        let expected = expect![[r#"
+            {
+              "data": {
+                "big": "98765432109876543210987654321",
+                "defaultBig": "123456789012345678901234567890"
+              }
+            }"#]];
+        assert_execute(query, serde_json::json!({}), expected);
+
+        let query = r#"
+            query($big: BigInt = "forty-two") {
+                big(value: $big)
+            }
+        "#;
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Could not coerce variable default value big: \"forty-two\" to type BigInt: expected an integer",
+                  "locations": [
+                    {
+                      "line": 2,
+                      "column": 34
+                    }
+                  ]
+                }
+              ]
+            }"#]];
+        assert_execute(query, serde_json::json!({}), expected);
+    }
+
+    #[test]
+    fn results() {
+        let query = r#"
+            {
+                badUuid
+            }
+        "#;
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Resolver returned \"nope\", expected UUID: expected a UUID string",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "badUuid"
+                  ]
+                }
+              ],
+              "data": {
+                "badUuid": null
+              }
+            }"#]];
+        assert_execute(query, serde_json::json!({}), expected);
+    }
+}
+
+mod json_root {
+    use super::schema;
+    use apollo_compiler::execution::execute_request_with_json_root;
+    use apollo_compiler::ExecutableDocument;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    fn assert_execute(query: &str, data: serde_json::Value, expected: Expect) {
+        let schema = schema();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let data = serde_json::from_value(data).unwrap();
+        let response =
+            execute_request_with_json_root(&schema, &document, None, &Default::default(), &data);
+        expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+    }
+
+    #[test]
+    fn test_fields_and_aliases() {
+        let query = r#"
+            query {
+                greeting: hello(name: "ignored")
+                users { id ...Name }
+                user(id: "1") { name friends { id } }
+            }
+            fragment Name on User { name n: name }
+        "#;
+        let data = serde_json::json!({
+            "hello": "Hello!",
+            "users": [
+                {"id": "1", "name": "Alice"},
+                {"id": "2", "name": "Bob"},
+            ],
+            "user": {"name": "Alice", "friends": [{"id": "2", "name": "Bob"}]},
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "data": {
+                "greeting": "Hello!",
+                "users": [
+                  {
+                    "id": "1",
+                    "name": "Alice",
+                    "n": "Alice"
+                  },
+                  {
+                    "id": "2",
+                    "name": "Bob",
+                    "n": "Bob"
+                  }
+                ],
+                "user": {
+                  "name": "Alice",
+                  "friends": [
+                    {
+                      "id": "2"
+                    }
+                  ]
+                }
+              }
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_abstract_type() {
+        let query = r#"
+            query {
+                node(id: "1") { __typename id ... on User { name } }
+            }
+        "#;
+        let data = serde_json::json!({
+            "node": {"__typename": "User", "id": "1", "name": "Alice"},
+        });
+        assert_execute(
+            query,
+            data.clone(),
+            expect![[r#"
+            {
+              "data": {
+                "node": {
+                  "__typename": "User",
+                  "id": "1",
+                  "name": "Alice"
+                }
+              }
+            }"#]],
+        );
+
+        let data = serde_json::json!({
+            "node": {"id": "1", "name": "Alice"},
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "resolver error: JSON data for abstract type Node has no __typename string",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "node"
+                  ]
+                }
+              ],
+              "data": {
+                "node": null
+              }
+            }"#]],
+        );
+
+        // Query is an object type in the schema, but does not implement Node
+        let data = serde_json::json!({
+            "node": {"__typename": "Query", "id": "1"},
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Resolver returned an object of type Query, which is not a possible type of Node",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "node"
+                  ]
+                }
+              ],
+              "data": {
+                "node": null
+              }
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_null_propagation() {
+        let query = r#"
+            query {
+                hello
+                user(id: "1") { id name }
+                fail
+            }
+        "#;
+        let data = serde_json::json!({
+            "hello": "Hello!",
+            "user": {"id": "1", "name": null},
+            "fail": "not an Int",
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Non-null type String! resolved to null",
+                  "locations": [
+                    {
+                      "line": 4,
+                      "column": 36
+                    }
+                  ],
+                  "path": [
+                    "user",
+                    "name"
+                  ]
+                },
+                {
+                  "message": "Resolver returned \"not an Int\", expected Int",
+                  "locations": [
+                    {
+                      "line": 5,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "fail"
+                  ]
+                }
+              ],
+              "data": {
+                "hello": "Hello!",
+                "user": null,
+                "fail": null
+              }
+            }"#]],
+        );
+
+        let data = serde_json::json!({
+            "hello": null,
+            "user": null,
+            "fail": 1,
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Non-null type String! resolved to null",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "hello"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let query = r#"
+            query {
+                users { id name }
+            }
+        "#;
+        let data = serde_json::json!({
+            "users": [{"id": "1"}],
+        });
+        assert_execute(
+            query,
+            data,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "resolver error: JSON data for type User has no key name",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 28
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    0,
+                    "name"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+}
+
+mod response_validation {
+    use super::schema;
+    use apollo_compiler::execution::validate_response_data;
+    use apollo_compiler::execution::ResponseDataPathElement;
+    use apollo_compiler::execution::ResponseShapeErrorKind;
+    use apollo_compiler::validation::Valid;
+    use apollo_compiler::ExecutableDocument;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    fn assert_validate(query: &str, data: serde_json::Value, expected: Expect) {
+        let schema = schema();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let operation = document.operations.get(None).unwrap();
+        let variables = Valid::assume_valid(Default::default());
+        let data = serde_json::from_value(data).unwrap();
+        let errors = validate_response_data(&schema, &document, operation, &variables, &data);
+        let errors = errors
+            .iter()
+            .map(|error| format!("{error}\n"))
+            .collect::<String>();
+        expected.assert_eq(&errors);
+    }
+
+    const QUERY: &str = r#"
+        query {
+            greeting: hello
+            users { id ... on User { name } }
+            node(id: "1") { id ... on User { friends { name } } }
+        }
+    "#;
+
+    #[test]
+    fn test_valid() {
+        let data = serde_json::json!({
+            "greeting": "Hello!",
+            "users": [{"id": "1", "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "node": {"id": "1", "friends": []},
+        });
+        assert_validate(QUERY, data, expect![[""]]);
+
+        let data = serde_json::json!({
+            "greeting": "Hello!",
+            "users": [],
+            "node": null,
+        });
+        assert_validate(QUERY, data, expect![[""]]);
+    }
+
+    #[test]
+    fn test_keys() {
+        let data = serde_json::json!({
+            "hello": "Hello!",
+            "users": [{"id": "1"}],
+            "node": {"id": "1", "friends": [{"name": "Bob", "id": "2"}]},
+        });
+        assert_validate(
+            QUERY,
+            data,
+            expect![[r#"
+            missing key at path greeting
+            missing key at path users.0.name
+            unexpected key at path node.friends.0.id
+            unexpected key at path hello
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_types_and_nulls() {
+        let data = serde_json::json!({
+            "greeting": null,
+            "users": [{"id": 1.5, "name": ["Alice"]}, null],
+            "node": {"id": "1", "friends": {"name": "Bob"}},
+        });
+        assert_validate(
+            QUERY,
+            data,
+            expect![[r#"
+            null value for non-null type String! at path greeting
+            expected type ID!, found 1.5 at path users.0.id
+            expected type String!, found ["Alice"] at path users.0.name
+            null value for non-null type User! at path users.1
+            expected type [User!]!, found {"name":"Bob"} at path node.friends
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_typename() {
+        let query = r#"
+            query {
+                node(id: "1") { __typename ... on User { name } }
+            }
+        "#;
+        let data = serde_json::json!({
+            "node": {"__typename": "User", "name": "Alice"},
+        });
+        assert_validate(query, data, expect![[""]]);
+
+        let data = serde_json::json!({
+            "node": {"__typename": "Post", "name": "Alice"},
+        });
+        assert_validate(
+            query,
+            data,
+            expect![[r#"
+            unexpected key at path node.name
+        "#]],
+        );
+
+        let data = serde_json::json!({
+            "node": {"__typename": "Comment"},
+        });
+        assert_validate(
+            query,
+            data,
+            expect![[r#"
+            expected type String!, found "Comment": expected __typename Post at path node.__typename
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_float_accepts_integers() {
+        let schema = apollo_compiler::Schema::parse_and_validate(
+            "type Query { score: Float, scores: [Float] }",
+            "schema.graphql",
+        )
+        .unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, "{ score scores }", "query.graphql")
+                .unwrap();
+        let operation = document.operations.get(None).unwrap();
+        let variables = Valid::assume_valid(Default::default());
+        let data = serde_json::json!({"score": 2, "scores": [1.5, -3, 4]});
+        let data = serde_json::from_value(data).unwrap();
+        let errors = validate_response_data(&schema, &document, operation, &variables, &data);
+        assert!(errors.is_empty());
+
+        let data = serde_json::json!({"score": "2", "scores": [true]});
+        let data = serde_json::from_value(data).unwrap();
+        let errors = validate_response_data(&schema, &document, operation, &variables, &data);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_enum_and_location() {
+        let schema = apollo_compiler::Schema::parse_and_validate(
+            r#"
+                type Query { color: Color! }
+                enum Color { RED GREEN }
+            "#,
+            "schema.graphql",
+        )
+        .unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, "{ color }", "query.graphql").unwrap();
+        let operation = document.operations.get(None).unwrap();
+        let variables = Valid::assume_valid(Default::default());
+        let data = serde_json::from_value(serde_json::json!({"color": "BLUE"})).unwrap();
+        let errors = validate_response_data(&schema, &document, operation, &variables, &data);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ResponseShapeErrorKind::InvalidEnumValue { .. }
+        ));
+        assert_eq!(
+            errors[0].path,
+            [ResponseDataPathElement::Field(apollo_compiler::name!(
+                "color"
+            ))]
+        );
+        let location = errors[0]
+            .location
+            .unwrap()
+            .line_column_range(&document.sources);
+        assert_eq!(location.unwrap().start.column, 3);
+    }
+}
+
+mod middleware {
+    use super::Db;
+    use super::QueryResolver;
+    use super::SCHEMA;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::FieldContext;
+    use apollo_compiler::execution::FieldMiddleware;
+    use apollo_compiler::execution::GraphQLError;
+    use apollo_compiler::execution::JsonValue;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    const DIRECTIVES: &str = r#"
+        directive @uppercase on FIELD
+        directive @mask(with: String = "***") on FIELD
+    "#;
+
+    struct Uppercase;
+
+    impl FieldMiddleware for Uppercase {
+        fn after_field(
+            &self,
+            context: &FieldContext<'_>,
+            value: JsonValue,
+        ) -> Result<JsonValue, ResolverError> {
+            match value.as_str() {
+                Some(str) if context.directives().has("uppercase") => Ok(str.to_uppercase().into()),
+                _ => Ok(value),
+            }
+        }
+    }
+
+    struct Mask;
+
+    impl FieldMiddleware for Mask {
+        fn before_field(
+            &self,
+            context: &FieldContext<'_>,
+        ) -> Option<Result<JsonValue, ResolverError>> {
+            let directive = context.directives().get("mask")?;
+            let with = directive.specified_argument_by_name("with");
+            let with = with.and_then(|value| value.as_str()).unwrap_or("***");
+            Some(Ok(with.into()))
+        }
+    }
+
+    /// Denies `Query.fail`, and adds an error code to field errors
+    struct Auth;
+
+    impl FieldMiddleware for Auth {
+        fn before_field(
+            &self,
+            context: &FieldContext<'_>,
+        ) -> Option<Result<JsonValue, ResolverError>> {
+            if context.parent_type == "Query" && context.field.name == "fail" {
+                Some(Err(ResolverError::new("unauthorized")))
+            } else {
+                None
+            }
+        }
+
+        fn on_field_error(&self, _context: &FieldContext<'_>, error: &mut GraphQLError) {
+            error.extensions.insert("code", "FIELD_ERROR".into());
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Log(Arc<Mutex<Vec<String>>>);
+
+    impl FieldMiddleware for Log {
+        fn before_field(
+            &self,
+            context: &FieldContext<'_>,
+        ) -> Option<Result<JsonValue, ResolverError>> {
+            let path = serde_json::to_string(&context.path).unwrap();
+            let arguments = serde_json::to_string(context.arguments).unwrap();
+            self.0.lock().unwrap().push(format!(
+                "{}.{} {path} {arguments}",
+                context.parent_type, context.field.name
+            ));
+            None
+        }
+    }
+
+    #[test]
+    fn test_middleware() {
+        let schema =
+            Schema::parse_and_validate(format!("{SCHEMA}{DIRECTIVES}"), "schema.graphql").unwrap();
+        let query = r#"
+            query {
+                hello(name: "middleware") @uppercase
+                user(id: "2") {
+                    name @mask
+                    other: name @mask(with: "???")
+                    friends { id name @uppercase }
+                }
+                fail
+            }
+        "#;
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let log = Log::default();
+        let executor = Executor::new()
+            .field_middleware(log.clone())
+            .field_middleware(Auth)
+            .field_middleware(Mask)
+            .field_middleware(Uppercase);
+        let db = Db::new();
+        let root = QueryResolver { db: &db };
+        let response =
+            executor.execute_request(&schema, &document, None, &Default::default(), &root);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "unauthorized",
+                  "locations": [
+                    {
+                      "line": 9,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "fail"
+                  ],
+                  "extensions": {
+                    "code": "FIELD_ERROR"
+                  }
+                }
+              ],
+              "data": {
+                "hello": "HELLO MIDDLEWARE!",
+                "user": {
+                  "name": "***",
+                  "other": "???",
+                  "friends": [
+                    {
+                      "id": "1",
+                      "name": "ALICE"
+                    },
+                    {
+                      "id": "3",
+                      "name": "EVE"
+                    }
+                  ]
+                },
+                "fail": null
+              }
+            }"#]];
+        expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+        let expected = expect![[r#"
+            Query.hello ["hello"] {"name":"middleware"}
+            Query.user ["user"] {"id":"2"}
+            User.name ["user","name"] {}
+            User.name ["user","other"] {}
+            User.friends ["user","friends"] {}
+            User.id ["user","friends",0,"id"] {}
+            User.name ["user","friends",0,"name"] {}
+            User.id ["user","friends",1,"id"] {}
+            User.name ["user","friends",1,"name"] {}
+            Query.fail ["fail"] {}"#]];
+        expected.assert_eq(&log.0.lock().unwrap().join("\n"));
+    }
+}
+
+mod tracing {
+    use super::schema;
+    use super::Db;
+    use super::QueryResolver;
+    use apollo_compiler::execution::ExecutionTrace;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::FieldTrace;
+    use apollo_compiler::ExecutableDocument;
+    use expect_test::expect;
+
+    fn format_fields(fields: &[FieldTrace], parent: Option<&FieldTrace>, indent: usize) -> String {
+        let mut out = String::new();
+        for field in fields {
+            assert!(field.start_offset_nano <= field.end_offset_nano);
+            if let Some(parent) = parent {
+                assert!(parent.start_offset_nano <= field.start_offset_nano);
+                assert!(field.end_offset_nano <= parent.end_offset_nano);
+            }
+            out += &format!(
+                "{:indent$}{} {}.{}: {}{}\n",
+                "",
+                serde_json::to_string(&field.path).unwrap(),
+                field.parent_type,
+                field.field_name,
+                field.return_type,
+                field
+                    .errors
+                    .iter()
+                    .map(|error| format!(" ({})", error.message))
+                    .collect::<String>(),
+            );
+            out += &format_fields(&field.children, Some(field), indent + 2);
+        }
+        out
+    }
+
+    #[test]
+    fn test_field_tree() {
+        let schema = schema();
+        let query = r#"
+            query {
+                __typename
+                user(id: "2") {
+                    name
+                    friends { id }
+                }
+                fail
+            }
+        "#;
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let db = Db::new();
+        let root = QueryResolver { db: &db };
+        let executor = Executor::new().tracing(true);
+        let response =
+            executor.execute_request(&schema, &document, None, &Default::default(), &root);
+        let trace = ExecutionTrace::from_response(&response).unwrap();
+        assert!(trace.start_time_unix_nano > 0);
+        assert!(trace
+            .fields
+            .iter()
+            .all(|field| field.end_offset_nano <= trace.duration_nano));
+        let expected = expect![[r#"
+            ["user"] Query.user: User
+              ["user","name"] User.name: String!
+              ["user","friends"] User.friends: [User!]!
+                ["user","friends",0,"id"] User.id: ID!
+                ["user","friends",1,"id"] User.id: ID!
+            ["fail"] Query.fail: Int (resolver error: something went wrong)
+        "#]];
+        expected.assert_eq(&format_fields(&trace.fields, None, 0));
+
+        let response =
+            Executor::new().execute_request(&schema, &document, None, &Default::default(), &root);
+        assert!(response.extensions.is_empty());
+    }
+}
+
+mod limits {
+    use super::schema;
+    use super::Db;
+    use super::QueryResolver;
+    use apollo_compiler::execution::CancellationToken;
+    use apollo_compiler::execution::ExecutionLimits;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::ExecutableDocument;
+    use expect_test::expect;
+    use expect_test::Expect;
+    use std::time::Duration;
+
+    const QUERY: &str = r#"
+        query {
+            users {
+                name
+                friends { id }
+            }
+        }
+    "#;
+
+    fn assert_execute(executor: Executor, expected: Expect) {
+        let schema = schema();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, QUERY, "query.graphql").unwrap();
+        let db = Db::new();
+        let root = QueryResolver { db: &db };
+        let response =
+            executor.execute_request(&schema, &document, None, &Default::default(), &root);
+        expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+    }
+
+    #[test]
+    fn test_no_limit_exceeded() {
+        let limits = ExecutionLimits::new()
+            .max_resolved_fields(100)
+            .max_response_nodes(100)
+            .max_list_length(3)
+            .timeout(Duration::from_secs(3600));
+        let executor = Executor::new()
+            .limits(limits)
+            .cancellation_token(CancellationToken::new());
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "data": {
+                "users": [
+                  {
+                    "name": "Alice",
+                    "friends": [
+                      {
+                        "id": "2"
+                      }
+                    ]
+                  },
+                  {
+                    "name": "Bob",
+                    "friends": [
+                      {
+                        "id": "1"
+                      },
+                      {
+                        "id": "3"
+                      }
+                    ]
+                  },
+                  {
+                    "name": "Eve",
+                    "friends": []
+                  }
+                ]
+              }
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_max_resolved_fields() {
+        let executor = Executor::new().limits(ExecutionLimits::new().max_resolved_fields(4));
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum number of resolved fields exceeded",
+                  "locations": [
+                    {
+                      "line": 4,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    1,
+                    "name"
+                  ],
+                  "extensions": {
+                    "code": "MAX_RESOLVED_FIELDS_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_max_response_nodes() {
+        let executor = Executor::new().limits(ExecutionLimits::new().max_response_nodes(5));
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum response size exceeded",
+                  "locations": [
+                    {
+                      "line": 5,
+                      "column": 27
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    0,
+                    "friends",
+                    0,
+                    "id"
+                  ],
+                  "extensions": {
+                    "code": "MAX_RESPONSE_NODES_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_max_list_length() {
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(2));
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum list length exceeded",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 13
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    2
+                  ],
+                  "extensions": {
+                    "code": "MAX_LIST_LENGTH_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let executor = Executor::new().limits(ExecutionLimits::new().timeout(Duration::ZERO));
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Execution timed out",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 13
+                    }
+                  ],
+                  "path": [
+                    "users"
+                  ],
+                  "extensions": {
+                    "code": "EXECUTION_TIMEOUT"
+                  }
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let token = CancellationToken::new();
+        let executor = Executor::new().cancellation_token(token.clone());
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_execute(
+            executor,
+            expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Execution was cancelled",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 13
+                    }
+                  ],
+                  "path": [
+                    "users"
+                  ],
+                  "extensions": {
+                    "code": "EXECUTION_CANCELLED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]],
+        );
+    }
+}
+
+mod request {
+    use super::schema;
+    use super::Db;
+    use super::QueryResolver;
+    use apollo_compiler::execution::execute_request;
+    use apollo_compiler::execution::BatchRequest;
+    use apollo_compiler::execution::Request;
+    use apollo_compiler::execution::RequestError;
+    use apollo_compiler::ExecutableDocument;
+    use expect_test::expect;
+
+    #[test]
+    fn test_post() {
+        let body = br#"{
+            "query": "query($id: ID!) { user(id: $id) { name } }",
+            "operationName": null,
+            "variables": {"id": "2"},
+            "extensions": {"persistedQuery": {"version": 1}},
+            "unknown": true
+        }"#;
+        let request = Request::from_json(body).unwrap();
+        assert_eq!(request.operation_name, None);
+        assert_eq!(request.extensions["persistedQuery"]["version"], 1);
+
+        let schema = schema();
+        let document = ExecutableDocument::parse_and_validate(
+            &schema,
+            request.query.as_ref().unwrap(),
+            "request.graphql",
+        )
+        .unwrap();
+        let db = Db::new();
+        let root = QueryResolver { db: &db };
+        let response = execute_request(
+            &schema,
+            &document,
+            request.operation_name.as_deref(),
+            &request.variables,
+            &root,
+        );
+        let expected = expect![[r#"{"data":{"user":{"name":"Bob"}}}"#]];
+        expected.assert_eq(&serde_json::to_string(&response).unwrap());
+
+        let expected = expect![[
+            r#"{"query":"query($id: ID!) { user(id: $id) { name } }","variables":{"id":"2"},"extensions":{"persistedQuery":{"version":1}}}"#
+        ]];
+        expected.assert_eq(&serde_json::to_string(&request).unwrap());
+        let roundtrip: Request =
+            serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(roundtrip, request);
+    }
+
+    #[test]
+    fn test_batch() {
+        let body = br#"[{"query": "{ a }"}, {"query": "{ b }", "operationName": "B"}]"#;
+        let batch = BatchRequest::from_json(body).unwrap();
+        assert!(batch.is_batch());
+        let requests = batch.into_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].query.as_deref(), Some("{ a }"));
+        assert_eq!(requests[1].operation_name.as_deref(), Some("B"));
+
+        let batch = BatchRequest::from_json(br#"{"query": "{ a }"}"#).unwrap();
+        assert!(!batch.is_batch());
+        assert_eq!(batch.into_requests(), [Request::new("{ a }")]);
+    }
+
+    #[test]
+    fn test_get() {
+        let request = Request::from_query_string(
+            "query=query+Q(%24id%3A+ID!)+%7B+user(id%3A+%24id)+%7B+name+%7D+%7D\
+             &operationName=Q&variables=%7B%22id%22%3A%221%22%7D&other=%zz",
+        )
+        .unwrap();
+        let expected = expect![[r#"
+            Request {
+                query: Some(
+                    "query Q($id: ID!) { user(id: $id) { name } }",
+                ),
+                operation_name: Some(
+                    "Q",
+                ),
+                document_id: None,
+                variables: {
+                    "id": String(
+                        "1",
+                    ),
+                },
+                extensions: {},
+            }
+        "#]];
+        expected.assert_debug_eq(&request);
+
+        let schema = schema();
+        let document = ExecutableDocument::parse_and_validate(
+            &schema,
+            request.query.as_ref().unwrap(),
+            "request.graphql",
+        )
+        .unwrap();
+        assert_eq!(request.check_http_get(&document), Ok(()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = [
+            BatchRequest::from_json(b"{").unwrap_err(),
+            BatchRequest::from_json(b"[]").unwrap_err(),
+            BatchRequest::from_json(br#"[{"query": "{ a }"}, 1]"#).unwrap_err(),
+            Request::from_json(br#"[{"query": "{ a }"}]"#).unwrap_err(),
+            Request::from_json(br#"{"operationName": "A"}"#).unwrap_err(),
+            Request::from_json(br#"{"extensions": {"persistedQuery": {"version": 1}}}"#)
+                .unwrap_err(),
+            Request::from_json(br#"{"documentId": 1}"#).unwrap_err(),
+            Request::from_json(br#"{"query": 1}"#).unwrap_err(),
+            Request::from_json(br#"{"query": "{ a }", "variables": []}"#).unwrap_err(),
+            Request::from_query_string("query=%7B+a+%7D&variables=%7B").unwrap_err(),
+            Request::from_query_string("operationName=A").unwrap_err(),
+        ];
+        let formatted = errors
+            .iter()
+            .map(|error| format!("{} {error}\n", error.status_code()))
+            .collect::<String>();
+        let expected = expect![[r#"
+            400 invalid JSON in request body: EOF while parsing an object at line 1 column 1
+            400 request body must be a JSON object or a non-empty array of objects
+            400 request body must be a JSON object or a non-empty array of objects
+            400 request body must be a JSON object or a non-empty array of objects
+            400 missing `query` parameter
+            400 missing `query` parameter
+            400 invalid `documentId` parameter: expected a string
+            400 invalid `query` parameter: expected a string
+            400 invalid `variables` parameter: expected an object
+            400 invalid `variables` parameter: expected a JSON-encoded object
+            400 missing `query` parameter
+        "#]];
+        expected.assert_eq(&formatted);
+
+        let response = errors.into_iter().next().unwrap().into_response();
+        let expected = expect![[
+            r#"{"errors":[{"message":"invalid JSON in request body: EOF while parsing an object at line 1 column 1"}]}"#
+        ]];
+        expected.assert_eq(&serde_json::to_string(&response).unwrap());
+    }
+
+    #[test]
+    fn test_mutation_over_get() {
+        let schema = apollo_compiler::Schema::parse_and_validate(
+            "type Query { a: Int } type Mutation { b: Int }",
+            "schema.graphql",
+        )
+        .unwrap();
+        let mut request =
+            Request::from_query_string("query=query+A+%7B+a+%7D+mutation+B+%7B+b+%7D").unwrap();
+        let document = ExecutableDocument::parse_and_validate(
+            &schema,
+            request.query.as_ref().unwrap(),
+            "request.graphql",
+        )
+        .unwrap();
+        // Operation not found is reported during execution
+        assert_eq!(request.check_http_get(&document), Ok(()));
+
+        request.operation_name = Some("A".into());
+        assert_eq!(request.check_http_get(&document), Ok(()));
+
+        request.operation_name = Some("B".into());
+        let error = request.check_http_get(&document).unwrap_err();
+        assert!(matches!(
+            error,
+            RequestError::OperationNotAllowedOverGet { .. }
+        ));
+        assert_eq!(error.status_code(), 405);
+        let expected =
+            expect!["mutation operations are not allowed in GET requests, use POST instead"];
+        expected.assert_eq(&error.to_string());
+    }
+}
+
+mod persisted_queries {
+    use apollo_compiler::execution::persisted_query_id;
+    use apollo_compiler::execution::PersistedQueryManifest;
+    use apollo_compiler::execution::Request;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+
+    #[test]
+    fn test_manifests() {
+        let schema =
+            Schema::parse_and_validate("type Query { a: Int b: Int }", "schema.graphql").unwrap();
+        let apollo = r#"{
+            "format": "apollo-persisted-query-manifest",
+            "version": 1,
+            "operations": [
+                {"id": "1", "name": "A", "type": "query", "body": "query A { a }"},
+                {"id": "2", "name": "B", "type": "query", "body": "query B { b }"}
+            ]
+        }"#;
+        let manifest =
+            PersistedQueryManifest::from_apollo_json(&schema, apollo.as_bytes()).unwrap();
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(manifest.get("2").unwrap().name.as_deref(), Some("B"));
+
+        let relay = r#"{"1": "query A { a }", "2": "query B { b }"}"#;
+        let manifest = PersistedQueryManifest::from_relay_json(&schema, relay.as_bytes()).unwrap();
+        let ids = manifest.iter().map(|entry| &entry.id).collect::<Vec<_>>();
+        assert_eq!(ids, ["1", "2"]);
+
+        let invalid = r#"{
+            "format": "apollo-persisted-query-manifest",
+            "version": 1,
+            "operations": [
+                {"id": "1", "body": "query A { a }"},
+                {"id": "2", "body": "query B { c }"},
+                {"id": "1", "body": "query C { b }"}
+            ]
+        }"#;
+        let errors = PersistedQueryManifest::from_apollo_json(&schema, invalid.as_bytes())
+            .unwrap_err()
+            .iter()
+            .map(|error| format!("{error}\n"))
+            .collect::<String>();
+        expect![[r#"
+            invalid persisted query `2`:
+            Error: type `Query` does not have a field `c`
+               ╭─[2:1:11]
+               │
+             1 │ query B { c }
+               │           ┬  
+               │           ╰── field `c` selected here
+               │
+               ├─[schema.graphql:1:6]
+               │
+             1 │ type Query { a: Int b: Int }
+               │      ──┬──  
+               │        ╰──── type `Query` defined here
+               │ 
+               │ Note: path to the field: `query B → c`
+            ───╯
+
+            duplicate persisted query ID `1`
+        "#]]
+        .assert_eq(&errors);
+
+        let errors = [
+            PersistedQueryManifest::from_apollo_json(&schema, b"{}").unwrap_err(),
+            PersistedQueryManifest::from_apollo_json(
+                &schema,
+                br#"{"format": "other", "version": 1, "operations": []}"#,
+            )
+            .unwrap_err(),
+            PersistedQueryManifest::from_relay_json(&schema, br#"{"1": 1}"#).unwrap_err(),
+        ];
+        let errors = errors
+            .iter()
+            .flatten()
+            .map(|error| format!("{error}\n"))
+            .collect::<String>();
+        expect![[r#"
+            invalid JSON in manifest: missing field `format` at line 1 column 2
+            invalid manifest: expected format `apollo-persisted-query-manifest` version 1
+            invalid manifest: expected an object with string values
+        "#]]
+        .assert_eq(&errors);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let schema = Schema::parse_and_validate("type Query { a: Int }", "schema.graphql").unwrap();
+        let body = "query A { a }";
+        let apq_id = persisted_query_id(body);
+        let mut manifest = PersistedQueryManifest::new();
+        manifest.insert(&schema, "a", None, body).unwrap();
+        manifest.insert(&schema, &apq_id, None, body).unwrap();
+
+        let apq = format!(
+            r#"{{"extensions": {{"persistedQuery": {{"version": 1, "sha256Hash": "{apq_id}"}}}}}}"#
+        );
+        let resolve = |manifest: &PersistedQueryManifest, body: &str| {
+            let request = Request::from_json(body.as_bytes()).unwrap();
+            match manifest.resolve(&request) {
+                Ok(entry) => format!("Ok {}\n", entry.id),
+                Err(error) => format!(
+                    "{}\n",
+                    serde_json::to_string(&error.into_response()).unwrap()
+                ),
+            }
+        };
+        let requests = [
+            r#"{"doc_id": "a"}"#,
+            r#"{"documentId": "a", "query": "query A { a }"}"#,
+            &apq,
+            r#"{"query": "query A { a }"}"#,
+            r#"{"documentId": "b"}"#,
+            r#"{"documentId": "a", "query": "{ a }"}"#,
+            r#"{"query": "{ a }"}"#,
+        ];
+        let results = requests
+            .iter()
+            .map(|body| resolve(&manifest, body))
+            .collect::<String>();
+        expect![[r#"
+            Ok a
+            Ok a
+            Ok 7d0eedabb966107835cf307a0ebaf93b5d2cb8c30228611ffe3d27a53c211a0c
+            Ok a
+            {"errors":[{"message":"persisted query `b` not found","extensions":{"code":"PERSISTED_QUERY_NOT_IN_LIST"}}]}
+            {"errors":[{"message":"query does not match persisted query `a`","extensions":{"code":"PERSISTED_QUERY_HASH_MISMATCH"}}]}
+            {"errors":[{"message":"operation is not in the safelist","extensions":{"code":"QUERY_NOT_IN_SAFELIST"}}]}
+        "#]].assert_eq(&results);
+
+        let manifest = manifest.require_id(true);
+        let results = requests[2..4]
+            .iter()
+            .map(|body| resolve(&manifest, body))
+            .collect::<String>();
+        expect![[r#"
+            Ok 7d0eedabb966107835cf307a0ebaf93b5d2cb8c30228611ffe3d27a53c211a0c
+            {"errors":[{"message":"a persisted query ID is required","extensions":{"code":"PERSISTED_QUERY_ID_REQUIRED"}}]}
+        "#]].assert_eq(&results);
+    }
+}
+
+mod batching {
+    use apollo_compiler::execution::execute_request_async;
+    use apollo_compiler::execution::AsyncResolvedValue;
+    use apollo_compiler::execution::AsyncResolver;
+    use apollo_compiler::execution::ExecutionLimits;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::FieldContext;
+    use apollo_compiler::execution::FieldMiddleware;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::execution::JsonValue;
+    use apollo_compiler::execution::ResolvedValue;
+    use apollo_compiler::execution::Resolver;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use futures::future::BoxFuture;
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            users: [User!]!
+            teams: [[User]]
+            everyone: [User!]!
+        }
+
+        type User {
+            id: ID!
+            name: String!
+            greeting(prefix: String! = "Hello"): String!
+            friends: [User!]!
+            unbatched: String
+            broken: String
+        }
+    "#;
+
+    const NAMES: [&str; 3] = ["Alice", "Bob", "Eve"];
+    const FRIENDS: [&[usize]; 3] = [&[1], &[0, 2], &[]];
+
+    struct Query<'a> {
+        log: &'a RefCell<Vec<String>>,
+    }
+
+    struct User<'a> {
+        log: &'a RefCell<Vec<String>>,
+        id: usize,
+    }
+
+    fn user_ids(keys: &[JsonValue]) -> Vec<usize> {
+        keys.iter()
+            .map(|key| key.as_u64().unwrap() as usize)
+            .collect()
+    }
+
+    impl Resolver for Query<'_> {
+        fn type_name(&self) -> &str {
+            "Query"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> Result<ResolvedValue<'a>, ResolverError> {
+            let user = |id| ResolvedValue::object(User { log: self.log, id });
+            match field_name {
+                "users" => Ok(ResolvedValue::list((0..3).map(user))),
+                "teams" => Ok(ResolvedValue::list([
+                    ResolvedValue::list([user(0), user(1)]),
+                    ResolvedValue::null(),
+                    ResolvedValue::list([ResolvedValue::null(), user(2)]),
+                ])),
+                // Never ends
+                "everyone" => Ok(ResolvedValue::list((0..).map(move |id| user(id % 3)))),
+                _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+            }
+        }
+    }
+
+    impl Resolver for User<'_> {
+        fn type_name(&self) -> &str {
+            "User"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> Result<ResolvedValue<'a>, ResolverError> {
+            self.log
+                .borrow_mut()
+                .push(format!("{field_name} of {}", self.id));
+            match field_name {
+                "id" => Ok(ResolvedValue::leaf(self.id.to_string())),
+                "name" | "unbatched" => Ok(ResolvedValue::leaf(NAMES[self.id])),
+                _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+            }
+        }
+
+        fn batch_key(&self) -> Option<JsonValue> {
+            Some(self.id.into())
+        }
+
+        fn resolve_field_batch<'a>(
+            &'a self,
+            field_name: &'a str,
+            arguments: &'a JsonMap,
+            keys: &'a [JsonValue],
+        ) -> Option<Vec<Result<ResolvedValue<'a>, ResolverError>>> {
+            let ids = user_ids(keys);
+            let results = match field_name {
+                "name" => ids
+                    .iter()
+                    .map(|&id| Ok(ResolvedValue::leaf(NAMES[id])))
+                    .collect(),
+                "greeting" => {
+                    let prefix = arguments["prefix"].as_str().unwrap();
+                    ids.iter()
+                        .map(|&id| Ok(ResolvedValue::leaf(format!("{prefix} {}", NAMES[id]))))
+                        .collect()
+                }
+                "friends" => ids
+                    .iter()
+                    .map(|&id| {
+                        Ok(ResolvedValue::list(FRIENDS[id].iter().map(|&id| {
+                            ResolvedValue::object(User { log: self.log, id })
+                        })))
+                    })
+                    .collect(),
+                "broken" => Vec::new(),
+                _ => return None,
+            };
+            self.log
+                .borrow_mut()
+                .push(format!("batch {field_name} of {ids:?} {arguments:?}"));
+            Some(results)
+        }
+    }
+
+    fn execute(query: &str) -> (String, String) {
+        execute_with(&Executor::new(), query)
+    }
+
+    fn execute_with(executor: &Executor, query: &str) -> (String, String) {
+        execute_with_variables(executor, query, serde_json::json!({}))
+    }
+
+    fn execute_with_variables(
+        executor: &Executor,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> (String, String) {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let variables: JsonMap = serde_json::from_value(variables).unwrap();
+        let log = RefCell::new(Vec::new());
+        let root = Query { log: &log };
+        let response = executor.execute_request(&schema, &document, None, &variables, &root);
+        (
+            serde_json::to_string_pretty(&response).unwrap(),
+            log.into_inner().join("\n"),
+        )
+    }
+
+    #[test]
+    fn test_batched_fields() {
+        let (response, log) = execute(
+            r#"{
+                users {
+                    id
+                    name
+                    greeting(prefix: "Hi")
+                    hello: greeting(prefix: "Hello")
+                    friends { name }
+                    unbatched
+                }
+            }"#,
+        );
+        let expected = expect![[r#"
+            batch name of [0, 1, 2] {}
+            batch greeting of [0, 1, 2] {"prefix": String("Hi")}
+            batch greeting of [0, 1, 2] {"prefix": String("Hello")}
+            batch friends of [0, 1, 2] {}
+            id of 0
+            batch name of [1] {}
+            unbatched of 0
+            id of 1
+            batch name of [0, 2] {}
+            unbatched of 1
+            id of 2
+            unbatched of 2"#]];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "data": {
+                "users": [
+                  {
+                    "id": "0",
+                    "name": "Alice",
+                    "greeting": "Hi Alice",
+                    "hello": "Hello Alice",
+                    "friends": [
+                      {
+                        "name": "Bob"
+                      }
+                    ],
+                    "unbatched": "Alice"
+                  },
+                  {
+                    "id": "1",
+                    "name": "Bob",
+                    "greeting": "Hi Bob",
+                    "hello": "Hello Bob",
+                    "friends": [
+                      {
+                        "name": "Alice"
+                      },
+                      {
+                        "name": "Eve"
+                      }
+                    ],
+                    "unbatched": "Bob"
+                  },
+                  {
+                    "id": "2",
+                    "name": "Eve",
+                    "greeting": "Hi Eve",
+                    "hello": "Hello Eve",
+                    "friends": [],
+                    "unbatched": "Eve"
+                  }
+                ]
+              }
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        let (response, log) = execute("{ teams { name } }");
+        // Each list is batched separately, and items that are not objects are skipped
+        let expected = expect![[r#"
+            batch name of [0, 1] {}
+            batch name of [2] {}"#]];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "data": {
+                "teams": [
+                  [
+                    {
+                      "name": "Alice"
+                    },
+                    {
+                      "name": "Bob"
+                    }
+                  ],
+                  null,
+                  [
+                    null,
+                    {
+                      "name": "Eve"
+                    }
+                  ]
+                ]
+              }
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_wrong_result_count() {
+        let (response, log) = execute("{ users { broken } }");
+        let expected = expect!["batch broken of [0, 1, 2] {}"];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "resolver error: resolve_field_batch returned 0 results for 3 keys",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 11
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    0,
+                    "broken"
+                  ]
+                },
+                {
+                  "message": "resolver error: resolve_field_batch returned 0 results for 3 keys",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 11
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    1,
+                    "broken"
+                  ]
+                },
+                {
+                  "message": "resolver error: resolve_field_batch returned 0 results for 3 keys",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 11
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    2,
+                    "broken"
+                  ]
+                }
+              ],
+              "data": {
+                "users": [
+                  {
+                    "broken": null
+                  },
+                  {
+                    "broken": null
+                  },
+                  {
+                    "broken": null
+                  }
+                ]
+              }
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_endless_list() {
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(4));
+        let (response, log) = execute_with(&executor, "{ everyone { name } }");
+        let expected = expect!["batch name of [0, 1, 2, 0] {}"];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum list length exceeded",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 3
+                    }
+                  ],
+                  "path": [
+                    "everyone",
+                    4
+                  ],
+                  "extensions": {
+                    "code": "MAX_LIST_LENGTH_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_limits_before_batch() {
+        // Counting `name` for each of the 3 users would exceed the limit
+        let executor = Executor::new().limits(ExecutionLimits::new().max_resolved_fields(3));
+        let (response, log) = execute_with(&executor, "{ users { name } }");
+        let expected = expect![[""]];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum number of resolved fields exceeded",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 11
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    0,
+                    "name"
+                  ],
+                  "extensions": {
+                    "code": "MAX_RESOLVED_FIELDS_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    /// Denies `User.name`
+    struct Deny;
+
+    impl FieldMiddleware for Deny {
+        fn before_field(
+            &self,
+            context: &FieldContext<'_>,
+        ) -> Option<Result<JsonValue, ResolverError>> {
+            (context.parent_type == "User" && context.field.name == "name")
+                .then(|| Err(ResolverError::new("unauthorized")))
+        }
+    }
+
+    #[test]
+    fn test_middleware_disables_batching() {
+        let executor = Executor::new().field_middleware(Deny);
+        let (response, log) = execute_with(&executor, "{ users { id name } }");
+        // Neither `resolve_field_batch` nor `resolve_field` is called for the denied field
+        let expected = expect!["id of 0"];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "unauthorized",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 14
+                    }
+                  ],
+                  "path": [
+                    "users",
+                    0,
+                    "name"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_argument_coercion_error() {
+        let executor = Executor::new();
+        let query = "query($prefix: String) { users { greeting(prefix: $prefix) } }";
+        let variables = serde_json::json!({"prefix": null});
+        let (response, log) = execute_with_variables(&executor, query, variables);
+        // Not batched: the field reports the error without calling `resolve_field`
+        let expected = expect![[r#""#]];
+        expected.assert_eq(&log);
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "null value for non-nullable argument prefix",
+                  "path": [
+                    "users",
+                    0,
+                    "greeting"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    struct AsyncQuery<'a> {
+        log: &'a Mutex<Vec<String>>,
+    }
+
+    struct AsyncUser<'a> {
+        log: &'a Mutex<Vec<String>>,
+        id: usize,
+    }
+
+    impl AsyncResolver for AsyncQuery<'_> {
+        fn type_name(&self) -> &str {
+            "Query"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                match field_name {
+                    "users" => Ok(AsyncResolvedValue::list((0..3).map(|id| {
+                        AsyncResolvedValue::object(AsyncUser { log: self.log, id })
+                    }))),
+                    // Never ends
+                    "everyone" => Ok(AsyncResolvedValue::list((0..).map(|id| {
+                        AsyncResolvedValue::object(AsyncUser {
+                            log: self.log,
+                            id: id % 3,
+                        })
+                    }))),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+    }
+
+    impl AsyncResolver for AsyncUser<'_> {
+        fn type_name(&self) -> &str {
+            "User"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{field_name} of {}", self.id));
+                match field_name {
+                    "id" => Ok(AsyncResolvedValue::leaf(self.id.to_string())),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+
+        fn batch_key(&self) -> Option<JsonValue> {
+            Some(self.id.into())
+        }
+
+        fn resolve_field_batch<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+            keys: &'a [JsonValue],
+        ) -> Option<BoxFuture<'a, Vec<Result<AsyncResolvedValue<'a>, ResolverError>>>> {
+            if field_name != "name" {
+                return None;
+            }
+            Some(Box::pin(async move {
+                let ids = user_ids(keys);
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("batch {field_name} of {ids:?}"));
+                ids.iter()
+                    .map(|&id| Ok(AsyncResolvedValue::leaf(NAMES[id])))
+                    .collect()
+            }))
+        }
+    }
+
+    #[test]
+    fn test_async() {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document = ExecutableDocument::parse_and_validate(
+            &schema,
+            "{ users { id name } }",
+            "query.graphql",
+        )
+        .unwrap();
+        let log = Mutex::new(Vec::new());
+        let root = AsyncQuery { log: &log };
+        let variables = JsonMap::new();
+        let future = execute_request_async(&schema, &document, None, &variables, &root);
+        let response = futures::executor::block_on(future);
+        let expected = expect![[r#"
+            batch name of [0, 1, 2]
+            id of 0
+            id of 1
+            id of 2"#]];
+        expected.assert_eq(&log.into_inner().unwrap().join("\n"));
+        let expected = expect![[
+            r#"{"data":{"users":[{"id":"0","name":"Alice"},{"id":"1","name":"Bob"},{"id":"2","name":"Eve"}]}}"#
+        ]];
+        expected.assert_eq(&serde_json::to_string(&response).unwrap());
+    }
+
+    #[test]
+    fn test_async_endless_list() {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document = ExecutableDocument::parse_and_validate(
+            &schema,
+            "{ everyone { name } }",
+            "query.graphql",
+        )
+        .unwrap();
+        let log = Mutex::new(Vec::new());
+        let root = AsyncQuery { log: &log };
+        let variables = JsonMap::new();
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(4));
+        let future = executor.execute_request_async(&schema, &document, None, &variables, &root);
+        let response = futures::executor::block_on(future);
+        let expected = expect![[""]];
+        expected.assert_eq(&log.into_inner().unwrap().join("\n"));
+        let expected = expect![[
+            r#"{"errors":[{"message":"Maximum list length exceeded","locations":[{"line":1,"column":3}],"path":["everyone",4],"extensions":{"code":"MAX_LIST_LENGTH_EXCEEDED"}}],"data":null}"#
+        ]];
+        expected.assert_eq(&serde_json::to_string(&response).unwrap());
+    }
+}
+
+mod response_merge {
+    use apollo_compiler::execution::Response;
+    use apollo_compiler::execution::ResponseDataPathElement;
+    use apollo_compiler::name;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    fn assert_merge(
+        target: serde_json::Value,
+        path: &[ResponseDataPathElement],
+        other: serde_json::Value,
+        expected: Expect,
+    ) {
+        let mut response: Response = serde_json::from_value(target).unwrap();
+        let other = serde_json::from_value(other).unwrap();
+        let merge_errors = response.merge_at_path(path, other);
+        let mut output = serde_json::to_string_pretty(&response).unwrap();
+        output.push('\n');
+        for error in merge_errors {
+            output.push_str(&format!("{error}\n"))
+        }
+        expected.assert_eq(&output);
+    }
+
+    #[test]
+    fn test_merge_object() {
+        let path = [
+            ResponseDataPathElement::Field(name!("me")),
+            ResponseDataPathElement::Field(name!("bestFriend")),
+        ];
+        assert_merge(
+            serde_json::json!({"data": {"me": {"id": "1", "bestFriend": {"id": "2"}}}}),
+            &path,
+            serde_json::json!({
+                "data": {"id": "2", "name": "Bob", "address": {"city": "Paris"}},
+                "errors": [{"message": "no email", "path": ["email"]}],
+                "extensions": {"cost": 3},
+            }),
+            expect![[r#"
+                {
+                  "errors": [
+                    {
+                      "message": "no email",
+                      "path": [
+                        "me",
+                        "bestFriend",
+                        "email"
+                      ]
+                    }
+                  ],
+                  "data": {
+                    "me": {
+                      "bestFriend": {
+                        "id": "2",
+                        "address": {
+                          "city": "Paris"
+                        },
+                        "name": "Bob"
+                      },
+                      "id": "1"
+                    }
+                  },
+                  "extensions": {
+                    "cost": 3
+                  }
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_entities() {
+        let path = [
+            ResponseDataPathElement::Field(name!("users")),
+            ResponseDataPathElement::Field(name!("friends")),
+        ];
+        assert_merge(
+            serde_json::json!({"data": {"users": [
+                {"friends": [{"id": "1"}, null]},
+                null,
+                {"friends": [{"id": "2"}]},
+            ]}}),
+            &path,
+            serde_json::json!({
+                "data": {"_entities": [{"name": "Alice"}, null]},
+                "errors": [
+                    {"message": "entity not found", "path": ["_entities", 1]},
+                    {"message": "subgraph timed out"},
+                ],
+            }),
+            expect![[r#"
+                {
+                  "errors": [
+                    {
+                      "message": "entity not found",
+                      "path": [
+                        "users",
+                        2,
+                        "friends",
+                        0
+                      ]
+                    },
+                    {
+                      "message": "subgraph timed out",
+                      "path": [
+                        "users"
+                      ]
+                    }
+                  ],
+                  "data": {
+                    "users": [
+                      {
+                        "friends": [
+                          {
+                            "id": "1",
+                            "name": "Alice"
+                          },
+                          null
+                        ]
+                      },
+                      null,
+                      {
+                        "friends": [
+                          {
+                            "id": "2"
+                          }
+                        ]
+                      }
+                    ]
+                  }
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_list_index() {
+        let path = [
+            ResponseDataPathElement::Field(name!("users")),
+            ResponseDataPathElement::ListIndex(1),
+        ];
+        assert_merge(
+            serde_json::json!({"data": {"users": [{"id": "1"}, {"id": "2", "tags": ["a", "b"]}]}}),
+            &path,
+            serde_json::json!({"data": {"id": "2", "tags": ["a", "b"], "name": "Bob"}}),
+            expect![[r#"
+                {
+                  "data": {
+                    "users": [
+                      {
+                        "id": "1"
+                      },
+                      {
+                        "id": "2",
+                        "tags": [
+                          "a",
+                          "b"
+                        ],
+                        "name": "Bob"
+                      }
+                    ]
+                  }
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let path = [ResponseDataPathElement::Field(name!("me"))];
+        assert_merge(
+            serde_json::json!({"data": {"me": {"id": "1", "tags": ["a"], "pets": [{"name": "Rex"}]}}}),
+            &path,
+            serde_json::json!({"data": {"id": "2", "tags": ["a", "b"], "pets": [{"name": "Tom"}]}}),
+            expect![[r#"
+                {
+                  "data": {
+                    "me": {
+                      "id": "1",
+                      "pets": [
+                        {
+                          "name": "Rex"
+                        }
+                      ],
+                      "tags": [
+                        "a"
+                      ]
+                    }
+                  }
+                }
+                conflicting values "1" and "2" at path me.id
+                conflicting values "Rex" and "Tom" at path me.pets.0.name
+                conflicting values ["a"] and ["a","b"] at path me.tags
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let path = [
+            ResponseDataPathElement::Field(name!("me")),
+            ResponseDataPathElement::Field(name!("bestFriend")),
+        ];
+        assert_merge(
+            serde_json::json!({"data": {"me": {"bestFriend": null}}}),
+            &path,
+            serde_json::json!({"data": {"name": "Bob"}}),
+            expect![[r#"
+                {
+                  "data": {
+                    "me": {
+                      "bestFriend": null
+                    }
+                  }
+                }
+                no object to merge into at path me.bestFriend
+            "#]],
+        );
+        let path = [
+            ResponseDataPathElement::Field(name!("users")),
+            ResponseDataPathElement::Field(name!("bestFriend")),
+        ];
+        assert_merge(
+            serde_json::json!({"data": {"users": [{"bestFriend": {"id": "1"}}, {"bestFriend": {"id": "2"}}]}}),
+            &path,
+            serde_json::json!({"data": {"_entities": [{"name": "Bob"}]}}),
+            expect![[r#"
+                {
+                  "data": {
+                    "users": [
+                      {
+                        "bestFriend": {
+                          "id": "1"
+                        }
+                      },
+                      {
+                        "bestFriend": {
+                          "id": "2"
+                        }
+                      }
+                    ]
+                  }
+                }
+                expected incoming data with a single list of 2 entities at path users
+            "#]],
+        );
+    }
+}
+
+mod mutation {
+    use apollo_compiler::execution::execute_request;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::execution::ResolvedValue;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::impl_resolver;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use std::cell::Cell;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            counter: Int!
+        }
+
+        type Mutation {
+            increment(by: Int! = 1): Int!
+            fail: Int
+            failNonNull: Int!
+        }
+    "#;
+
+    struct Mutation {
+        counter: Cell<i32>,
+    }
+
+    impl_resolver! {
+        for Mutation:
+
+        __typename = "Mutation";
+
+        fn increment(&self_, args) {
+            let by = args["by"].as_i64().unwrap() as i32;
+            self_.counter.set(self_.counter.get() + by);
+            Ok(ResolvedValue::leaf(self_.counter.get()))
+        }
+
+        fn fail() {
+            Err(ResolverError::new("nope"))
+        }
+
+        fn failNonNull() {
+            Err(ResolverError::new("nope"))
+        }
+    }
+
+    /// Returns the serialized response and the final counter value
+    fn execute(mutation: &str) -> (String, i32) {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, mutation, "mutation.graphql").unwrap();
+        let root = Mutation {
+            counter: Cell::new(0),
+        };
+        let response = execute_request(&schema, &document, None, &JsonMap::new(), &root);
+        (
+            serde_json::to_string_pretty(&response).unwrap(),
+            root.counter.get(),
+        )
+    }
+
+    #[test]
+    fn test_side_effects_in_order() {
+        let (response, counter) = execute(
+            r#"mutation {
+                a: increment
+                b: increment(by: 10)
+                c: increment
+            }"#,
+        );
+        let expected = expect![[r#"
+            {
+              "data": {
+                "a": 1,
+                "b": 11,
+                "c": 12
+              }
+            }"#]];
+        expected.assert_eq(&response);
+        assert_eq!(counter, 12);
+    }
+
+    #[test]
+    fn test_nullable_field_error() {
+        let (response, counter) = execute(
+            r#"mutation {
+                a: increment
+                fail
+                b: increment
+            }"#,
+        );
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "resolver error: nope",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "fail"
+                  ]
+                }
+              ],
+              "data": {
+                "a": 1,
+                "fail": null,
+                "b": 2
+              }
+            }"#]];
+        expected.assert_eq(&response);
+        assert_eq!(counter, 2);
+    }
+
+    #[test]
+    fn test_non_null_field_error() {
+        let (response, counter) = execute(
+            r#"mutation {
+                a: increment
+                failNonNull
+                b: increment
+            }"#,
+        );
+        // Null propagates to the root, and the remaining field is not executed
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "resolver error: nope",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 17
+                    }
+                  ],
+                  "path": [
+                    "failNonNull"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+        assert_eq!(counter, 1);
+    }
+}
+
+mod asynchronous {
+    use apollo_compiler::execution::AsyncResolvedValue;
+    use apollo_compiler::execution::AsyncResolver;
+    use apollo_compiler::execution::ExecutionLimits;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use futures::future::BoxFuture;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Mutex;
+    use std::task::Context;
+    use std::task::Poll;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            slow(name: String!): String
+            object(name: String!): Object
+            objects: [Object!]!
+            endless: [String]
+        }
+
+        type Mutation {
+            slow(name: String!): String
+            object(name: String!): Object
+        }
+
+        type Object {
+            slow(name: String!): String
+            nonNull: String!
+        }
+    "#;
+
+    /// Returns `Pending` once, so that other futures can make progress in the meantime
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    struct Root<'a> {
+        log: &'a Mutex<Vec<String>>,
+    }
+
+    struct Object<'a> {
+        log: &'a Mutex<Vec<String>>,
+    }
+
+    async fn slow(log: &Mutex<Vec<String>>, name: &str) -> String {
+        log.lock().unwrap().push(format!("start {name}"));
+        YieldNow(false).await;
+        log.lock().unwrap().push(format!("end {name}"));
+        name.to_owned()
+    }
+
+    impl AsyncResolver for Root<'_> {
+        fn type_name(&self) -> &str {
+            "Query"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                let log = self.log;
+                match field_name {
+                    "slow" => Ok(AsyncResolvedValue::leaf(
+                        slow(log, arguments["name"].as_str().unwrap()).await,
+                    )),
+                    "object" => {
+                        slow(log, arguments["name"].as_str().unwrap()).await;
+                        Ok(AsyncResolvedValue::object(Object { log }))
+                    }
+                    "objects" => Ok(AsyncResolvedValue::list(
+                        (0..2).map(|_| AsyncResolvedValue::object(Object { log })),
+                    )),
+                    "endless" => Ok(AsyncResolvedValue::list(std::iter::repeat_with(|| {
+                        AsyncResolvedValue::leaf("again")
+                    }))),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+    }
+
+    impl AsyncResolver for Object<'_> {
+        fn type_name(&self) -> &str {
+            "Object"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                match field_name {
+                    "slow" => Ok(AsyncResolvedValue::leaf(
+                        slow(self.log, arguments["name"].as_str().unwrap()).await,
+                    )),
+                    "nonNull" => Ok(AsyncResolvedValue::null()),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+    }
+
+    fn execute(query: &str) -> (String, Vec<String>) {
+        execute_with(&Executor::new(), query)
+    }
+
+    fn execute_with(executor: &Executor, query: &str) -> (String, Vec<String>) {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let log = Mutex::new(Vec::new());
+        let root = Root { log: &log };
+        let variables = JsonMap::new();
+        let future = executor.execute_request_async(&schema, &document, None, &variables, &root);
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&future);
+        let response = futures::executor::block_on(future);
+        (
+            serde_json::to_string_pretty(&response).unwrap(),
+            log.into_inner().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_concurrent_siblings() {
+        let (response, log) = execute(
+            r#"{
+                a: slow(name: "a")
+                b: slow(name: "b")
+                c: object(name: "c") { d: slow(name: "d") }
+                __typename
+            }"#,
+        );
+        let expected = expect![[r#"
+            {
+              "data": {
+                "a": "a",
+                "b": "b",
+                "c": {
+                  "d": "d"
+                },
+                "__typename": "Query"
+              }
+            }"#]];
+        expected.assert_eq(&response);
+        // Sibling fields start before any of them ends
+        let expected = expect![[r#"
+            [
+                "start a",
+                "start b",
+                "start c",
+                "end a",
+                "end b",
+                "end c",
+                "start d",
+                "end d",
+            ]
+        "#]];
+        expected.assert_debug_eq(&log);
+    }
+
+    #[test]
+    fn test_serial_mutation() {
+        let (response, log) = execute(
+            r#"mutation {
+                a: slow(name: "a")
+                b: object(name: "b") { c: slow(name: "c") d: slow(name: "d") }
+                e: slow(name: "e")
+            }"#,
+        );
+        let expected = expect![[r#"
+            {
+              "data": {
+                "a": "a",
+                "b": {
+                  "c": "c",
+                  "d": "d"
+                },
+                "e": "e"
+              }
+            }"#]];
+        expected.assert_eq(&response);
+        // Each top-level field is completed before the next one starts,
+        // but nested fields are still executed concurrently
+        let expected = expect![[r#"
+            [
+                "start a",
+                "end a",
+                "start b",
+                "end b",
+                "start c",
+                "start d",
+                "end c",
+                "end d",
+                "start e",
+                "end e",
+            ]
+        "#]];
+        expected.assert_debug_eq(&log);
+    }
+
+    #[test]
+    fn test_null_propagation() {
+        let (response, _log) = execute(
+            r#"{
+                a: slow(name: "a")
+                objects { slow(name: "b") nonNull }
+            }"#,
+        );
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Non-null type String! resolved to null",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 43
+                    }
+                  ],
+                  "path": [
+                    "objects",
+                    0,
+                    "nonNull"
+                  ]
+                },
+                {
+                  "message": "Non-null type String! resolved to null",
+                  "locations": [
+                    {
+                      "line": 3,
+                      "column": 43
+                    }
+                  ],
+                  "path": [
+                    "objects",
+                    1,
+                    "nonNull"
+                  ]
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+
+    #[test]
+    fn test_endless_list() {
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(3));
+        let (response, _log) = execute_with(&executor, "{ endless }");
+        let expected = expect![[r#"
+            {
+              "errors": [
+                {
+                  "message": "Maximum list length exceeded",
+                  "locations": [
+                    {
+                      "line": 1,
+                      "column": 3
+                    }
+                  ],
+                  "path": [
+                    "endless",
+                    3
+                  ],
+                  "extensions": {
+                    "code": "MAX_LIST_LENGTH_EXCEEDED"
+                  }
+                }
+              ],
+              "data": null
+            }"#]];
+        expected.assert_eq(&response);
+    }
+}
+
+mod subscription {
+    use apollo_compiler::execution::subscribe;
+    use apollo_compiler::execution::AsyncResolvedValue;
+    use apollo_compiler::execution::AsyncResolver;
+    use apollo_compiler::execution::ExecutionLimits;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::execution::SourceEventStream;
+    use apollo_compiler::execution::SubscriptionResolver;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use expect_test::Expect;
+    use futures::future::BoxFuture;
+    use futures::StreamExt;
+
+    const SCHEMA: &str = r#"
+        type Query {
+            unused: Int
+        }
+
+        type Subscription {
+            counter(upTo: Int!, failAt: Int): Counter!
+        }
+
+        type Counter {
+            value: Int!
+            double: Int
+        }
+    "#;
+
+    struct Root;
+
+    /// A source event, used as the initial value for executing the subscription selection set
+    struct Event(i32);
+
+    struct Counter(i32);
+
+    impl SubscriptionResolver for Root {
+        fn resolve_field_event_stream<'a>(
+            &'a self,
+            field_name: &'a str,
+            arguments: JsonMap,
+        ) -> BoxFuture<'a, Result<SourceEventStream<'a>, ResolverError>> {
+            Box::pin(async move {
+                if field_name != "counter" {
+                    return Err(ResolverError::new(format!("unexpected field {field_name}")));
+                }
+                let up_to = arguments["upTo"].as_i64().unwrap() as i32;
+                if up_to < 0 {
+                    return Err(ResolverError::new("upTo must be positive"));
+                }
+                let fail_at = arguments.get("failAt").and_then(|v| v.as_i64());
+                let stream = futures::stream::iter(1..=up_to).map(move |value| {
+                    if Some(i64::from(value)) == fail_at {
+                        Err(ResolverError::new(format!("failed at {value}")))
+                    } else {
+                        Ok(Box::new(Event(value)) as Box<_>)
+                    }
+                });
+                Ok(stream.boxed())
+            })
+        }
+    }
+
+    impl AsyncResolver for Event {
+        fn type_name(&self) -> &str {
+            "Subscription"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                match field_name {
+                    "counter" => Ok(AsyncResolvedValue::object(Counter(self.0))),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+    }
+
+    impl AsyncResolver for Counter {
+        fn type_name(&self) -> &str {
+            "Counter"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> BoxFuture<'a, Result<AsyncResolvedValue<'a>, ResolverError>> {
+            Box::pin(async move {
+                match field_name {
+                    "value" => Ok(AsyncResolvedValue::leaf(self.0)),
+                    "double" if self.0 % 2 == 0 => Ok(AsyncResolvedValue::leaf(self.0 * 2)),
+                    "double" => Err(ResolverError::new("odd value")),
+                    _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+                }
+            })
+        }
+    }
+
+    /// Serializes each response on one line, or the request error response prefixed with `Err`
+    fn assert_subscribe(query: &str, expected: Expect) {
+        assert_subscribe_with(None, query, expected)
+    }
+
+    fn assert_subscribe_with(executor: Option<&Executor>, query: &str, expected: Expect) {
+        let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let variables = JsonMap::new();
+        let future = async {
+            match executor {
+                Some(executor) => {
+                    executor
+                        .subscribe(&schema, &document, None, &variables, &Root)
+                        .await
+                }
+                None => subscribe(&schema, &document, None, &variables, &Root).await,
+            }
+        };
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&future);
+        let result = futures::executor::block_on(async {
+            match future.await {
+                Ok(stream) => {
+                    let responses: Vec<_> = stream.collect().await;
+                    responses
+                        .iter()
+                        .map(|response| serde_json::to_string(response).unwrap() + "\n")
+                        .collect::<String>()
+                }
+                Err(response) => format!("Err {}\n", serde_json::to_string(&response).unwrap()),
+            }
+        });
+        expected.assert_eq(&result);
+    }
+
+    #[test]
+    fn test_event_stream() {
+        assert_subscribe(
+            "subscription { counter(upTo: 3) { value double } }",
+            expect![[r#"
+                {"errors":[{"message":"resolver error: odd value","locations":[{"line":1,"column":41}],"path":["counter","double"]}],"data":{"counter":{"value":1,"double":null}}}
+                {"data":{"counter":{"value":2,"double":4}}}
+                {"errors":[{"message":"resolver error: odd value","locations":[{"line":1,"column":41}],"path":["counter","double"]}],"data":{"counter":{"value":3,"double":null}}}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_source_stream_error() {
+        assert_subscribe(
+            "subscription { counter(upTo: 3, failAt: 2) { value } }",
+            expect![[r#"
+                {"data":{"counter":{"value":1}}}
+                {"errors":[{"message":"source stream error: failed at 2"}]}
+                {"data":{"counter":{"value":3}}}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_resolver_error() {
+        assert_subscribe(
+            "subscription { counter(upTo: -1) { value } }",
+            expect![[r#"
+                Err {"errors":[{"message":"resolver error: upTo must be positive","locations":[{"line":1,"column":16}]}]}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_not_a_subscription() {
+        assert_subscribe(
+            "{ unused }",
+            expect![[r#"
+                Err {"errors":[{"message":"subscribe called with a query","locations":[{"line":1,"column":1}]}]}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_skipped_root_field() {
+        assert_subscribe(
+            "subscription { counter(upTo: 1) @skip(if: true) { value } }",
+            expect![[r#"
+                Err {"errors":[{"message":"subscription must select exactly one root field, found 0","locations":[{"line":1,"column":1}]}]}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_limits_per_event() {
+        // Each event resolves 2 fields, 6 in total
+        let executor = Executor::new().limits(ExecutionLimits::new().max_resolved_fields(2));
+        assert_subscribe_with(
+            Some(&executor),
+            "subscription { counter(upTo: 3) { value } }",
+            expect![[r#"
+                {"data":{"counter":{"value":1}}}
+                {"data":{"counter":{"value":2}}}
+                {"data":{"counter":{"value":3}}}
+            "#]],
+        );
+        assert_subscribe_with(
+            Some(&executor),
+            "subscription { counter(upTo: 2) { value double } }",
+            expect![[r#"
+                {"errors":[{"message":"Maximum number of resolved fields exceeded","locations":[{"line":1,"column":41}],"path":["counter","double"],"extensions":{"code":"MAX_RESOLVED_FIELDS_EXCEEDED"}}],"data":null}
+                {"errors":[{"message":"Maximum number of resolved fields exceeded","locations":[{"line":1,"column":41}],"path":["counter","double"],"extensions":{"code":"MAX_RESOLVED_FIELDS_EXCEEDED"}}],"data":null}
+            "#]],
+        );
+    }
+}
+
+mod incremental {
+    use super::Db;
+    use super::QueryResolver;
+    use super::SCHEMA;
+    use apollo_compiler::execution::execute_incremental_request;
+    use apollo_compiler::execution::ExecutionLimits;
+    use apollo_compiler::execution::Executor;
+    use apollo_compiler::execution::JsonMap;
+    use apollo_compiler::execution::ResolvedValue;
+    use apollo_compiler::execution::Resolver;
+    use apollo_compiler::execution::ResolverError;
+    use apollo_compiler::ExecutableDocument;
+    use apollo_compiler::Schema;
+    use expect_test::expect;
+    use expect_test::Expect;
+
+    /// Serializes the initial response and each subsequent payload on one line
+    fn assert_execute(query: &str, expected: Expect) {
+        assert_execute_with(None, query, expected)
+    }
+
+    fn assert_execute_with(executor: Option<&Executor>, query: &str, expected: Expect) {
+        let schema = Schema::builder()
+            .enable_incremental_delivery()
+            .parse(SCHEMA, "schema.graphql")
+            .build()
+            .unwrap()
+            .validate()
+            .unwrap();
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let db = Db::new();
+        let root = QueryResolver { db: &db };
+        let variables = JsonMap::new();
+        let (initial, subsequent) = match executor {
+            Some(executor) => {
+                executor.execute_incremental_request(&schema, &document, None, &variables, &root)
+            }
+            None => execute_incremental_request(&schema, &document, None, &variables, &root),
+        };
+        let mut payloads = serde_json::to_string(&initial).unwrap() + "\n";
+        for payload in &subsequent {
+            payloads += &serde_json::to_string(payload).unwrap();
+            payloads += "\n";
+        }
+        expected.assert_eq(&payloads);
+    }
+
+    #[test]
+    fn test_no_defer() {
+        assert_execute(
+            r#"{ user(id: "1") { name } }"#,
+            expect![[r#"
+                {"data":{"user":{"name":"Alice"}},"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_defer() {
+        assert_execute(
+            r#"{
+                user(id: "2") {
+                    id
+                    ...Friends @defer(label: "friends")
+                }
+                ... @defer { hello }
+            }
+            fragment Friends on User {
+                friends {
+                    name
+                    ... @defer(label: "nested") { id }
+                }
+            }"#,
+            expect![[r#"
+                {"data":{"user":{"id":"2"}},"pending":[{"id":"0","path":["user"],"label":"friends"},{"id":"1","path":[]}],"hasNext":true}
+                {"pending":[{"id":"2","path":["user","friends",0],"label":"nested"},{"id":"3","path":["user","friends",1],"label":"nested"}],"incremental":[{"id":"0","data":{"friends":[{"name":"Alice"},{"name":"Eve"}]}}],"completed":[{"id":"0"}],"hasNext":true}
+                {"incremental":[{"id":"1","data":{"hello":"Hello World!"}}],"completed":[{"id":"1"}],"hasNext":true}
+                {"incremental":[{"id":"2","data":{"id":"1"}}],"completed":[{"id":"2"}],"hasNext":true}
+                {"incremental":[{"id":"3","data":{"id":"3"}}],"completed":[{"id":"3"}],"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_defer_if_false() {
+        assert_execute(
+            r#"{ user(id: "1") { id ... @defer(if: false) { name } } }"#,
+            expect![[r#"
+                {"data":{"user":{"id":"1","name":"Alice"}},"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_defer_error() {
+        assert_execute(
+            r#"{
+                user(id: "1") {
+                    id
+                    ... @defer(label: "fail") { name nonNullFail }
+                }
+            }"#,
+            expect![[r#"
+                {"data":{"user":{"id":"1"}},"pending":[{"id":"0","path":["user"],"label":"fail"}],"hasNext":true}
+                {"completed":[{"id":"0","errors":[{"message":"Non-null type Int! resolved to null","locations":[{"line":4,"column":54}],"path":["user","nonNullFail"]}]}],"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_defer_under_nulled_parent() {
+        // The deferred fragment is executed,
+        // but not announced since its parent object is replaced with null
+        assert_execute(
+            r#"{
+                user(id: "1") {
+                    ... @defer { name }
+                    nonNullFail
+                }
+            }"#,
+            expect![[r#"
+                {"errors":[{"message":"Non-null type Int! resolved to null","locations":[{"line":4,"column":21}],"path":["user","nonNullFail"]}],"data":{"user":null},"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        assert_execute(
+            r#"{
+                users @stream(label: "users", initialCount: 1) {
+                    name
+                    ... @defer { id }
+                }
+                allInitial: users @stream(initialCount: 5) { id }
+            }"#,
+            expect![[r#"
+                {"data":{"users":[{"name":"Alice"}],"allInitial":[{"id":"1"},{"id":"2"},{"id":"3"}]},"pending":[{"id":"0","path":["users",0]},{"id":"1","path":["users"],"label":"users"}],"hasNext":true}
+                {"incremental":[{"id":"0","data":{"id":"1"}}],"completed":[{"id":"0"}],"hasNext":true}
+                {"pending":[{"id":"2","path":["users",1]},{"id":"3","path":["users",2]}],"incremental":[{"id":"1","items":[{"name":"Bob"},{"name":"Eve"}]}],"completed":[{"id":"1"}],"hasNext":true}
+                {"incremental":[{"id":"2","data":{"id":"2"}}],"completed":[{"id":"2"}],"hasNext":true}
+                {"incremental":[{"id":"3","data":{"id":"3"}}],"completed":[{"id":"3"}],"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_stream_error() {
+        assert_execute(
+            r#"{
+                user(id: "2") {
+                    friends @stream(initialCount: 0) { name nonNullFail }
+                }
+            }"#,
+            expect![[r#"
+                {"data":{"user":{"friends":[]}},"pending":[{"id":"0","path":["user","friends"]}],"hasNext":true}
+                {"completed":[{"id":"0","errors":[{"message":"Non-null type Int! resolved to null","locations":[{"line":3,"column":61}],"path":["user","friends",0,"nonNullFail"]}]}],"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_stream_negative_initial_count() {
+        assert_execute(
+            r#"{
+                user(id: "2") {
+                    id
+                    friends @stream(initialCount: -1) { name }
+                }
+            }"#,
+            expect![[r#"
+                {"errors":[{"message":"initialCount must be a non-negative integer","locations":[{"line":4,"column":21}],"path":["user","friends"]}],"data":{"user":null},"hasNext":false}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let query = r#"{
+            users @stream(initialCount: 1) { name }
+            user(id: "2") { ... @defer { id } }
+        }"#;
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(3));
+        assert_execute_with(
+            Some(&executor),
+            query,
+            expect![[r#"
+            {"data":{"users":[{"name":"Alice"}],"user":{}},"pending":[{"id":"0","path":["users"]},{"id":"1","path":["user"]}],"hasNext":true}
+            {"incremental":[{"id":"0","items":[{"name":"Bob"},{"name":"Eve"}]}],"completed":[{"id":"0"}],"hasNext":true}
+            {"incremental":[{"id":"1","data":{"id":"2"}}],"completed":[{"id":"1"}],"hasNext":false}
+        "#]],
+        );
+        // Exceeded in a streamed list item
+        let executor = Executor::new().limits(ExecutionLimits::new().max_list_length(2));
+        assert_execute_with(
+            Some(&executor),
+            query,
+            expect![[r#"
+            {"errors":[{"message":"Maximum list length exceeded","locations":[{"line":2,"column":13}],"path":["users",2],"extensions":{"code":"MAX_LIST_LENGTH_EXCEEDED"}}],"data":null,"hasNext":false}
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_stream_negative_initial_count_skips_resolver() {
+        struct Root<'a> {
+            calls: &'a std::cell::Cell<usize>,
+        }
+
+        impl Resolver for Root<'_> {
+            fn type_name(&self) -> &str {
+                "Query"
+            }
+
+            fn resolve_field<'a>(
+                &'a self,
+                _field_name: &'a str,
+                _arguments: &'a JsonMap,
+            ) -> Result<ResolvedValue<'a>, ResolverError> {
+                self.calls.set(self.calls.get() + 1);
+                Ok(ResolvedValue::list([]))
+            }
+        }
+
+        let schema = Schema::builder()
+            .enable_incremental_delivery()
+            .parse(SCHEMA, "schema.graphql")
+            .build()
+            .unwrap()
+            .validate()
+            .unwrap();
+        let query = "{ users @stream(initialCount: -1) { id } }";
+        let document =
+            ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+        let calls = std::cell::Cell::new(0);
+        let root = Root { calls: &calls };
+        let (initial, subsequent) =
+            execute_incremental_request(&schema, &document, None, &JsonMap::new(), &root);
+        assert_eq!(calls.get(), 0);
+        assert!(subsequent.is_empty());
+        let expected = expect![[
+            r#"{"errors":[{"message":"initialCount must be a non-negative integer","locations":[{"line":1,"column":3}],"path":["users"]}],"data":null,"hasNext":false}"#
+        ]];
+        expected.assert_eq(&serde_json::to_string(&initial).unwrap());
+    }
+}
@@ -1,11 +1,20 @@
 use apollo_compiler::ast::FieldDefinition;
 use apollo_compiler::ast::InputValueDefinition;
+use apollo_compiler::execution::check_introspection_max_depth;
 use apollo_compiler::execution::coerce_variable_values;
+use apollo_compiler::execution::execute_introspection_only_query;
+use apollo_compiler::execution::Executor;
+use apollo_compiler::execution::IntrospectionQueryOptions;
 use apollo_compiler::execution::JsonMap;
+use apollo_compiler::execution::ResolvedValue;
+use apollo_compiler::execution::Resolver;
+use apollo_compiler::execution::ResolverError;
 use apollo_compiler::execution::Response;
 use apollo_compiler::execution::SchemaIntrospectionQuery;
 use apollo_compiler::name;
 use apollo_compiler::schema::ExtendedType;
+use apollo_compiler::schema::HiddenByDirective;
+use apollo_compiler::schema::SchemaVisibility;
 use apollo_compiler::ty;
 use apollo_compiler::ExecutableDocument;
 use apollo_compiler::Schema;
@@ -239,3 +248,741 @@ fn built_in_scalars() {
     assert!(valid_after_mutation.types.contains_key("String"));
     assert!(valid_after_mutation.types.contains_key("Boolean"));
 }
+
+#[test]
+fn one_of() {
+    let schema = r#"
+        type Query {
+            pet(by: PetBy, filter: Filter): String
+        }
+
+        input PetBy @oneOf {
+            id: ID
+            name: String
+        }
+
+        input Filter {
+            name: String
+        }
+    "#;
+    let schema = Schema::parse_and_validate(schema, "schema.graphql").unwrap();
+    let query = r#"
+        {
+            PetBy: __type(name: "PetBy") { isOneOf }
+            Filter: __type(name: "Filter") { isOneOf }
+            Query: __type(name: "Query") { isOneOf }
+            __schema { directives { name locations } }
+        }
+    "#;
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let variables = coerce_variable_values(&schema, operation, &JsonMap::new()).unwrap();
+    let response = SchemaIntrospectionQuery::split_and_execute(
+        &schema,
+        &document,
+        operation,
+        &variables,
+        |_| unreachable!(),
+    );
+    let response = serde_json::to_value(&response).unwrap();
+    let data = &response["data"];
+    assert_eq!(data["PetBy"]["isOneOf"], true);
+    assert_eq!(data["Filter"]["isOneOf"], false);
+    assert!(data["Query"]["isOneOf"].is_null());
+    let directives = data["__schema"]["directives"].as_array().unwrap();
+    let one_of = directives
+        .iter()
+        .find(|directive| directive["name"] == "oneOf")
+        .unwrap();
+    expect![[r#"
+        {
+          "locations": [
+            "INPUT_OBJECT"
+          ],
+          "name": "oneOf"
+        }"#]]
+    .assert_eq(&serde_json::to_string_pretty(one_of).unwrap());
+}
+
+#[test]
+fn from_introspection() {
+    let sdl = r#"
+        "The schema"
+        schema {
+          query: Query
+          mutation: Mutation
+        }
+
+        directive @tag(name: String!) repeatable on FIELD_DEFINITION | OBJECT
+
+        "Root query type"
+        type Query implements Node {
+          id: ID!
+          pets(first: Int = 10, by: PetBy = {name: "Rex"}): [Pet!]! @deprecated(reason: "use `animals`")
+          url: Url
+          kind(kind: Kind = DOG): Kind
+        }
+
+        type Mutation {
+          adopt(id: ID!): Pet
+        }
+
+        interface Node {
+          id: ID!
+        }
+
+        type Dog implements Node {
+          id: ID!
+          barks: Boolean
+        }
+
+        union Pet = Dog
+
+        enum Kind {
+          DOG
+          CAT @deprecated
+        }
+
+        input PetBy @oneOf {
+          id: ID
+          name: String
+        }
+
+        scalar Url @specifiedBy(url: "https://url.spec.whatwg.org/")
+    "#;
+    let schema = Schema::parse_and_validate(sdl, "schema.graphql").unwrap();
+    let query = r#"
+        {
+          __schema {
+            description
+            queryType { name }
+            mutationType { name }
+            subscriptionType { name }
+            types { ...FullType }
+            directives {
+              name
+              description
+              locations
+              isRepeatable
+              args(includeDeprecated: true) { ...InputValue }
+            }
+          }
+        }
+        fragment FullType on __Type {
+          kind
+          name
+          description
+          specifiedByURL
+          isOneOf
+          fields(includeDeprecated: true) {
+            name
+            description
+            args(includeDeprecated: true) { ...InputValue }
+            type { ...TypeRef }
+            isDeprecated
+            deprecationReason
+          }
+          inputFields(includeDeprecated: true) { ...InputValue }
+          interfaces { ...TypeRef }
+          enumValues(includeDeprecated: true) {
+            name
+            description
+            isDeprecated
+            deprecationReason
+          }
+          possibleTypes { ...TypeRef }
+        }
+        fragment InputValue on __InputValue {
+          name
+          description
+          type { ...TypeRef }
+          defaultValue
+          isDeprecated
+          deprecationReason
+        }
+        fragment TypeRef on __Type {
+          kind
+          name
+          ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+        }
+    "#;
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+    let operation = document.operations.get(None).unwrap();
+    let variables = coerce_variable_values(&schema, operation, &JsonMap::new()).unwrap();
+    let response = SchemaIntrospectionQuery::split_and_execute(
+        &schema,
+        &document,
+        operation,
+        &variables,
+        |_| unreachable!(),
+    );
+    let response = serde_json::to_value(&response).unwrap();
+    let response = serde_json::from_value(response).unwrap();
+    let from_introspection = Schema::from_introspection(&response).unwrap();
+    expect![[r#"
+        """The schema"""
+        schema {
+          query: Query
+          mutation: Mutation
+        }
+
+        directive @tag(name: String!) repeatable on FIELD_DEFINITION | OBJECT
+
+        """Root query type"""
+        type Query implements Node {
+          id: ID!
+          pets(first: Int = 10, by: PetBy = {name: "Rex"}): [Pet!]! @deprecated(reason: "use `animals`")
+          url: Url
+          kind(kind: Kind = DOG): Kind
+        }
+
+        type Mutation {
+          adopt(id: ID!): Pet
+        }
+
+        interface Node {
+          id: ID!
+        }
+
+        type Dog implements Node {
+          id: ID!
+          barks: Boolean
+        }
+
+        union Pet = Dog
+
+        enum Kind {
+          DOG
+          CAT @deprecated
+        }
+
+        input PetBy @oneOf {
+          id: ID
+          name: String
+        }
+
+        scalar Url @specifiedBy(url: "https://url.spec.whatwg.org/")
+    "#]].assert_eq(&from_introspection.to_string());
+    assert_eq!(from_introspection.to_string(), schema.to_string());
+}
+
+#[test]
+fn from_invalid_introspection() {
+    let introspection = serde_json::json!({
+        "__schema": {
+            "queryType": {"name": "Query"},
+            "types": [
+                {
+                    "kind": "OBJECT",
+                    "name": "Query",
+                    "fields": [
+                        {"name": "a", "type": {"kind": "NAMED", "name": "Int"}},
+                        {"name": "b", "type": {"kind": "SCALAR", "name": "Missing"}},
+                        {"name": "c"},
+                        {
+                            "name": "d",
+                            "args": [{
+                                "name": "arg",
+                                "type": {"kind": "SCALAR", "name": "Int"},
+                                "defaultValue": "1) { e"
+                            }],
+                            "type": {"kind": "SCALAR", "name": "Int"}
+                        },
+                    ],
+                    "interfaces": [],
+                },
+                {"kind": "WHATEVER", "name": "Thing"},
+                {"kind": "SCALAR", "name": "not a name"},
+            ],
+            "directives": [{"name": "dir", "locations": ["NOWHERE"]}],
+        }
+    });
+    let introspection = serde_json::from_value(introspection).unwrap();
+    let errors = Schema::from_introspection(&introspection)
+        .unwrap_err()
+        .errors;
+    expect![[r#"
+        Error: invalid introspection result at `__schema.types.0.fields.2`: missing `type`
+        Error: invalid introspection result at `__schema.types.1.kind`: expected a named type kind, found `WHATEVER`
+        Error: invalid introspection result at `__schema.types.2`: `not a name` is not a valid GraphQL name
+        Error: invalid introspection result at `__schema.directives.0.locations.0`: expected a directive location
+        Error: cannot find type `Missing` in this document
+        Error: syntax error: expected R_CURLY, got )
+           ╭─[__schema.types.0.fields.3.args.0.defaultValue:1:36]
+           │
+         1 │ input DefaultValue { value: Int = 1) { e }
+           │                                    ┬  
+           │                                    ╰── expected R_CURLY, got )
+        ───╯
+        Error: syntax error: expected a StringValue, Name or OperationDefinition
+           ╭─[__schema.types.0.fields.3.args.0.defaultValue:1:36]
+           │
+         1 │ input DefaultValue { value: Int = 1) { e }
+           │                                    ┬  
+           │                                    ╰── expected a StringValue, Name or OperationDefinition
+        ───╯
+    "#]].assert_eq(&errors.to_string());
+}
+
+#[test]
+fn introspection_query() {
+    expect![[r#"
+        query IntrospectionQuery {
+          __schema {
+            queryType {
+              name
+            }
+            mutationType {
+              name
+            }
+            subscriptionType {
+              name
+            }
+            types {
+              ...FullType
+            }
+            directives {
+              name
+              description
+              locations
+              args {
+                ...InputValue
+              }
+            }
+          }
+        }
+
+        fragment FullType on __Type {
+          kind
+          name
+          description
+          fields(includeDeprecated: true) {
+            name
+            description
+            args {
+              ...InputValue
+            }
+            type {
+              ...TypeRef
+            }
+            isDeprecated
+            deprecationReason
+          }
+          inputFields {
+            ...InputValue
+          }
+          interfaces {
+            ...TypeRef
+          }
+          enumValues(includeDeprecated: true) {
+            name
+            description
+            isDeprecated
+            deprecationReason
+          }
+          possibleTypes {
+            ...TypeRef
+          }
+        }
+
+        fragment InputValue on __InputValue {
+          name
+          description
+          type {
+            ...TypeRef
+          }
+          defaultValue
+        }
+
+        fragment TypeRef on __Type {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+            }
+          }
+        }
+    "#]]
+    .assert_eq(&IntrospectionQueryOptions::new().type_depth(2).source_text());
+    let all = IntrospectionQueryOptions::new()
+        .descriptions(false)
+        .specified_by_url(true)
+        .directive_is_repeatable(true)
+        .schema_description(true)
+        .input_value_deprecation(true)
+        .one_of(true)
+        .type_depth(1);
+    expect![[r#"
+        query IntrospectionQuery {
+          __schema {
+            description
+            queryType {
+              name
+            }
+            mutationType {
+              name
+            }
+            subscriptionType {
+              name
+            }
+            types {
+              ...FullType
+            }
+            directives {
+              name
+              isRepeatable
+              locations
+              args(includeDeprecated: true) {
+                ...InputValue
+              }
+            }
+          }
+        }
+
+        fragment FullType on __Type {
+          kind
+          name
+          specifiedByURL
+          isOneOf
+          fields(includeDeprecated: true) {
+            name
+            args(includeDeprecated: true) {
+              ...InputValue
+            }
+            type {
+              ...TypeRef
+            }
+            isDeprecated
+            deprecationReason
+          }
+          inputFields(includeDeprecated: true) {
+            ...InputValue
+          }
+          interfaces {
+            ...TypeRef
+          }
+          enumValues(includeDeprecated: true) {
+            name
+            isDeprecated
+            deprecationReason
+          }
+          possibleTypes {
+            ...TypeRef
+          }
+        }
+
+        fragment InputValue on __InputValue {
+          name
+          type {
+            ...TypeRef
+          }
+          defaultValue
+          isDeprecated
+          deprecationReason
+        }
+
+        fragment TypeRef on __Type {
+          kind
+          name
+          ofType {
+            kind
+            name
+          }
+        }
+    "#]]
+    .assert_eq(&all.source_text());
+
+    let sdl = r#"
+        "The schema"
+        schema { query: Query }
+        type Query {
+          field(old: Int @deprecated, by: By): [[[Int!]!]!]!
+        }
+        input By @oneOf { id: ID, name: String @deprecated(reason: "use `id`") }
+        scalar Url @specifiedBy(url: "https://url.spec.whatwg.org/")
+        directive @tag(name: String!) repeatable on FIELD_DEFINITION
+    "#;
+    let schema = Schema::parse_and_validate(sdl, "schema.graphql").unwrap();
+    for options in [
+        IntrospectionQueryOptions::new(),
+        all.type_depth(7).descriptions(true),
+    ] {
+        let document = options.document(&schema);
+        let operation = document.operations.get(None).unwrap();
+        check_introspection_max_depth(&document, operation).unwrap();
+        let variables = coerce_variable_values(&schema, operation, &JsonMap::new()).unwrap();
+        let response = execute_introspection_only_query(&schema, &document, operation, &variables);
+        assert!(response.errors.is_empty());
+        let response = serde_json::from_value(serde_json::to_value(&response).unwrap()).unwrap();
+        let from_introspection = Schema::from_introspection(&response).unwrap();
+        if options == IntrospectionQueryOptions::new() {
+            // Without newer introspection fields, some information is lost
+            assert_ne!(from_introspection.to_string(), schema.to_string());
+        } else {
+            assert_eq!(from_introspection.to_string(), schema.to_string());
+        }
+    }
+}
+
+#[test]
+fn visibility() {
+    let sdl = r#"
+        directive @inaccessible on OBJECT | FIELD_DEFINITION | ARGUMENT_DEFINITION | ENUM_VALUE
+        directive @internal on FIELD_DEFINITION
+
+        type Query {
+          node: Node
+          secret: Secret
+          user(id: ID, legacyId: Int @inaccessible): User
+          status: Status
+        }
+
+        interface Node { id: ID! }
+
+        type User implements Node {
+          id: ID!
+          name: String
+          email: String @internal
+          ssn: String @inaccessible
+        }
+
+        type Secret implements Node @inaccessible {
+          id: ID!
+        }
+
+        enum Status { ACTIVE OLD @inaccessible }
+    "#;
+    let schema = Schema::parse_and_validate(sdl, "schema.graphql").unwrap();
+    let query = r#"
+        {
+          __schema { types { name } }
+          Node: __type(name: "Node") { possibleTypes { name } }
+          Secret: __type(name: "Secret") { name }
+          Query: __type(name: "Query") { fields { name args { name } } }
+          User: __type(name: "User") { fields { name } }
+          Status: __type(name: "Status") { enumValues(includeDeprecated: true) { name } }
+        }
+    "#;
+    let document = ExecutableDocument::parse_and_validate(&schema, query, "query.graphql").unwrap();
+
+    /// Hides `@internal` fields from callers without the admin role
+    struct Roles {
+        admin: bool,
+    }
+
+    impl SchemaVisibility for Roles {
+        fn is_field_visible(
+            &self,
+            _parent_type: &ExtendedType,
+            field: &apollo_compiler::schema::FieldDefinition,
+        ) -> bool {
+            self.admin || !field.directives.has("internal")
+        }
+    }
+
+    let executor = Executor::new().schema_visibility(HiddenByDirective::new(name!("inaccessible")));
+    struct Root;
+
+    impl Resolver for Root {
+        fn type_name(&self) -> &str {
+            "Query"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> Result<ResolvedValue<'a>, ResolverError> {
+            Err(ResolverError::new(format!("unexpected field {field_name}")))
+        }
+    }
+
+    let root = Root;
+    let response = executor.execute_request(&schema, &document, None, &JsonMap::new(), &root);
+    expect![[r#"
+        {
+          "data": {
+            "__schema": {
+              "types": [
+                {
+                  "name": "__Schema"
+                },
+                {
+                  "name": "__Type"
+                },
+                {
+                  "name": "__TypeKind"
+                },
+                {
+                  "name": "__Field"
+                },
+                {
+                  "name": "__InputValue"
+                },
+                {
+                  "name": "__EnumValue"
+                },
+                {
+                  "name": "__Directive"
+                },
+                {
+                  "name": "__DirectiveLocation"
+                },
+                {
+                  "name": "Int"
+                },
+                {
+                  "name": "String"
+                },
+                {
+                  "name": "Boolean"
+                },
+                {
+                  "name": "ID"
+                },
+                {
+                  "name": "Query"
+                },
+                {
+                  "name": "Node"
+                },
+                {
+                  "name": "User"
+                },
+                {
+                  "name": "Status"
+                }
+              ]
+            },
+            "Node": {
+              "possibleTypes": [
+                {
+                  "name": "User"
+                }
+              ]
+            },
+            "Secret": null,
+            "Query": {
+              "fields": [
+                {
+                  "name": "node",
+                  "args": []
+                },
+                {
+                  "name": "user",
+                  "args": [
+                    {
+                      "name": "id"
+                    }
+                  ]
+                },
+                {
+                  "name": "status",
+                  "args": []
+                }
+              ]
+            },
+            "User": {
+              "fields": [
+                {
+                  "name": "id"
+                },
+                {
+                  "name": "name"
+                },
+                {
+                  "name": "email"
+                }
+              ]
+            },
+            "Status": {
+              "enumValues": [
+                {
+                  "name": "ACTIVE"
+                }
+              ]
+            }
+          }
+        }"#]]
+    .assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+
+    let executor = Executor::new().schema_visibility(Roles { admin: false });
+    let response = executor.execute_request(&schema, &document, None, &JsonMap::new(), &root);
+    let response = serde_json::to_value(&response).unwrap();
+    expect![[r#"{"fields":[{"name":"id"},{"name":"name"},{"name":"ssn"}]}"#]]
+        .assert_eq(&response["data"]["User"].to_string());
+
+    let query = r#"
+        query Q {
+          user { id ssn email ...F }
+          node { ... on Secret { id } }
+        }
+        fragment F on User { name ssn }
+        fragment S on Secret { id }
+        query R { secret { ...S } }
+    "#;
+    let document = ExecutableDocument::parse(&schema, query, "query.graphql").unwrap();
+    document.clone().validate(&schema).unwrap();
+    let errors = document
+        .validate_with_visibility(&schema, &HiddenByDirective::new(name!("inaccessible")))
+        .unwrap_err()
+        .errors;
+    expect![[r#"
+        Error: type `User` does not have a field `ssn`
+           ╭─[query.graphql:3:21]
+           │
+         3 │           user { id ssn email ...F }
+           │                     ─┬─  
+           │                      ╰─── field `ssn` selected here
+           │
+           ├─[schema.graphql:8:54]
+           │
+         8 │           user(id: ID, legacyId: Int @inaccessible): User
+           │                                                      ──┬─  
+           │                                                        ╰─── type `User` defined here
+           │ 
+           │ Note: path to the field: `query Q → user`
+        ───╯
+        Error: type condition `Secret` of inline fragment is not a type defined in the schema
+           ╭─[query.graphql:4:18]
+           │
+         4 │           node { ... on Secret { id } }
+           │                  ──────────┬─────────  
+           │                            ╰─────────── type condition here
+           │ 
+           │ Note: path to the inline fragment: `query Q → node → ...`
+        ───╯
+        Error: type `User` does not have a field `ssn`
+           ╭─[query.graphql:6:35]
+           │
+         6 │         fragment F on User { name ssn }
+           │                       ──┬─        ─┬─  
+           │                         ╰────────────── type `User` defined here
+           │                                    │   
+           │                                    ╰─── field `ssn` selected here
+           │ 
+           │ Note: path to the field: `fragment F`
+        ───╯
+        Error: type condition `Secret` of fragment `S` is not a type defined in the schema
+           ╭─[query.graphql:7:9]
+           │
+         7 │         fragment S on Secret { id }
+           │         ─────────────┬─────────────  
+           │                      ╰─────────────── type condition here
+        ───╯
+        Error: type `Query` does not have a field `secret`
+           ╭─[query.graphql:8:19]
+           │
+         8 │         query R { secret { ...S } }
+           │                   ───┬──  
+           │                      ╰──── field `secret` selected here
+           │ 
+           │ Note: path to the field: `query R`
+        ───╯
+    "#]]
+    .assert_eq(&errors.to_string());
+}
@@ -1,4 +1,7 @@
+mod authorization;
+mod cost;
 mod executable;
+mod execution;
 mod extensions;
 mod field_set;
 mod field_type;
@@ -10,6 +13,7 @@ mod merge_schemas;
 /// Formerly in src/lib.rs
 mod misc;
 mod name;
+mod operation_split;
 mod parser;
 mod schema;
 mod serde;
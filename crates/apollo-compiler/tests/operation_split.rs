@@ -0,0 +1,183 @@
+use apollo_compiler::coordinate::TypeAttributeCoordinate;
+use apollo_compiler::execution::execute_request;
+use apollo_compiler::execution::JsonMap;
+use apollo_compiler::execution::OperationSplit;
+use apollo_compiler::execution::ResolvedValue;
+use apollo_compiler::execution::Resolver;
+use apollo_compiler::execution::ResolverError;
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+
+const SCHEMA: &str = r#"
+    type Query {
+        local: Int
+        remote(n: Int): Int
+        query: Query
+    }
+"#;
+
+#[test]
+fn test_none_and_only() {
+    let doc = r#"
+        query TheOperation { local }
+        query OtherOperation { remote }
+    "#;
+    assert_split(doc, expect!["None"]);
+    let doc = r#"
+        query TheOperation { remote __typename }
+    "#;
+    assert_split(doc, expect!["Only"]);
+}
+
+#[test]
+fn test_both() {
+    let doc = r#"
+        query TheOperation($n: Int, $skip: Boolean!) {
+            local
+            ...F
+            ... @skip(if: $skip) { remote(n: $n) query { local } }
+        }
+        fragment F on Query { local: local r: remote(n: 1) }
+    "#;
+    let expected = expect![[r#"
+        Matching parts:
+        query TheOperation($n: Int, $skip: Boolean!) {
+          ...F
+          ... @skip(if: $skip) {
+            remote(n: $n)
+            query {
+              local
+            }
+          }
+        }
+
+        fragment F on Query {
+          r: remote(n: 1)
+        }
+
+        Other parts:
+        query TheOperation {
+          local
+          ...F
+        }
+
+        fragment F on Query {
+          local: local
+        }
+    "#]];
+    assert_split(doc, expected);
+}
+
+#[test]
+fn test_nested_fragment() {
+    let doc = r#"
+        query TheOperation { ...F query { ...F } }
+        fragment F on Query { local remote }
+    "#;
+    let expected = expect![[r#"
+        GraphQLError {
+            message: "Fragment F has top-level fields in both parts of the split and cannot also be used nested in other fields",
+            locations: [
+                3:9,
+            ],
+            path: [],
+            extensions: {},
+        }
+    "#]];
+    assert_split(doc, expected);
+
+    // Fine if the fragment is not split
+    let doc = r#"
+        query TheOperation { remote ...F query { ...F } }
+        fragment F on Query { local }
+    "#;
+    let expected = expect![[r#"
+        Matching parts:
+        query TheOperation {
+          remote
+          query {
+            ...F
+          }
+        }
+
+        fragment F on Query {
+          local
+        }
+
+        Other parts:
+        query TheOperation {
+          ...F
+        }
+
+        fragment F on Query {
+          local
+        }
+    "#]];
+    assert_split(doc, expected);
+}
+
+#[test]
+fn test_execute_and_merge() {
+    struct Root {
+        remote: bool,
+    }
+
+    impl Resolver for Root {
+        fn type_name(&self) -> &str {
+            "Query"
+        }
+
+        fn resolve_field<'a>(
+            &'a self,
+            field_name: &'a str,
+            _arguments: &'a JsonMap,
+        ) -> Result<ResolvedValue<'a>, ResolverError> {
+            match (field_name, self.remote) {
+                ("local", false) => Ok(ResolvedValue::leaf(1)),
+                ("remote", true) => Ok(ResolvedValue::leaf(2)),
+                _ => Err(ResolverError::new(format!("unexpected field {field_name}"))),
+            }
+        }
+    }
+
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let doc = ExecutableDocument::parse_and_validate(
+        &schema,
+        "{ a: remote local b: remote }",
+        "doc.graphql",
+    )
+    .unwrap();
+    let operation = doc.operations.get(None).unwrap();
+    let OperationSplit::Both { matching, other } =
+        OperationSplit::split(&schema, &doc, operation, |coord| {
+            coord.attribute == "remote"
+        })
+        .unwrap()
+    else {
+        panic!("expected both parts")
+    };
+    let vars = JsonMap::new();
+    let remote_response = execute_request(&schema, &matching, None, &vars, &Root { remote: true });
+    let local_response = execute_request(&schema, &other, None, &vars, &Root { remote: false });
+    let response = remote_response.merge(local_response);
+    expect![[r#"{"data":{"a":2,"b":2,"local":1}}"#]]
+        .assert_eq(&serde_json::to_string(&response).unwrap());
+}
+
+#[track_caller]
+fn assert_split(doc: &str, expected: expect_test::Expect) {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let doc = ExecutableDocument::parse_and_validate(&schema, doc, "doc.graphql").unwrap();
+    let operation = doc.operations.get(Some("TheOperation")).unwrap();
+
+    let is_remote = |coord: &TypeAttributeCoordinate| coord.to_string() != "Query.local";
+    match OperationSplit::split(&schema, &doc, operation, is_remote) {
+        Ok(OperationSplit::None) => expected.assert_eq("None"),
+        Ok(OperationSplit::Only) => expected.assert_eq("Only"),
+        Ok(OperationSplit::Both { matching, other }) => expected.assert_eq(&format!(
+            "Matching parts:\n{matching}\nOther parts:\n{other}"
+        )),
+        Err(err) => expected.assert_debug_eq(&err.into_graphql_error(&doc.sources)),
+    }
+}
@@ -1,4 +1,5 @@
 use apollo_compiler::Schema;
+use expect_test::expect;
 
 #[test]
 fn find_definitions_with_directive() {
@@ -226,3 +227,105 @@ fn test_default_root_op_name_ignored_with_explicit_schema_def() {
     let schema = Schema::parse_and_validate(input, "schema.graphql").unwrap();
     assert!(schema.schema_definition.mutation.is_none())
 }
+
+#[test]
+fn content_hash() {
+    let hash = |source: &str| {
+        Schema::parse_and_validate(source, "schema.graphql")
+            .unwrap()
+            .content_hash()
+    };
+    let reference = hash(
+        r#"
+        directive @tag(name: String, size: Int = 1) repeatable on OBJECT | FIELD_DEFINITION
+        interface Node { id: ID! }
+        "A user"
+        type User implements Node @tag(name: "a") @tag(name: "b") {
+          id: ID!
+          posts(first: Int = 10, after: String): [Post]
+        }
+        type Post implements Node { id: ID! }
+        union Entity = User | Post
+        enum Role { ADMIN USER }
+        input Filter { role: Role, limit: Float = 1.5 }
+        type Query { user(filter: Filter = {role: ADMIN, limit: 2.0}): User entity: Entity }
+        "#,
+    );
+    expect!["71b6ae7d6064fad8a2842a40f3c5e136e975f547250386616125a7f9afe0b0a7"]
+        .assert_eq(&reference);
+
+    // Formatting, comments, definition order, extensions,
+    // and the order of fields, arguments, and members don’t matter
+    let equivalent = hash(
+        r#"
+        # Comment
+        type Query { entity: Entity }
+        extend type Query { user(filter: Filter = {limit: 2.00, role: ADMIN}): User }
+        input Filter { limit: Float = 1.50, role: Role }
+        enum Role { USER ADMIN }
+        union Entity = Post | User
+        type Post implements Node { id: ID! }
+        "A user"
+        type User @tag(name: "a") {
+          posts(after: String, first: Int = 10): [Post]
+        }
+        extend type User implements Node @tag(name: "b") { id: ID! }
+        interface Node { id: ID! }
+        directive @tag(size: Int = 1, name: String) repeatable on FIELD_DEFINITION | OBJECT
+        "#,
+    );
+    assert_eq!(equivalent, reference);
+
+    // Descriptions, default values, and the order of directive applications do
+    let different = hash(
+        r#"
+        directive @tag(name: String, size: Int = 1) repeatable on OBJECT | FIELD_DEFINITION
+        interface Node { id: ID! }
+        "A user"
+        type User implements Node @tag(name: "b") @tag(name: "a") {
+          id: ID!
+          posts(first: Int = 10, after: String): [Post]
+        }
+        type Post implements Node { id: ID! }
+        union Entity = User | Post
+        enum Role { ADMIN USER }
+        input Filter { role: Role, limit: Float = 1.5 }
+        type Query { user(filter: Filter = {role: ADMIN, limit: 2.0}): User entity: Entity }
+        "#,
+    );
+    assert_ne!(different, reference);
+    let different = hash(
+        r#"
+        directive @tag(name: String, size: Int = 1) repeatable on OBJECT | FIELD_DEFINITION
+        interface Node { id: ID! }
+        "A person"
+        type User implements Node @tag(name: "a") @tag(name: "b") {
+          id: ID!
+          posts(first: Int = 10, after: String): [Post]
+        }
+        type Post implements Node { id: ID! }
+        union Entity = User | Post
+        enum Role { ADMIN USER }
+        input Filter { role: Role, limit: Float = 1.5 }
+        type Query { user(filter: Filter = {role: ADMIN, limit: 2.0}): User entity: Entity }
+        "#,
+    );
+    assert_ne!(different, reference);
+    let different = hash(
+        r#"
+        directive @tag(name: String, size: Int = 1) repeatable on OBJECT | FIELD_DEFINITION
+        interface Node { id: ID! }
+        "A user"
+        type User implements Node @tag(name: "a") @tag(name: "b") {
+          id: ID!
+          posts(first: Int = 20, after: String): [Post]
+        }
+        type Post implements Node { id: ID! }
+        union Entity = User | Post
+        enum Role { ADMIN USER }
+        input Filter { role: Role, limit: Float = 1.5 }
+        type Query { user(filter: Filter = {role: ADMIN, limit: 2.0}): User entity: Entity }
+        "#,
+    );
+    assert_ne!(different, reference);
+}
@@ -0,0 +1,254 @@
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+use expect_test::Expect;
+
+const SCHEMA: &str = r#"
+    type Query {
+        list: [Item]
+        item: Item
+    }
+
+    type Subscription {
+        items: [Item]
+    }
+
+    type Item {
+        id: ID!
+        children: [Item!]!
+    }
+"#;
+
+fn assert_errors(query: &str, expected: Expect) {
+    let schema = Schema::builder()
+        .enable_incremental_delivery()
+        .parse(SCHEMA, "schema.graphql")
+        .build()
+        .unwrap()
+        .validate()
+        .unwrap();
+    let errors = match ExecutableDocument::parse_and_validate(&schema, query, "query.graphql") {
+        Ok(_) => String::new(),
+        Err(with_errors) => with_errors.errors.to_string(),
+    };
+    expected.assert_eq(&errors);
+}
+
+#[test]
+fn directives_are_opt_in() {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    assert!(!schema.directive_definitions.contains_key("defer"));
+    assert!(!schema.directive_definitions.contains_key("stream"));
+    let doc = "{ ... @defer { item { id } } }";
+    let errors = ExecutableDocument::parse_and_validate(&schema, doc, "query.graphql")
+        .unwrap_err()
+        .errors
+        .to_string();
+    assert!(
+        errors.contains("cannot find directive `@defer`"),
+        "{errors}"
+    );
+
+    let schema = Schema::builder()
+        .enable_incremental_delivery()
+        .parse(SCHEMA, "schema.graphql")
+        .build()
+        .unwrap();
+    assert!(schema.directive_definitions["defer"].is_built_in());
+    assert!(schema.directive_definitions["stream"].is_built_in());
+    // Built-in definitions are not serialized
+    assert!(!schema.to_string().contains("@defer"));
+}
+
+#[test]
+fn valid() {
+    assert_errors(
+        r#"
+        query {
+            ... @defer(label: "a") { item { id } }
+            list @stream(label: "b", initialCount: 1) {
+                ...Children @defer(label: "c")
+            }
+        }
+        fragment Children on Item {
+            children @stream { id }
+        }
+        subscription {
+            items {
+                ... @defer { id }
+            }
+        }
+        "#,
+        expect![[r#"
+            Error: anonymous operation cannot be selected when the document contains other operations
+               ╭─[query.graphql:2:9]
+               │
+             2 │ ╭─▶         query {
+               ┆ ┆   
+             7 │ ├─▶         }
+               │ │               
+               │ ╰─────────────── provide a name for this definition
+               │     
+               │     Help: GraphQL requires operations to be named if the document has more than one
+            ───╯
+            Error: anonymous operation cannot be selected when the document contains other operations
+                ╭─[query.graphql:11:9]
+                │
+             11 │ ╭─▶         subscription {
+                ┆ ┆   
+             15 │ ├─▶         }
+                │ │               
+                │ ╰─────────────── provide a name for this definition
+                │     
+                │     Help: GraphQL requires operations to be named if the document has more than one
+            ────╯
+        "#]],
+    );
+}
+
+#[test]
+fn unique_labels() {
+    assert_errors(
+        r#"
+        query A($label: String) {
+            ... @defer(label: "a") { item { id } }
+            list @stream(label: "a") { id }
+            item { ... @defer(label: $label) { id } }
+        }
+        query B {
+            ...F
+        }
+        fragment F on Query {
+            ... @defer(label: "a") { item { id } }
+        }
+        "#,
+        expect![[r#"
+            Error: the label `a` is used by multiple `@defer` or `@stream` directives
+               ╭─[query.graphql:4:33]
+               │
+             3 │             ... @defer(label: "a") { item { id } }
+               │                               ─┬─  
+               │                                ╰─── label `a` first used here
+             4 │             list @stream(label: "a") { id }
+               │                                 ─┬─  
+               │                                  ╰─── label `a` used again here
+               │ 
+               │ Help: labels identify deferred or streamed results, so they must be unique in the document
+            ───╯
+            Error: the `label` argument of `@defer` must be a static string
+               ╭─[query.graphql:5:38]
+               │
+             5 │             item { ... @defer(label: $label) { id } }
+               │                                      ───┬──  
+               │                                         ╰──── label provided here
+               │ 
+               │ Help: use a string literal instead of a variable
+            ───╯
+            Error: the label `a` is used by multiple `@defer` or `@stream` directives
+                ╭─[query.graphql:11:31]
+                │
+              3 │             ... @defer(label: "a") { item { id } }
+                │                               ─┬─  
+                │                                ╰─── label `a` first used here
+                │ 
+             11 │             ... @defer(label: "a") { item { id } }
+                │                               ─┬─  
+                │                                ╰─── label `a` used again here
+                │ 
+                │ Help: labels identify deferred or streamed results, so they must be unique in the document
+            ────╯
+        "#]],
+    );
+}
+
+#[test]
+fn stream_on_non_list_field() {
+    assert_errors(
+        r#"
+        {
+            item @stream { id }
+            list { id @stream }
+        }
+        "#,
+        expect![[r#"
+            Error: `@stream` cannot be used on non-list field `Query.item`
+               ╭─[query.graphql:3:18]
+               │
+             3 │             item @stream { id }
+               │                  ───┬───  
+               │                     ╰───── directive used here
+               │
+               ├─[schema.graphql:4:9]
+               │
+             4 │         item: Item
+               │         ─────┬────  
+               │              ╰────── `Query.item` has type `Item` defined here
+            ───╯
+            Error: `@stream` cannot be used on non-list field `Item.id`
+                ╭─[query.graphql:4:23]
+                │
+              4 │             list { id @stream }
+                │                       ───┬───  
+                │                          ╰───── directive used here
+                │
+                ├─[schema.graphql:12:9]
+                │
+             12 │         id: ID!
+                │         ───┬───  
+                │            ╰───── `Item.id` has type `ID!` defined here
+            ────╯
+        "#]],
+    );
+}
+
+#[test]
+fn subscription_root() {
+    assert_errors(
+        r#"
+        subscription A {
+            items @stream { id }
+        }
+        subscription B {
+            ... @defer { items { id } }
+        }
+        subscription C {
+            ...F @defer
+        }
+        fragment F on Subscription {
+            items { id }
+        }
+        subscription D {
+            items @stream(if: false) { id }
+        }
+        "#,
+        expect![[r#"
+            Error: `@stream` cannot be used on root fields of a subscription operation
+               ╭─[query.graphql:3:19]
+               │
+             3 │             items @stream { id }
+               │                   ───┬───  
+               │                      ╰───── `@stream` used within a selection on `Subscription`
+               │ 
+               │ Help: a subscription sends one response per event, which cannot be split into incremental payloads
+            ───╯
+            Error: `@defer` cannot be used on root fields of a subscription operation
+               ╭─[query.graphql:6:17]
+               │
+             6 │             ... @defer { items { id } }
+               │                 ───┬──  
+               │                    ╰──── `@defer` used within a selection on `Subscription`
+               │ 
+               │ Help: a subscription sends one response per event, which cannot be split into incremental payloads
+            ───╯
+            Error: `@defer` cannot be used on root fields of a subscription operation
+               ╭─[query.graphql:9:18]
+               │
+             9 │             ...F @defer
+               │                  ───┬──  
+               │                     ╰──── `@defer` used within a selection on `Subscription`
+               │ 
+               │ Help: a subscription sends one response per event, which cannot be split into incremental payloads
+            ───╯
+        "#]],
+    );
+}
@@ -1,7 +1,10 @@
+mod defer_stream;
 mod field_merging;
 mod interface;
 mod object;
+mod one_of;
 mod operation;
+mod operation_limits;
 mod recursion;
 mod types;
 mod variable;
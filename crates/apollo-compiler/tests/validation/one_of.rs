@@ -0,0 +1,222 @@
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+use expect_test::Expect;
+
+const SCHEMA: &str = r#"
+    type Query {
+        pet(by: PetBy!): String
+        pets(by: [PetBy!]): [String]
+    }
+
+    input PetBy @oneOf {
+        id: ID
+        name: String
+        owner: OwnerBy
+    }
+
+    input OwnerBy @oneOf {
+        id: ID
+        email: String
+    }
+"#;
+
+fn assert_errors(query: &str, expected: Expect) {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let errors = match ExecutableDocument::parse_and_validate(&schema, query, "query.graphql") {
+        Ok(_) => String::new(),
+        Err(with_errors) => with_errors.errors.to_string(),
+    };
+    expected.assert_eq(&errors);
+}
+
+#[test]
+fn built_in_directive() {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    assert!(schema.directive_definitions["oneOf"].is_built_in());
+    assert!(schema.get_input_object("PetBy").unwrap().is_one_of());
+    // Built-in definitions are not serialized
+    assert!(!schema.to_string().contains("directive @oneOf"));
+}
+
+#[test]
+fn schema_definition() {
+    let schema = r#"
+        type Query {
+            pet(by: PetBy): String
+        }
+
+        input PetBy @oneOf {
+            id: ID!
+            name: String = "Fido"
+            nickname: String
+        }
+    "#;
+    let errors = Schema::parse_and_validate(schema, "schema.graphql")
+        .unwrap_err()
+        .errors
+        .to_string();
+    let expected = expect![[r#"
+        Error: field `PetBy.id` of a oneOf input object must be nullable
+           ╭─[schema.graphql:7:17]
+           │
+         7 │             id: ID!
+           │                 ─┬─  
+           │                  ╰─── field declared with non-null type `ID!`
+           │ 
+           │ Help: exactly one field of a oneOf input object is given a value, so all of its fields must be nullable
+        ───╯
+        Error: field `PetBy.name` of a oneOf input object cannot have a default value
+           ╭─[schema.graphql:8:13]
+           │
+         8 │             name: String = "Fido"
+           │             ──────────┬───────┬──  
+           │                       ╰──────────── field defined here
+           │                               │    
+           │                               ╰──── remove this default value
+        ───╯
+    "#]];
+    expected.assert_eq(&errors);
+}
+
+#[test]
+fn valid() {
+    assert_errors(
+        r#"
+        query($id: ID!, $by: PetBy, $owner: OwnerBy!) {
+            a: pet(by: { id: 1 })
+            b: pet(by: { owner: { email: "alice@example.com" } })
+            c: pet(by: { id: $id })
+            d: pet(by: $by)
+            e: pet(by: { owner: $owner })
+            f: pets(by: [{ name: "Fido" }, { id: 2 }])
+        }
+        "#,
+        expect![[r#"
+            Error: variable `$by` of type `PetBy` cannot be used for argument `by` of type `PetBy!`
+               ╭─[query.graphql:6:20]
+               │
+             2 │         query($id: ID!, $by: PetBy, $owner: OwnerBy!) {
+               │                         ─────┬────  
+               │                              ╰────── variable `$by` of type `PetBy` is declared here
+               │ 
+             6 │             d: pet(by: $by)
+               │                    ───┬───  
+               │                       ╰───── variable `$by` used here
+            ───╯
+        "#]],
+    );
+}
+
+#[test]
+fn field_count() {
+    assert_errors(
+        r#"
+        query {
+            a: pet(by: {})
+            b: pet(by: { id: 1, name: "Fido" })
+            c: pets(by: [{ name: "Fido" }, { owner: { id: 1, email: "a" } }])
+        }
+        "#,
+        expect![[r#"
+            Error: oneOf input object `PetBy` must specify exactly one field
+               ╭─[query.graphql:3:24]
+               │
+             3 │             a: pet(by: {})
+               │                        ─┬  
+               │                         ╰── 0 fields specified here
+               │
+               ├─[schema.graphql:7:17]
+               │
+             7 │     input PetBy @oneOf {
+               │                 ───┬──  
+               │                    ╰──── `PetBy` is marked with `@oneOf` here
+            ───╯
+            Error: oneOf input object `PetBy` must specify exactly one field
+               ╭─[query.graphql:4:24]
+               │
+             4 │             b: pet(by: { id: 1, name: "Fido" })
+               │                        ───────────┬───────────  
+               │                                   ╰───────────── 2 fields specified here
+               │
+               ├─[schema.graphql:7:17]
+               │
+             7 │     input PetBy @oneOf {
+               │                 ───┬──  
+               │                    ╰──── `PetBy` is marked with `@oneOf` here
+            ───╯
+            Error: oneOf input object `OwnerBy` must specify exactly one field
+                ╭─[query.graphql:5:53]
+                │
+              5 │             c: pets(by: [{ name: "Fido" }, { owner: { id: 1, email: "a" } }])
+                │                                                     ──────────┬──────────  
+                │                                                               ╰──────────── 2 fields specified here
+                │
+                ├─[schema.graphql:13:19]
+                │
+             13 │     input OwnerBy @oneOf {
+                │                   ───┬──  
+                │                      ╰──── `OwnerBy` is marked with `@oneOf` here
+            ────╯
+        "#]],
+    );
+}
+
+#[test]
+fn null_field() {
+    assert_errors(
+        r#"
+        query {
+            pet(by: { name: null })
+        }
+        "#,
+        expect![[r#"
+            Error: field `PetBy.name` of a oneOf input object cannot be null
+               ╭─[query.graphql:3:29]
+               │
+             3 │             pet(by: { name: null })
+               │                             ──┬─  
+               │                               ╰─── null value provided here
+            ───╯
+        "#]],
+    );
+}
+
+#[test]
+fn nullable_variable() {
+    assert_errors(
+        r#"
+        query($name: String, $owner: OwnerBy = { id: 1 }) {
+            a: pet(by: { name: $name })
+            b: pet(by: { owner: $owner })
+        }
+        "#,
+        expect![[r#"
+            Error: variable `$name` of nullable type `String` cannot be used for oneOf input field `PetBy.name`
+               ╭─[query.graphql:3:32]
+               │
+             2 │         query($name: String, $owner: OwnerBy = { id: 1 }) {
+               │               ──────┬──────  
+               │                     ╰──────── `$name` declared here
+             3 │             a: pet(by: { name: $name })
+               │                                ──┬──  
+               │                                  ╰──── variable used here
+               │ 
+               │ Help: the variable could be null, which a oneOf input object does not allow
+            ───╯
+            Error: variable `$owner` of nullable type `OwnerBy` cannot be used for oneOf input field `PetBy.owner`
+               ╭─[query.graphql:4:33]
+               │
+             2 │         query($name: String, $owner: OwnerBy = { id: 1 }) {
+               │                              ─────────────┬─────────────  
+               │                                           ╰─────────────── `$owner` declared here
+               │ 
+             4 │             b: pet(by: { owner: $owner })
+               │                                 ───┬──  
+               │                                    ╰──── variable used here
+               │ 
+               │ Help: the variable could be null, which a oneOf input object does not allow
+            ───╯
+        "#]],
+    );
+}
@@ -0,0 +1,197 @@
+use apollo_compiler::execution::Response;
+use apollo_compiler::validation::OperationLimits;
+use apollo_compiler::ExecutableDocument;
+use apollo_compiler::Schema;
+use expect_test::expect;
+use expect_test::Expect;
+
+const SCHEMA: &str = r#"
+    directive @a on FIELD
+    directive @b on FIELD
+    directive @c on FIELD
+
+    type Query {
+        me: User
+        user(id: ID!): User
+    }
+
+    type User {
+        name: String
+        friends: [User]
+    }
+"#;
+
+fn assert_errors(query: &str, limits: OperationLimits, expected: Expect) {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let document = ExecutableDocument::parse(&schema, query, "query.graphql").unwrap();
+    let errors = match document.validate_with_limits(&schema, &limits) {
+        Ok(_) => String::new(),
+        Err(with_errors) => with_errors.errors.to_string(),
+    };
+    expected.assert_eq(&errors);
+}
+
+#[test]
+fn no_limits_by_default() {
+    assert_errors(
+        "{ a: me { friends { friends { name } } } b: me { name @a @b @c } }",
+        OperationLimits::new(),
+        expect![[""]],
+    );
+}
+
+#[test]
+fn max_depth() {
+    let query = r#"
+        query Q { me { ...F } }
+        fragment F on User { friends { friends { name } } }
+    "#;
+    assert_errors(query, OperationLimits::new().max_depth(4), expect![[""]]);
+    assert_errors(
+        query,
+        OperationLimits::new().max_depth(3),
+        expect![[r#"
+        Error: the operation has a selection depth of 4, exceeding the limit of 3
+           ╭─[query.graphql:3:50]
+           │
+         2 │         query Q { me { ...F } }
+           │         ───────────┬───────────  
+           │                    ╰───────────── in this operation
+         3 │         fragment F on User { friends { friends { name } } }
+           │                                                  ──┬─  
+           │                                                    ╰─── field at depth 4
+        ───╯
+    "#]],
+    );
+}
+
+#[test]
+fn max_aliases() {
+    let query = r#"
+        query Q { a: me { ...F } b: me { ...F } }
+        fragment F on User { n: name }
+    "#;
+    assert_errors(query, OperationLimits::new().max_aliases(4), expect![[""]]);
+    assert_errors(
+        query,
+        OperationLimits::new().max_aliases(3),
+        expect![[r#"
+        Error: the operation uses 4 aliases, exceeding the limit of 3
+           ╭─[query.graphql:2:9]
+           │
+         2 │         query Q { a: me { ...F } b: me { ...F } }
+           │         ────────────────────┬────────────────────  
+           │                             ╰────────────────────── 4 aliases used here
+        ───╯
+    "#]],
+    );
+}
+
+#[test]
+fn max_root_fields() {
+    let query = r#"
+        query Q { me { name } ... { a: me { name } } ...F }
+        fragment F on Query { b: me { name } }
+    "#;
+    assert_errors(
+        query,
+        OperationLimits::new().max_root_fields(3),
+        expect![[""]],
+    );
+    assert_errors(
+        query,
+        OperationLimits::new().max_root_fields(2),
+        expect![[r#"
+            Error: the operation selects 3 root fields, exceeding the limit of 2
+               ╭─[query.graphql:2:9]
+               │
+             2 │         query Q { me { name } ... { a: me { name } } ...F }
+               │         ─────────────────────────┬─────────────────────────  
+               │                                  ╰─────────────────────────── 3 root fields selected here
+            ───╯
+        "#]],
+    );
+}
+
+#[test]
+fn max_directives_per_field() {
+    let query = "{ me @a @b { name @a @b @c } }";
+    assert_errors(
+        query,
+        OperationLimits::new().max_directives_per_field(3),
+        expect![[""]],
+    );
+    assert_errors(
+        query,
+        OperationLimits::new().max_directives_per_field(2),
+        expect![[r#"
+            Error: the field `name` has 3 directives, exceeding the limit of 2
+               ╭─[query.graphql:1:14]
+               │
+             1 │ { me @a @b { name @a @b @c } }
+               │              ──────┬──────  
+               │                    ╰──────── 3 directives used here
+            ───╯
+        "#]],
+    );
+}
+
+#[test]
+fn max_fields() {
+    // Each level doubles the number of fields after expanding fragments: 1 + 2 × (1 + 2 × (…))
+    let query = r#"
+        query Q { me { ...F1 } }
+        fragment F1 on User { a: friends { ...F2 } b: friends { ...F2 } }
+        fragment F2 on User { a: friends { ...F3 } b: friends { ...F3 } }
+        fragment F3 on User { a: friends { ...F4 } b: friends { ...F4 } }
+        fragment F4 on User { a: friends { name } b: friends { name } }
+    "#;
+    assert_errors(query, OperationLimits::new().max_fields(47), expect![[""]]);
+    assert_errors(
+        query,
+        OperationLimits::new().max_fields(46),
+        expect![[r#"
+        Error: the operation selects 47 fields, exceeding the limit of 46
+           ╭─[query.graphql:2:9]
+           │
+         2 │         query Q { me { ...F1 } }
+           │         ────────────┬───────────  
+           │                     ╰───────────── 47 fields selected here
+           │ 
+           │ Help: fields of fragments are counted each time the fragment is used
+        ───╯
+    "#]],
+    );
+}
+
+#[test]
+fn into_response() {
+    let schema = Schema::parse_and_validate(SCHEMA, "schema.graphql").unwrap();
+    let limits = OperationLimits::new().max_depth(1).max_root_fields(1);
+    let errors = ExecutableDocument::parse(&schema, "{ me { name } a: me { name } }", "q.graphql")
+        .unwrap()
+        .validate_with_limits(&schema, &limits)
+        .unwrap_err()
+        .errors;
+    let names = errors
+        .iter()
+        .map(|diagnostic| diagnostic.error.unstable_error_name().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["MaxRootFieldsExceeded", "MaxDepthExceeded"]);
+    let response = Response::from_request_error(errors.iter().next().unwrap().to_json());
+    let expected = expect![[r#"
+        {
+          "errors": [
+            {
+              "message": "the operation selects 2 root fields, exceeding the limit of 1",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 1
+                }
+              ]
+            }
+          ]
+        }"#]];
+    expected.assert_eq(&serde_json::to_string_pretty(&response).unwrap());
+}